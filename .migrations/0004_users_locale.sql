@@ -0,0 +1,3 @@
+ALTER TABLE users ADD COLUMN IF NOT EXISTS locale VARCHAR(16);
+
+ALTER TABLE deleted_users ADD COLUMN IF NOT EXISTS locale VARCHAR(16);
@@ -0,0 +1,21 @@
+CREATE TABLE IF NOT EXISTS email_outbox (
+    id               UUID PRIMARY KEY NOT NULL,
+    idempotency_key  TEXT NOT NULL UNIQUE,
+
+    to_email         VARCHAR(320) NOT NULL,
+    subject          TEXT NOT NULL,
+    text_body        TEXT NOT NULL,
+    html_body        TEXT NOT NULL,
+
+    -- pending, sent or dead
+    status           VARCHAR(16) NOT NULL DEFAULT 'pending',
+    attempts         INTEGER NOT NULL DEFAULT 0,
+    last_error       TEXT,
+
+    created_at       TIMESTAMPTZ NOT NULL,
+    next_attempt_at  TIMESTAMPTZ NOT NULL,
+    sent_at          TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_email_outbox_pending ON email_outbox(next_attempt_at) WHERE status = 'pending';
+CREATE INDEX IF NOT EXISTS idx_email_outbox_status ON email_outbox(status);
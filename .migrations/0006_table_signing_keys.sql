@@ -0,0 +1,8 @@
+-- keys signing the session cookies, the newest active key signs new cookies
+-- the key derived from `SECRET_KEY` has the id '0' and is only stored once retired
+CREATE TABLE IF NOT EXISTS signing_keys (
+    id          VARCHAR(16) PRIMARY KEY NOT NULL,
+    secret      BYTEA NOT NULL,
+    created_at  TIMESTAMPTZ NOT NULL,
+    retired_at  TIMESTAMPTZ
+);
@@ -0,0 +1,6 @@
+ALTER TABLE sessions ADD COLUMN IF NOT EXISTS login_at TIMESTAMPTZ;
+ALTER TABLE sessions ADD COLUMN IF NOT EXISTS persistent BOOLEAN NOT NULL DEFAULT TRUE;
+
+-- existing sessions were all created with "remember me"
+UPDATE sessions SET login_at = created_at WHERE login_at IS NULL;
+ALTER TABLE sessions ALTER COLUMN login_at SET NOT NULL;
@@ -0,0 +1,7 @@
+-- deleted accounts can be restored until `purge_at`, NULL for the ones deleted before
+ALTER TABLE deleted_users ADD COLUMN IF NOT EXISTS purge_at TIMESTAMPTZ;
+ALTER TABLE deleted_users ADD COLUMN IF NOT EXISTS restore_token VARCHAR(64);
+
+CREATE INDEX IF NOT EXISTS idx_deleted_users_purge_at ON deleted_users(purge_at);
+CREATE INDEX IF NOT EXISTS idx_deleted_users_email ON deleted_users(email);
+CREATE INDEX IF NOT EXISTS idx_deleted_users_username ON deleted_users(username);
@@ -0,0 +1,5 @@
+ALTER TABLE sessions ADD COLUMN IF NOT EXISTS authenticated_at TIMESTAMPTZ;
+
+-- existing sessions were last authenticated when signing in
+UPDATE sessions SET authenticated_at = login_at WHERE authenticated_at IS NULL;
+ALTER TABLE sessions ALTER COLUMN authenticated_at SET NOT NULL;
@@ -0,0 +1,9 @@
+-- usernames released by renames, they redirect to the current username of their last owner
+CREATE TABLE IF NOT EXISTS username_history (
+    username     VARCHAR(32) NOT NULL,
+    user_id      UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    released_at  TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_username_history_username ON username_history(username, released_at);
+CREATE INDEX IF NOT EXISTS idx_username_history_user_id ON username_history(user_id, released_at);
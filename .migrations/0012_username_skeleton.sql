@@ -0,0 +1,14 @@
+-- the form usernames are unique by, mirrors `shared::validation::username_skeleton`
+CREATE OR REPLACE FUNCTION username_skeleton(username TEXT) RETURNS TEXT AS $$
+    SELECT replace(
+        replace(translate(lower(username), '01аеорсухіοαρχ.', 'olaeopcyxioapx'), 'rn', 'm'),
+        'vv',
+        'w'
+    )
+$$ LANGUAGE SQL IMMUTABLE STRICT PARALLEL SAFE;
+
+-- not unique, accounts created before may already share a skeleton, new usernames are checked
+-- against it before they're set
+CREATE INDEX IF NOT EXISTS idx_users_username_skeleton ON users(username_skeleton(username));
+CREATE INDEX IF NOT EXISTS idx_username_history_skeleton
+    ON username_history(username_skeleton(username), released_at);
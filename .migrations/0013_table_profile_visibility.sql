@@ -0,0 +1,9 @@
+-- who can see the optional fields of a profile, users without a row have the defaults
+CREATE TABLE IF NOT EXISTS profile_visibility (
+    user_id     UUID PRIMARY KEY NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    icon        VARCHAR(16) NOT NULL,
+    banner      VARCHAR(16) NOT NULL,
+    country     VARCHAR(16) NOT NULL,
+    birth_date  VARCHAR(16) NOT NULL,
+    gender      VARCHAR(16) NOT NULL
+);
@@ -0,0 +1,7 @@
+-- user search matches usernames and display names by trigram similarity and substrings, the
+-- extension ships with PostgreSQL but creating it needs the CREATE privilege on the database
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE INDEX IF NOT EXISTS idx_users_username_trgm ON users USING GIN (username gin_trgm_ops);
+CREATE INDEX IF NOT EXISTS idx_users_display_name_trgm ON users USING GIN (display_name gin_trgm_ops);
+CREATE INDEX IF NOT EXISTS idx_users_country ON users(country);
@@ -0,0 +1,2 @@
+-- sent mails no longer keep their bodies, which hold codes and sign-in links
+UPDATE email_outbox SET text_body = '', html_body = '' WHERE status = 'sent';
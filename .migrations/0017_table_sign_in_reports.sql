@@ -0,0 +1,10 @@
+-- single use "this wasn't me" links sent for unfamiliar sign-ins, a row is deleted once its link
+-- is used
+CREATE TABLE IF NOT EXISTS sign_in_reports (
+    nonce          VARCHAR(64) PRIMARY KEY,
+    user_id        UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    unsigned_ssid  UUID NOT NULL,
+    expires_at     TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_sign_in_reports_expires_at ON sign_in_reports(expires_at);
@@ -0,0 +1,19 @@
+-- accounts created before the skeleton check may share a skeleton, all but the oldest of them are
+-- renamed with a suffix taken from their id, the old usernames are kept in their history for
+-- redirects
+WITH clashes AS (
+    SELECT id, username,
+        ROW_NUMBER() OVER (PARTITION BY username_skeleton(username) ORDER BY created, id) AS n
+    FROM users
+), renamed AS (
+    UPDATE users u
+    SET username = rtrim(left(c.username, 11), '.') || '.' || left(replace(u.id::TEXT, '-', ''), 8)
+    FROM clashes c
+    WHERE u.id = c.id AND c.n > 1
+    RETURNING u.id, c.username AS old_username
+)
+INSERT INTO username_history (username, user_id) SELECT old_username, id FROM renamed;
+
+-- usernames that look alike can no longer be set at the same time by concurrent requests
+DROP INDEX IF EXISTS idx_users_username_skeleton;
+CREATE UNIQUE INDEX IF NOT EXISTS users_username_skeleton_key ON users(username_skeleton(username));
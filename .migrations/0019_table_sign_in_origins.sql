@@ -0,0 +1,16 @@
+-- devices and addresses the user signed in from, new sign-ins are compared against them
+--
+-- kept apart from `sessions` so that ending sessions doesn't make the next sign-in look familiar,
+-- the rows of a deleted account are only removed once it's purged
+CREATE TABLE IF NOT EXISTS sign_in_origins (
+    user_id     UUID NOT NULL,
+    user_agent  TEXT NOT NULL DEFAULT '',
+    ip_address  INET NOT NULL,
+    last_seen   TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (user_id, user_agent, ip_address)
+);
+
+INSERT INTO sign_in_origins (user_id, user_agent, ip_address, last_seen)
+SELECT user_id, COALESCE(user_agent, ''), ip_address, MAX(created_at) FROM sessions
+GROUP BY user_id, COALESCE(user_agent, ''), ip_address
+ON CONFLICT DO NOTHING;
@@ -0,0 +1,2 @@
+-- subjects used to start with the code they carried, those are cut down to the rest of the subject
+UPDATE email_outbox SET subject = regexp_replace(subject, '^[0-9]{6} ', '') WHERE subject ~ '^[0-9]{6} ';
@@ -0,0 +1,4 @@
+-- deleting an account moves its row to `deleted_users`, its blocks have to survive that for a
+-- restore, they are removed when the account is purged instead
+ALTER TABLE user_blocks DROP CONSTRAINT IF EXISTS user_blocks_blocker_id_fkey;
+ALTER TABLE user_blocks DROP CONSTRAINT IF EXISTS user_blocks_blocked_id_fkey;
@@ -0,0 +1,3 @@
+-- the released usernames of a deleted account are kept for a restore, they are removed when the
+-- account is purged instead
+ALTER TABLE username_history DROP CONSTRAINT IF EXISTS username_history_user_id_fkey;
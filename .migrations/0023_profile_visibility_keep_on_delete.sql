@@ -0,0 +1,3 @@
+-- the visibility of a deleted account is kept for a restore, it's removed when the account is
+-- purged instead
+ALTER TABLE profile_visibility DROP CONSTRAINT IF EXISTS profile_visibility_user_id_fkey;
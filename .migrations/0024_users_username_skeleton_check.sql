@@ -0,0 +1,44 @@
+-- gives back the usernames 0018 replaced, accounts that already shared a skeleton keep sharing it
+-- as they did before 0018. A username someone else has taken since stays renamed, its owner can
+-- still sign in with the email
+WITH renamed AS (
+    SELECT DISTINCT ON (u.id) u.id, h.username
+    FROM users u
+    JOIN username_history h ON h.user_id = u.id
+    WHERE u.username = rtrim(left(h.username, 11), '.') || '.' || left(replace(u.id::TEXT, '-', ''), 8)
+        AND NOT EXISTS (SELECT 1 FROM users o WHERE o.username = h.username)
+        AND NOT EXISTS (SELECT 1 FROM deleted_users d WHERE d.username = h.username)
+    ORDER BY u.id, h.released_at
+), restored AS (
+    UPDATE users u SET username = r.username FROM renamed r WHERE u.id = r.id
+    RETURNING u.id, u.username
+)
+DELETE FROM username_history h USING restored r
+WHERE h.user_id = r.id AND h.username = r.username;
+
+DROP INDEX IF EXISTS users_username_skeleton_key;
+CREATE INDEX IF NOT EXISTS idx_users_username_skeleton ON users(username_skeleton(username));
+
+-- new usernames are checked against the skeletons of the other accounts when they're written, the
+-- lock makes concurrent writes of look-alike usernames wait for each other so only one gets it
+CREATE OR REPLACE FUNCTION check_username_skeleton() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'UPDATE' AND NEW.username = OLD.username THEN
+        RETURN NEW;
+    END IF;
+    PERFORM pg_advisory_xact_lock(hashtext(username_skeleton(NEW.username)));
+    IF EXISTS (
+        SELECT 1 FROM users
+        WHERE username_skeleton(username) = username_skeleton(NEW.username) AND id <> NEW.id
+    ) THEN
+        RAISE EXCEPTION 'the username looks like a taken one'
+            USING ERRCODE = 'unique_violation', CONSTRAINT = 'users_username_skeleton_key';
+    END IF;
+    RETURN NEW;
+END
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS users_username_skeleton_check ON users;
+CREATE TRIGGER users_username_skeleton_check
+    BEFORE INSERT OR UPDATE OF username ON users
+    FOR EACH ROW EXECUTE FUNCTION check_username_skeleton();
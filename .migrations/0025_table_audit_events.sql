@@ -0,0 +1,14 @@
+-- security relevant changes made to an account, listed in its data export
+--
+-- not tied to `users` so that they survive the grace period of a deleted account, the rows are
+-- removed when the account is purged
+CREATE TABLE IF NOT EXISTS audit_events (
+    id          UUID PRIMARY KEY NOT NULL,
+    user_id     UUID NOT NULL,
+    kind        VARCHAR(32) NOT NULL,
+    user_agent  TEXT,
+    ip_address  INET,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_events_user_id ON audit_events(user_id, created_at);
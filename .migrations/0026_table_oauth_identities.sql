@@ -0,0 +1,15 @@
+-- accounts of OpenID Connect providers linked to a user, as vouched for by the provider
+--
+-- an account signed up before this table only gets its row on its next sign-in with the provider,
+-- the rows of a deleted account are removed when it's purged
+CREATE TABLE IF NOT EXISTS oauth_identities (
+    provider   VARCHAR(32) NOT NULL,
+    subject    TEXT NOT NULL,
+    user_id    UUID NOT NULL,
+    email      VARCHAR(320) NOT NULL,
+    linked_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_used  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (provider, subject)
+);
+
+CREATE INDEX IF NOT EXISTS idx_oauth_identities_user_id ON oauth_identities(user_id);
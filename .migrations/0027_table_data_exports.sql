@@ -0,0 +1,14 @@
+-- the personal data export of a user, at most one at a time
+--
+-- `status` is `pending` while the archive is built or `ready` once it's stored in the bucket as
+-- `object`, a pending export older than an hour is considered lost and can be requested again
+CREATE TABLE IF NOT EXISTS data_exports (
+    user_id     UUID PRIMARY KEY NOT NULL,
+    id          VARCHAR(32) NOT NULL UNIQUE,
+    status      VARCHAR(8) NOT NULL,
+    object      TEXT,
+    created_at  TIMESTAMPTZ NOT NULL,
+    expires_at  TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_data_exports_expires_at ON data_exports(expires_at);
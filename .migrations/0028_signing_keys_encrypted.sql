@@ -0,0 +1,3 @@
+-- the secrets are encrypted with `service.signing_keys_key`, the rows stored before are encrypted
+-- by the server once it loads them
+ALTER TABLE signing_keys ADD COLUMN IF NOT EXISTS encrypted BOOLEAN NOT NULL DEFAULT FALSE;
@@ -4,8 +4,20 @@ mod stuff;
 fn main() {
     dioxus::serve(|| async move {
         dotenv::dotenv().ok();
+        // every missing or invalid value is reported at once, before anything uses the config
+        let config = util::config::load().unwrap_or_else(|errors| {
+            for error in &errors {
+                tracing::error!("[Invalid Config] {error}");
+            }
+            std::process::exit(1);
+        });
+        if let Err(e) = util::init(&config) {
+            tracing::error!("[Invalid Config] {e}");
+            std::process::exit(1);
+        }
         dioxus::fullstack::set_server_url(&shared::SERVICE_DOMAIN);
-        let mut router = dioxus::server::router(stuff::App).merge(server::routes().await);
+        let db = database::Db::new(std::sync::Arc::new(config)).await;
+        let mut router = dioxus::server::router(stuff::App).merge(server::routes(db));
         Ok(router)
     });
 }
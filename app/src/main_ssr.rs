@@ -11,13 +11,25 @@ async fn main() {
         .with(tracing::level_filters::LevelFilter::from_level(tracing::Level::DEBUG))
         .with(tracing_subscriber::fmt::Layer::default())
         .init();
+    // every missing or invalid value is reported at once, before anything uses the config
+    let config = util::config::load().unwrap_or_else(|errors| {
+        for error in &errors {
+            tracing::error!("[Invalid Config] {error}");
+        }
+        std::process::exit(1);
+    });
+    if let Err(e) = util::init(&config) {
+        tracing::error!("[Invalid Config] {e}");
+        std::process::exit(1);
+    }
+    let db = database::Db::new(std::sync::Arc::new(config)).await;
     let addr = dioxus::cli_config::fullstack_address_or_localhost();
     // let server_addr = std::net::SocketAddr::from_str(&std::env::var("SOCKET").unwrap()).unwrap();
     let router = axum::Router::new()
         .serve_dioxus_application(ServeConfig::new(), stuff::App)
-        .merge(server::routes().await);
+        .merge(server::routes(db.clone()));
     axum::serve(
-        server::get_custom_listener(addr).await,
+        server::get_custom_listener(addr, db).await,
         router.into_make_service_with_connect_info::<server::ClientSocket>(),
     )
     .with_graceful_shutdown(shutdown_signal())
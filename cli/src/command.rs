@@ -0,0 +1,386 @@
+use database::{Db, migrations, users::User};
+use std::{collections::HashMap, io::Write, sync::Arc};
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+use util::{AppError, config::Config, oauth::OAuthProvider, session::keyring};
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    MigrateRun,
+    MigrateStatus,
+    CreateUser {
+        username: String,
+        email: String,
+        display_name: Option<String>,
+        password: Option<PasswordInput>,
+        admin: bool,
+    },
+    GrantAdmin(String),
+    RevokeAdmin(String),
+    ResetPassword {
+        username: String,
+        password: Option<PasswordInput>,
+    },
+    ExportUsers(Option<String>),
+    ImportUsers(String),
+    ListSessions(String),
+    RevokeSessions {
+        username: String,
+        session_id: Option<uuid::Uuid>,
+    },
+    PurgeSessions,
+    PurgeDeletedUsers,
+    ListKeys,
+    RotateKey,
+    RetireKey(String),
+}
+
+/// where a password is read from, it's never taken from the arguments as they are visible to
+/// the other users of the machine
+#[derive(Debug, PartialEq)]
+pub enum PasswordInput {
+    Prompt, // `--password`, typed twice on the terminal without echo
+    Stdin,  // `--password-stdin`, the first line of stdin
+}
+
+impl PasswordInput {
+    fn from_options(options: &mut HashMap<&str, String>) -> Option<Self> {
+        match (options.remove("--password"), options.remove("--password-stdin")) {
+            (_, Some(_)) => Some(Self::Stdin),
+            (Some(_), None) => Some(Self::Prompt),
+            (None, None) => None,
+        }
+    }
+
+    fn read(&self) -> Result<String, String> {
+        let password = match self {
+            Self::Prompt => {
+                let password = rpassword::prompt_password("Password: ")
+                    .map_err(|e| format!("Failed to read the password: {e}"))?;
+                let repeated = rpassword::prompt_password("Repeat the password: ")
+                    .map_err(|e| format!("Failed to read the password: {e}"))?;
+                if password != repeated {
+                    return Err("The passwords didn't match".to_string());
+                }
+                password
+            }
+            Self::Stdin => {
+                let mut line = String::new();
+                std::io::stdin()
+                    .read_line(&mut line)
+                    .map_err(|e| format!("Failed to read the password: {e}"))?;
+                line.trim_end_matches(['\r', '\n']).to_string()
+            }
+        };
+        shared::validation::is_password_strong(&password).map_err(|e| e.to_string())?;
+        Ok(password)
+    }
+}
+
+// options that don't take a value
+const SWITCHES: [&str; 3] = ["--admin", "--password", "--password-stdin"];
+
+impl Command {
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut positional = Vec::new();
+        let mut options = HashMap::new();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if SWITCHES.contains(&arg.as_str()) {
+                options.insert(arg.as_str(), String::new());
+            } else if arg.starts_with("--") {
+                let value = args.next().ok_or(format!("Missing value of {arg}"))?;
+                options.insert(arg.as_str(), value.clone());
+            } else {
+                positional.push(arg.as_str());
+            }
+        }
+
+        let command = match positional.as_slice() {
+            ["migrate", "run"] => Self::MigrateRun,
+            ["migrate", "status"] => Self::MigrateStatus,
+            ["user", "create", username, email] => Self::CreateUser {
+                username: username.to_string(),
+                email: email.to_string(),
+                display_name: options.remove("--name"),
+                password: PasswordInput::from_options(&mut options),
+                admin: options.remove("--admin").is_some(),
+            },
+            ["user", "grant-admin", username] => Self::GrantAdmin(username.to_string()),
+            ["user", "revoke-admin", username] => Self::RevokeAdmin(username.to_string()),
+            ["user", "reset-password", username] => Self::ResetPassword {
+                username: username.to_string(),
+                password: PasswordInput::from_options(&mut options),
+            },
+            ["user", "export"] => Self::ExportUsers(None),
+            ["user", "export", file] => Self::ExportUsers(Some(file.to_string())),
+            ["user", "import", file] => Self::ImportUsers(file.to_string()),
+            ["session", "list", username] => Self::ListSessions(username.to_string()),
+            ["session", "revoke", username] => {
+                Self::RevokeSessions { username: username.to_string(), session_id: None }
+            }
+            ["session", "revoke", username, id] => Self::RevokeSessions {
+                username: username.to_string(),
+                session_id: Some(id.parse().map_err(|_| format!("Invalid session id: {id}"))?),
+            },
+            ["purge", "sessions"] => Self::PurgeSessions,
+            ["purge", "deleted-users"] => Self::PurgeDeletedUsers,
+            ["keys", "list"] => Self::ListKeys,
+            ["keys", "rotate"] => Self::RotateKey,
+            ["keys", "retire", id] => Self::RetireKey(id.to_string()),
+            [] => return Err("Missing command".to_string()),
+            _ => return Err(format!("Unknown command: {}", positional.join(" "))),
+        };
+
+        match options.keys().next() {
+            Some(option) => Err(format!("Unexpected option: {option}")),
+            None => Ok(command),
+        }
+    }
+
+    pub async fn run(self, config: Arc<Config>) -> Result<(), String> {
+        // `Db::new` applies the pending migrations, so these connect on their own
+        match self {
+            Self::MigrateRun => {
+                let pool = migrations::connect(&config.database).await.map_err(describe)?;
+                migrations::run(&pool).await.map_err(describe)?;
+                println!("Migrations are up to date");
+                Ok(())
+            }
+            Self::MigrateStatus => {
+                let pool = migrations::connect(&config.database).await.map_err(describe)?;
+                for m in migrations::status(&pool).await.map_err(describe)? {
+                    let installed = m.installed_on.map_or("pending".to_string(), date);
+                    println!("{:04}  {:<24}  {installed}", m.version, m.description);
+                }
+                Ok(())
+            }
+            command => command.run_with(&Db::new(config).await).await,
+        }
+    }
+
+    async fn run_with(self, db: &Arc<Db>) -> Result<(), String> {
+        match self {
+            Self::MigrateRun | Self::MigrateStatus => unreachable!(),
+
+            Self::CreateUser { username, email, display_name, password, admin } => {
+                let display_name = display_name.unwrap_or(username.clone());
+                shared::validation::is_username_valid(&username).map_err(|e| e.to_string())?;
+                shared::validation::is_email_valid(&email).map_err(|e| e.to_string())?;
+                shared::validation::is_display_name_valid(&display_name)
+                    .map_err(|e| e.to_string())?;
+                let password = password.map(|v| v.read()).transpose()?;
+                let user = User {
+                    id: uuid::Uuid::new_v4(),
+                    display_name,
+                    email,
+                    birth_date: None,
+                    password,
+                    username,
+                    banner: None,
+                    icon: None,
+                    bio: None,
+                    legal_name: None,
+                    gender: None,
+                    phone: None,
+                    country: None,
+                    locale: None,
+                    oauth_provider: OAuthProvider::None,
+                    created: OffsetDateTime::now_utc(),
+                    role: if admin { "admin" } else { "user" }.to_string(),
+                };
+                db.create_user(&user).await.map_err(describe)?;
+                println!("Created @{} ({})", user.username, user.id);
+            }
+
+            Self::GrantAdmin(username) => {
+                db.update_role(&username, "admin").await.map_err(describe)?;
+                println!("@{username} is now an admin");
+            }
+
+            Self::RevokeAdmin(username) => {
+                db.update_role(&username, "user").await.map_err(describe)?;
+                // admin sessions must not outlive the role
+                let user = db.get_user_by_username(&username).await.map_err(describe)?;
+                db.remove_every_session(user.id).await.map_err(describe)?;
+                println!("@{username} is no longer an admin, every session was ended");
+            }
+
+            Self::ResetPassword { username, password } => {
+                let user = db.get_user_by_username(&username).await.map_err(describe)?;
+                match password {
+                    Some(password) => {
+                        let password = password.read()?;
+                        db.update_password(&user.email, &password).await.map_err(describe)?;
+                    }
+                    None => db.remove_password(&user.email).await.map_err(describe)?,
+                }
+                db.remove_every_session(user.id).await.map_err(describe)?;
+                println!("The password of @{username} was reset, every session was ended");
+            }
+
+            Self::ExportUsers(file) => {
+                let users = db.get_all_users().await.map_err(describe)?;
+                let mut out: Box<dyn Write> = match &file {
+                    Some(file) => Box::new(
+                        std::fs::File::create(file)
+                            .map_err(|e| format!("Failed to create {file}: {e}"))?,
+                    ),
+                    None => Box::new(std::io::stdout().lock()),
+                };
+                for user in &users {
+                    writeln!(out, "{}", crate::transfer::to_json(user))
+                        .map_err(|e| format!("Failed to write: {e}"))?;
+                }
+                eprintln!("Exported {} users", users.len());
+            }
+
+            Self::ImportUsers(file) => {
+                let input = match file.as_str() {
+                    "-" => std::io::read_to_string(std::io::stdin()),
+                    _ => std::fs::read_to_string(&file),
+                }
+                .map_err(|e| format!("Failed to read {file}: {e}"))?;
+
+                let (mut imported, mut skipped) = (0, 0);
+                for (i, line) in input.lines().enumerate().filter(|(_, v)| !v.trim().is_empty()) {
+                    let user = serde_json::from_str::<serde_json::Value>(line)
+                        .map_err(|e| e.to_string())
+                        .and_then(|v| crate::transfer::from_json(&v))
+                        .map_err(|e| format!("Line {}: {e}", i + 1))?;
+                    match db.create_user(&user).await {
+                        Ok(_) => imported += 1,
+                        // existing accounts are never overwritten
+                        Err(AppError::EmailTaken | AppError::UsernameTaken) => {
+                            eprintln!("Line {}: @{} already exists, skipped", i + 1, user.username);
+                            skipped += 1;
+                        }
+                        Err(e) => return Err(format!("Line {}: {}", i + 1, describe(e))),
+                    }
+                }
+                println!("Imported {imported} users, skipped {skipped}");
+            }
+
+            Self::ListSessions(username) => {
+                let user = db.get_user_by_username(&username).await.map_err(describe)?;
+                for s in db.get_sessions(user.id).await.map_err(describe)? {
+                    let ua = util::user_agent::parse(s.user_agent.as_deref().unwrap_or_default());
+                    println!(
+                        "{}  {}  last used {}  expires {}  {} on {} ({})",
+                        s.unsigned_ssid,
+                        s.ip_address,
+                        date(s.last_used),
+                        date(s.expires_at),
+                        ua.browser.as_deref().unwrap_or("unknown browser"),
+                        ua.os.as_deref().unwrap_or("unknown os"),
+                        ua.device.get_str(),
+                    );
+                }
+            }
+
+            Self::RevokeSessions { username, session_id } => {
+                let user = db.get_user_by_username(&username).await.map_err(describe)?;
+                match session_id {
+                    Some(id) => db.remove_session(user.id, id).await.map_err(describe)?,
+                    None => db.remove_every_session(user.id).await.map_err(describe)?,
+                }
+                println!("Revoked the sessions of @{username}");
+            }
+
+            Self::PurgeSessions => {
+                let count = db.purge_expired_sessions().await.map_err(describe)?;
+                println!("Removed {count} expired sessions");
+            }
+
+            Self::PurgeDeletedUsers => {
+                let count = db.purge_due_users().await.map_err(describe)?;
+                println!("Removed {count} deleted users whose grace period has ended");
+            }
+
+            Self::ListKeys => {
+                let current = keyring::current_key_id();
+                for key in db.get_signing_keys().await.map_err(describe)? {
+                    let state = match key.retired_at {
+                        Some(t) => format!("retired {}", date(t)),
+                        None if key.id == current => "current".to_string(),
+                        None => "active".to_string(),
+                    };
+                    println!("{}  created {}  {state}", key.id, date(key.created_at));
+                }
+            }
+
+            Self::RotateKey => {
+                let id = db.rotate_signing_key().await.map_err(describe)?;
+                println!(
+                    "New cookies are signed with {id}, older keys keep verifying until retired"
+                );
+            }
+
+            Self::RetireKey(id) => {
+                db.retire_signing_key(&id).await.map_err(describe)?;
+                println!("Retired {id}, cookies signed with it are no longer accepted");
+            }
+        }
+        Ok(())
+    }
+}
+
+fn date(t: OffsetDateTime) -> String {
+    t.format(&Rfc3339).unwrap_or_default()
+}
+
+// `AppError` is shaped for http responses, the cases reachable from here get a readable message
+fn describe(e: AppError) -> String {
+    match e {
+        AppError::BadReq(msg) => msg.to_string(),
+        AppError::Validation(e) => e.to_string(),
+        AppError::UserNotFound => "User not found".to_string(),
+        AppError::UsernameTaken => "Username is already taken".to_string(),
+        AppError::EmailTaken => "Email is already taken".to_string(),
+        AppError::ServerError => "Database request failed, see the log above".to_string(),
+        e => format!("{e:?}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Result<Command, String> {
+        Command::parse(&s.split_whitespace().map(str::to_string).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn command_parse_test() {
+        assert_eq!(
+            parse("user create ada ada@example.com --admin --password"),
+            Ok(Command::CreateUser {
+                username: "ada".to_string(),
+                email: "ada@example.com".to_string(),
+                display_name: None,
+                password: Some(PasswordInput::Prompt),
+                admin: true,
+            })
+        );
+        assert_eq!(
+            parse("user reset-password ada --password-stdin"),
+            Ok(Command::ResetPassword {
+                username: "ada".to_string(),
+                password: Some(PasswordInput::Stdin),
+            })
+        );
+        // a password in the arguments is left over as an unknown command
+        assert!(parse("user reset-password ada --password hunter2!A").is_err());
+        assert_eq!(parse("purge deleted-users"), Ok(Command::PurgeDeletedUsers));
+        assert_eq!(
+            parse("session revoke ada"),
+            Ok(Command::RevokeSessions { username: "ada".to_string(), session_id: None })
+        );
+
+        assert!(parse("session revoke ada not-a-uuid").is_err());
+        assert!(parse("keys rotate --admin").is_err());
+        assert!(parse("purge deleted-users --older-than 7").is_err());
+        assert!(parse("user create ada --name").is_err());
+        assert!(parse("").is_err());
+        assert!(parse("users list").is_err());
+    }
+}
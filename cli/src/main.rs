@@ -0,0 +1,69 @@
+mod command;
+mod transfer;
+
+const USAGE: &str = "Usage: stronghold-admin <command>
+
+Commands:
+  migrate run                                  apply the pending migrations
+  migrate status                               list the migrations and when they were applied
+  user create <username> <email> [--name <display name>] [--password | --password-stdin] [--admin]
+  user grant-admin <username>
+  user revoke-admin <username>                 also ends every session of the user
+  user reset-password <username> [--password | --password-stdin]
+                                               without a password, it has to be recovered by email
+  user export [<file>]                         one JSON user per line, to stdout by default
+  user import <file>                           reads the output of `user export`, `-` for stdin
+  session list <username>
+  session revoke <username> [<session id>]     every session of the user by default
+  purge sessions                               remove the expired sessions of every user
+  purge deleted-users                          remove the deleted users whose grace period has ended
+                                               together with their media
+  keys list
+  keys rotate
+  keys retire <key id>
+
+`--password` asks for the password on the terminal, `--password-stdin` reads it from the first
+line of stdin.
+
+The server configuration is used, see `stronghold.example.toml`.
+Running servers notice revoked sessions within 5 minutes and role changes on the next sign-in,
+the admin endpoints check both on every request.";
+
+#[tokio::main]
+async fn main() {
+    use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+    dotenv::dotenv().ok();
+    tracing_subscriber::registry()
+        .with(tracing::level_filters::LevelFilter::from_level(tracing::Level::INFO))
+        .with(tracing_subscriber::fmt::Layer::default().with_writer(std::io::stderr))
+        .init();
+
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if matches!(args.first().map(String::as_str), Some("help" | "--help" | "-h")) {
+        println!("{USAGE}");
+        return;
+    }
+    let command = match command::Command::parse(&args) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("{e}\n\n{USAGE}");
+            std::process::exit(2);
+        }
+    };
+
+    let config = util::config::load().unwrap_or_else(|errors| {
+        for error in &errors {
+            eprintln!("Invalid config: {error}");
+        }
+        std::process::exit(1);
+    });
+    if let Err(e) = util::init(&config) {
+        eprintln!("Invalid config: {e}");
+        std::process::exit(1);
+    }
+
+    if let Err(e) = command.run(std::sync::Arc::new(config)).await {
+        eprintln!("Error: {e}");
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,100 @@
+use database::users::User;
+use serde_json::{Value, json};
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+use util::oauth::OAuthProvider;
+
+/// converts `user` into one line of `user export`, dates are RFC 3339
+pub fn to_json(user: &User) -> Value {
+    let date = |t: OffsetDateTime| t.format(&Rfc3339).unwrap_or_default();
+    json!({
+        "id": user.id.to_string(),
+        "display_name": user.display_name,
+        "email": user.email,
+        "birth_date": user.birth_date.map(date),
+        "password": user.password,
+        "username": user.username,
+        "banner": user.banner,
+        "icon": user.icon,
+        "bio": user.bio,
+        "legal_name": user.legal_name,
+        "gender": user.gender,
+        "phone": user.phone,
+        "country": user.country,
+        "locale": user.locale,
+        "oauth_provider": user.oauth_provider.get_str(),
+        "created": date(user.created),
+        "role": user.role,
+    })
+}
+
+/// reads one line of `user export` back into a `User`
+pub fn from_json(value: &Value) -> Result<User, String> {
+    let optional = |key: &str| value.get(key).and_then(Value::as_str).map(str::to_string);
+    let string = |key: &str| optional(key).ok_or(format!("`{key}` is missing"));
+    let date = |key: &str, v: String| {
+        OffsetDateTime::parse(&v, &Rfc3339).map_err(|e| format!("Invalid `{key}`: {e}"))
+    };
+
+    let role = optional("role").unwrap_or("user".to_string());
+    if role != "user" && role != "admin" {
+        return Err(format!("Unknown role: {role}"));
+    }
+    Ok(User {
+        id: string("id")?.parse().map_err(|e| format!("Invalid `id`: {e}"))?,
+        display_name: string("display_name")?,
+        email: string("email")?,
+        birth_date: optional("birth_date").map(|v| date("birth_date", v)).transpose()?,
+        password: optional("password"),
+        username: string("username")?,
+        banner: optional("banner"),
+        icon: optional("icon"),
+        bio: optional("bio"),
+        legal_name: optional("legal_name"),
+        gender: optional("gender"),
+        phone: optional("phone"),
+        country: optional("country"),
+        locale: optional("locale"),
+        oauth_provider: OAuthProvider::from(optional("oauth_provider").unwrap_or_default()),
+        created: date("created", string("created")?)?,
+        role,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn user_transfer_test() {
+        let user = User {
+            id: uuid::Uuid::new_v4(),
+            display_name: "Ada".to_string(),
+            email: "ada@example.com".to_string(),
+            birth_date: Some(time::macros::datetime!(1990-12-10 0:00 UTC)),
+            password: None,
+            username: "ada".to_string(),
+            banner: None,
+            icon: None,
+            bio: Some("hello".to_string()),
+            legal_name: None,
+            gender: None,
+            phone: None,
+            country: None,
+            locale: Some("en".to_string()),
+            oauth_provider: OAuthProvider::Google,
+            created: time::macros::datetime!(2024-05-01 12:30:15 UTC),
+            role: "admin".to_string(),
+        };
+        let restored = from_json(&to_json(&user)).unwrap();
+        assert_eq!(restored.id, user.id);
+        assert_eq!(restored.birth_date, user.birth_date);
+        assert_eq!(restored.created, user.created);
+        assert_eq!(restored.bio, user.bio);
+        assert_eq!(restored.oauth_provider.get_str(), "google");
+        assert_eq!(restored.role, "admin");
+
+        let mut missing = to_json(&user);
+        missing.as_object_mut().unwrap().remove("email");
+        assert_eq!(from_json(&missing).unwrap_err(), "`email` is missing");
+    }
+}
@@ -0,0 +1,67 @@
+use std::{net::IpAddr, sync::Arc};
+use util::AppError;
+
+/// seconds after which an unused login code expires
+pub const LOGIN_OTP_TTL: u64 = 600;
+/// wrong guesses allowed per email within `LOGIN_OTP_WINDOW`, whatever the number of codes issued
+pub const LOGIN_OTP_ATTEMPTS: u8 = 5;
+/// seconds over which the guesses and the issued codes are counted
+pub const LOGIN_OTP_WINDOW: u64 = 3600;
+/// codes that can be issued per email within `LOGIN_OTP_WINDOW`
+pub const LOGIN_OTPS_PER_EMAIL: u32 = 5;
+/// codes that can be issued per ip address within `LOGIN_OTP_WINDOW`
+pub const LOGIN_OTPS_PER_IP: u32 = 20;
+
+// implementation block for logging in with a code sent to the email
+impl crate::Db {
+    /// counts a login code request, refused once the email or the ip address asked too often
+    ///
+    /// called whether an account exists for `email` or not, so that the answer doesn't tell
+    pub fn allow_login_otp(self: &Arc<Self>, email: &str, ip: IpAddr) -> Result<(), AppError> {
+        let apps = &self.applications;
+        if !apps.login_otp_ip_issues.hit(&ip.to_string()) || !apps.login_otp_issues.hit(email) {
+            tracing::warn!("[Login OTP Throttled] Email: {email}, IP: {ip}");
+            return Err(AppError::TooManyRequests(
+                "Too many login codes requested, please try again later",
+            ));
+        }
+        Ok(())
+    }
+
+    /// issues a new login code for `email`, replacing the previous one
+    pub fn create_login_otp(self: &Arc<Self>, email: String) -> String {
+        // a random secret per request, so that codes can't be derived from the email
+        let otp = util::generate::otp(&util::generate::random_string(32));
+        tracing::info!("[Login OTP Request] Email: {email}");
+        self.applications.login_otps.insert(email, otp.clone());
+        otp
+    }
+
+    /// consumes the login code of `email` if `otp` matches
+    ///
+    /// wrong guesses are counted per email rather than per code, so that requesting new codes
+    /// doesn't allow more of them
+    pub fn verify_login_otp(self: &Arc<Self>, email: &str, otp: &str) -> Result<(), AppError> {
+        let apps = &self.applications;
+        let code = apps
+            .login_otps
+            .get(email)
+            .ok_or(AppError::BadReq("The code has expired, please request a new one"))?;
+        if apps.login_otp_guesses.is_exhausted(email) {
+            apps.login_otps.invalidate(email);
+            return Err(AppError::TooManyRequests("Too many wrong codes, please try again later"));
+        }
+
+        if code == otp {
+            apps.login_otps.invalidate(email);
+            apps.login_otp_guesses.reset(email);
+            return Ok(());
+        }
+        apps.login_otp_guesses.hit(email);
+        if apps.login_otp_guesses.is_exhausted(email) {
+            apps.login_otps.invalidate(email);
+            tracing::warn!("[Login OTP Discarded] Email: {email}, too many attempts");
+        }
+        Err(AppError::InvalidOTP)
+    }
+}
@@ -0,0 +1,24 @@
+use std::{net::SocketAddr, sync::Arc};
+
+/// seconds after which an unused magic link expires
+pub const MAGIC_LINK_TTL: u64 = 900;
+
+// implementation block for passwordless logins through emailed links
+impl crate::Db {
+    /// stores the link `id` for `email`, invalidating the previous link of the same email
+    pub fn add_magic_link(self: &Arc<Self>, socket_addr: SocketAddr, email: String, id: String) {
+        self.applications.magic_links.get(&email).inspect(|id| {
+            self.applications.magic_links.invalidate(id);
+        });
+        tracing::info!("[Magic Link Request] Email: {email}, Socket: {}", socket_addr.to_string());
+        self.applications.magic_links.insert(id.clone(), email.clone());
+        self.applications.magic_links.insert(email, id);
+    }
+
+    /// consumes the link `id`, returns the email it was issued for
+    pub fn take_magic_link(self: &Arc<Self>, id: &str) -> Option<String> {
+        let email = self.applications.magic_links.remove(id)?;
+        self.applications.magic_links.invalidate(&email);
+        Some(email)
+    }
+}
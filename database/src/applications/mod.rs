@@ -1,17 +1,35 @@
 use moka::sync::Cache;
 use std::{net::SocketAddr, time::Duration};
 
+mod login_otp;
+mod magic_link;
 mod post_oidc;
 mod pre_oidc;
 mod pre_recovery;
+mod reauth;
 mod registration;
+mod throttle;
 mod update_email;
+mod update_phone;
+
+pub use login_otp::LOGIN_OTP_TTL;
+pub use magic_link::MAGIC_LINK_TTL;
 
 pub struct Applications {
     socket_index: Cache<SocketAddr, DropType>,
     registrants: Cache<String, RegistrantEntry>, // Email [post_oidc, registration, update_email]
     oidconnect: Cache<String, OidcInfo>,         // CSRF State [pre_oidc]
     recovery_codes: Cache<String, String>,       // Code/Email [pre_recovery]
+    magic_links: Cache<String, String>,          // Id/Email [magic_link]
+    login_otps: Cache<String, String>,           // Email/Code [login_otp]
+    login_otp_guesses: throttle::Throttle,       // Email [login_otp]
+    login_otp_issues: throttle::Throttle,        // Email [login_otp]
+    login_otp_ip_issues: throttle::Throttle,     // IP [login_otp]
+    phone_updates: Cache<sqlx::types::Uuid, update_phone::PhoneUpdate>, // User Id [update_phone]
+    phone_update_issues: throttle::Throttle,     // User Id [update_phone]
+    reauth_otps: Cache<sqlx::types::Uuid, String>, // User Id/Code [reauth]
+    reauth_failures: throttle::Throttle,         // User Id [reauth]
+    reauth_otp_issues: throttle::Throttle,       // User Id [reauth]
 }
 
 #[derive(Clone)]
@@ -26,8 +44,10 @@ pub struct RegistrantEntry {
     pub password: Option<String>,
     pub icon: Option<String>,
     pub phone: Option<String>,
+    pub locale: Option<String>,
     pub oauth_provider: util::oauth::OAuthProvider,
     pub status: RegistrantStatus,
+    pub return_to: Option<String>, // page opened after the registration is complete
 }
 
 #[derive(PartialEq, Debug, Clone)]
@@ -35,9 +55,8 @@ pub enum RegistrantStatus {
     Created(String), // OTP
     EmailVerified,
     PasswordSet,
-    OpenIDConnected,
+    OpenIDConnected(String), // subject at the provider
     UpdatingEmail { old_email: String, otp: String },
-    // UpdatingPhone { old_phone: String, otp: String },
 }
 
 #[derive(Clone)]
@@ -46,6 +65,9 @@ pub struct OidcInfo {
     pub code_verifier: String,
     pub nonce: String,
     pub provider: util::oauth::OAuthProvider,
+    pub return_to: Option<String>,
+    /// user id and unsigned ssid of the session re-authenticating, None while signing in
+    pub reauth: Option<(sqlx::types::Uuid, sqlx::types::Uuid)>,
 }
 
 impl Applications {
@@ -68,6 +90,46 @@ impl Applications {
                 .max_capacity(4096)
                 .time_to_live(Duration::from_secs(300))
                 .build(),
+            magic_links: Cache::builder()
+                .max_capacity(4096)
+                .time_to_live(Duration::from_secs(magic_link::MAGIC_LINK_TTL))
+                .build(),
+            login_otps: Cache::builder()
+                .max_capacity(4096)
+                .time_to_live(Duration::from_secs(login_otp::LOGIN_OTP_TTL))
+                .build(),
+            login_otp_guesses: throttle::Throttle::new(
+                login_otp::LOGIN_OTP_ATTEMPTS.into(),
+                Duration::from_secs(login_otp::LOGIN_OTP_WINDOW),
+            ),
+            login_otp_issues: throttle::Throttle::new(
+                login_otp::LOGIN_OTPS_PER_EMAIL,
+                Duration::from_secs(login_otp::LOGIN_OTP_WINDOW),
+            ),
+            login_otp_ip_issues: throttle::Throttle::new(
+                login_otp::LOGIN_OTPS_PER_IP,
+                Duration::from_secs(login_otp::LOGIN_OTP_WINDOW),
+            ),
+            phone_updates: Cache::builder()
+                .max_capacity(4096)
+                .time_to_live(Duration::from_secs(login_otp::LOGIN_OTP_TTL))
+                .build(),
+            phone_update_issues: throttle::Throttle::new(
+                update_phone::PHONE_CODES_PER_USER,
+                Duration::from_secs(login_otp::LOGIN_OTP_WINDOW),
+            ),
+            reauth_otps: Cache::builder()
+                .max_capacity(4096)
+                .time_to_live(Duration::from_secs(login_otp::LOGIN_OTP_TTL))
+                .build(),
+            reauth_failures: throttle::Throttle::new(
+                reauth::REAUTH_ATTEMPTS,
+                Duration::from_secs(login_otp::LOGIN_OTP_WINDOW),
+            ),
+            reauth_otp_issues: throttle::Throttle::new(
+                login_otp::LOGIN_OTPS_PER_EMAIL,
+                Duration::from_secs(login_otp::LOGIN_OTP_WINDOW),
+            ),
         }
     }
 
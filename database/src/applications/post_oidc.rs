@@ -7,10 +7,13 @@ impl crate::Db {
     pub async fn create_registrant_oidc(
         self: &Arc<Self>,
         socket_addr: SocketAddr,
+        subject: String,
         name: String,
         email: String,
         icon: String,
+        locale: Option<String>,
         oauth_provider: util::oauth::OAuthProvider,
+        return_to: Option<String>,
     ) -> Result<(), AppError> {
         self.is_email_available(&email).await?;
         self.applications.insert_registrant(
@@ -21,8 +24,10 @@ impl crate::Db {
                 password: None,
                 icon: Some(icon),
                 phone: None,
+                locale,
                 oauth_provider,
-                status: RegistrantStatus::OpenIDConnected,
+                status: RegistrantStatus::OpenIDConnected(subject),
+                return_to,
             },
         );
         Ok(())
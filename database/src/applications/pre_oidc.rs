@@ -1,20 +1,11 @@
 use super::OidcInfo;
-use std::{net::SocketAddr, sync::Arc};
-use util::oauth::OAuthProvider;
+use std::sync::Arc;
 
 // implementation block for those users who are authenticating using open_id_connect
 impl crate::Db {
     #[inline]
-    pub fn add_oidc_info(
-        self: &Arc<Self>,
-        socket_addr: SocketAddr,
-        csrf_state: String,
-        code_verifier: String,
-        nonce: String,
-        provider: OAuthProvider,
-    ) {
-        let oauth_info = OidcInfo { socket_addr, code_verifier, nonce, provider };
-        self.applications.oidconnect.insert(csrf_state, oauth_info);
+    pub fn add_oidc_info(self: &Arc<Self>, csrf_state: String, oidc_info: OidcInfo) {
+        self.applications.oidconnect.insert(csrf_state, oidc_info);
     }
 
     #[inline]
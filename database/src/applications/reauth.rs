@@ -0,0 +1,77 @@
+use sqlx::types::Uuid;
+use std::sync::Arc;
+use util::AppError;
+
+/// wrong passwords and codes allowed per user within `LOGIN_OTP_WINDOW`
+pub const REAUTH_ATTEMPTS: u32 = 5;
+
+// implementation block for re-authenticating a signed in user, kept apart from the login codes
+// so that nothing keyed by an email can reach them
+impl crate::Db {
+    /// issues a code for the user to re-authenticate with, replacing the previous one
+    pub fn create_reauth_otp(self: &Arc<Self>, user_id: Uuid) -> Result<String, AppError> {
+        let apps = &self.applications;
+        if !apps.reauth_otp_issues.hit(&user_id.to_string()) {
+            return Err(AppError::TooManyRequests(
+                "Too many codes requested, please try again later",
+            ));
+        }
+        let otp = util::generate::otp(&util::generate::random_string(32));
+        tracing::info!("[Reauth OTP Request] User ID: {user_id}");
+        apps.reauth_otps.insert(user_id, otp.clone());
+        Ok(otp)
+    }
+
+    /// consumes the re-authentication code of the user if `otp` matches
+    pub fn verify_reauth_otp(self: &Arc<Self>, user_id: Uuid, otp: &str) -> Result<(), AppError> {
+        self.check_reauth_attempts(user_id)?;
+        let apps = &self.applications;
+        let code = apps
+            .reauth_otps
+            .get(&user_id)
+            .ok_or(AppError::BadReq("The code has expired, please request a new one"))?;
+        if code == otp {
+            apps.reauth_otps.invalidate(&user_id);
+            apps.reauth_failures.reset(&user_id.to_string());
+            return Ok(());
+        }
+        self.fail_reauth(user_id);
+        Err(AppError::InvalidOTP)
+    }
+
+    /// checks `given` against the password of the user, `password` is `None` for accounts
+    /// without one
+    pub fn verify_reauth_password(
+        self: &Arc<Self>,
+        user_id: Uuid,
+        password: Option<&str>,
+        given: &str,
+    ) -> Result<(), AppError> {
+        self.check_reauth_attempts(user_id)?;
+        if password.is_none_or(|p| p != given) {
+            self.fail_reauth(user_id);
+            return Err(AppError::PasswordMismatch);
+        }
+        self.applications.reauth_failures.reset(&user_id.to_string());
+        Ok(())
+    }
+
+    fn check_reauth_attempts(self: &Arc<Self>, user_id: Uuid) -> Result<(), AppError> {
+        if self.applications.reauth_failures.is_exhausted(&user_id.to_string()) {
+            return Err(AppError::TooManyRequests(
+                "Too many failed attempts, please try again later",
+            ));
+        }
+        Ok(())
+    }
+
+    // wrong passwords and codes share the count, the pending code is dropped once it runs out
+    fn fail_reauth(self: &Arc<Self>, user_id: Uuid) {
+        let apps = &self.applications;
+        apps.reauth_failures.hit(&user_id.to_string());
+        if apps.reauth_failures.is_exhausted(&user_id.to_string()) {
+            apps.reauth_otps.invalidate(&user_id);
+            tracing::warn!("[Reauth Locked] User ID: {user_id}, too many attempts");
+        }
+    }
+}
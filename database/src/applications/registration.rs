@@ -12,6 +12,8 @@ impl crate::Db {
         name: String,
         email: String,
         otp: String,
+        locale: Option<String>,
+        return_to: Option<String>,
     ) -> Result<(), AppError> {
         self.is_email_available(&email).await?;
         self.applications.insert_registrant(
@@ -22,8 +24,10 @@ impl crate::Db {
                 password: None,
                 icon: None,
                 phone: None,
+                locale,
                 oauth_provider: util::oauth::OAuthProvider::None,
                 status: RegistrantStatus::Created(otp),
+                return_to,
             },
         );
         Ok(())
@@ -74,6 +78,47 @@ impl crate::Db {
     }
 
     // it works for both registration and openidconnect
+    /// returns the page to open once the registration of `email` is complete
+    pub fn get_registrant_return_to(self: &Arc<Self>, email: &str) -> Option<String> {
+        self.applications.registrants.get(email)?.return_to
+    }
+
+    /// a few available usernames for the registrant, made from their display name and email
+    pub async fn suggest_registrant_usernames(
+        self: &Arc<Self>,
+        email: &str,
+    ) -> Result<Vec<String>, AppError> {
+        const COUNT: usize = 5;
+        let registrant = self.applications.registrants.get(email).ok_or(AppError::UserNotFound)?;
+        let display_name = registrant.display_name.unwrap_or_default();
+        let candidates = shared::validation::username_candidates(&display_name, email);
+
+        let mut suggestions = Vec::with_capacity(COUNT);
+        for candidate in &candidates {
+            if suggestions.len() == COUNT {
+                return Ok(suggestions);
+            }
+            if self.is_username_available(candidate).await.is_ok() {
+                suggestions.push(candidate.clone());
+            }
+        }
+        // the rest is filled with numbered ones, a few tries each
+        for candidate in candidates.iter().cycle().take(COUNT * 2) {
+            if suggestions.len() == COUNT {
+                break;
+            }
+            let n = rand::random_range(1..1000);
+            let numbered = format!("{}{n}", &candidate[..candidate.len().min(17)]);
+            if shared::validation::is_username_valid(&numbered).is_ok()
+                && !suggestions.contains(&numbered)
+                && self.is_username_available(&numbered).await.is_ok()
+            {
+                suggestions.push(numbered);
+            }
+        }
+        Ok(suggestions)
+    }
+
     pub async fn set_registrant_username(
         self: &Arc<Self>,
         email: String,
@@ -126,10 +171,15 @@ impl crate::Db {
             gender: None,
             phone: None,
             country: None,
+            locale: registrant.locale,
             oauth_provider: registrant.oauth_provider,
             created: OffsetDateTime::now_utc(),
+            role: "user".to_string(),
         };
         self.create_user_forced(&user).await;
+        if let RegistrantStatus::OpenIDConnected(subject) = &registrant.status {
+            self.link_oauth_identity(user.id, user.oauth_provider, subject, &user.email).await?;
+        }
         self.applications.remove_registrant(&user.email);
         Ok(user)
     }
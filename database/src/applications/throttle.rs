@@ -0,0 +1,56 @@
+use moka::sync::Cache;
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicU32, Ordering},
+    },
+    time::Duration,
+};
+
+/// counts what a key does within a fixed window (e.g. codes sent to an email), the window starts
+/// with the first event of the key
+pub(super) struct Throttle {
+    counts: Cache<String, Arc<AtomicU32>>,
+    limit: u32,
+}
+
+impl Throttle {
+    pub(super) fn new(limit: u32, window: Duration) -> Self {
+        Self { counts: Cache::builder().max_capacity(65536).time_to_live(window).build(), limit }
+    }
+
+    /// counts an event of `key`, returns false if the limit of the window was already reached
+    pub(super) fn hit(&self, key: &str) -> bool {
+        // the counter is updated in place, so that the entry keeps its original expiry
+        let count = self.counts.get_with(key.to_string(), Default::default);
+        count.fetch_add(1, Ordering::SeqCst) < self.limit
+    }
+
+    /// whether `key` used up the window, without counting an event
+    pub(super) fn is_exhausted(&self, key: &str) -> bool {
+        self.counts.get(key).is_some_and(|v| v.load(Ordering::SeqCst) >= self.limit)
+    }
+
+    pub(super) fn reset(&self, key: &str) {
+        self.counts.invalidate(key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn throttle_test() {
+        let throttle = Throttle::new(2, Duration::from_secs(60));
+        assert!(!throttle.is_exhausted("a"));
+        assert!(throttle.hit("a"));
+        assert!(throttle.hit("a"));
+        assert!(throttle.is_exhausted("a"));
+        assert!(!throttle.hit("a"));
+        assert!(throttle.hit("b"));
+
+        throttle.reset("a");
+        assert!(throttle.hit("a"));
+    }
+}
@@ -20,8 +20,10 @@ impl crate::Db {
                 password: None,
                 icon: None,
                 phone: None,
+                locale: None,
                 oauth_provider: util::oauth::OAuthProvider::None,
                 status: RegistrantStatus::UpdatingEmail { old_email, otp },
+                return_to: None,
             },
         );
         Ok(())
@@ -39,14 +41,19 @@ impl crate::Db {
             RegistrantStatus::UpdatingEmail { old_email: mem_old_email, otp: mem_otp }
                 if otp == mem_otp && old_email == mem_old_email =>
             {
-                sqlx::query!("UPDATE users SET email = $1 WHERE email = $2", new_email, old_email)
-                    .execute(&self.pool)
-                    .await
-                    .map_err(|e| {
-                        tracing::error!("{:?}", e);
-                        AppError::ServerError
-                    })?;
+                let id = sqlx::query_scalar!(
+                    "UPDATE users SET email = $1 WHERE email = $2 RETURNING id",
+                    new_email,
+                    old_email
+                )
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| {
+                    tracing::error!("{:?}", e);
+                    AppError::ServerError
+                })?;
                 self.applications.remove_registrant(&new_email);
+                self.record_event(id, "email_changed", None, None).await?;
 
                 tracing::info!("[Email Updated] Old: {old_email}, New: {new_email}");
                 Ok(())
@@ -0,0 +1,77 @@
+use super::login_otp::LOGIN_OTP_ATTEMPTS;
+use sqlx::types::Uuid;
+use std::sync::{
+    Arc,
+    atomic::{AtomicU8, Ordering},
+};
+use util::AppError;
+
+/// verification texts that can be sent per user within `LOGIN_OTP_WINDOW`
+pub const PHONE_CODES_PER_USER: u32 = 5;
+
+#[derive(Clone)]
+pub(super) struct PhoneUpdate {
+    new_phone: String,
+    otp: String,
+    // shared between clones so that the cache entry keeps its original expiry
+    attempts: Arc<AtomicU8>,
+}
+
+// implementation block for verifying and updating the phone of a user
+impl crate::Db {
+    /// issues the code to send to `new_phone`, kept until the user with `user_id` verifies it
+    ///
+    /// refused once the user asked for `PHONE_CODES_PER_USER` codes, as every one costs a text
+    pub fn request_phone_update(
+        self: &Arc<Self>,
+        user_id: Uuid,
+        new_phone: String,
+    ) -> Result<String, AppError> {
+        if !self.applications.phone_update_issues.hit(&user_id.to_string()) {
+            return Err(AppError::TooManyRequests(
+                "Too many verification codes were sent, please try again later",
+            ));
+        }
+        // a random secret per request, so that codes can't be derived from the phone
+        let otp = util::generate::otp(&util::generate::random_string(32));
+        tracing::info!("[Phone Update Request] User ID: {user_id}, Phone: {new_phone}");
+        self.applications.phone_updates.insert(
+            user_id,
+            PhoneUpdate { new_phone, otp: otp.clone(), attempts: Arc::new(AtomicU8::new(0)) },
+        );
+        Ok(otp)
+    }
+
+    /// checks the otp and updates the phone of the user, returns the new phone
+    pub async fn update_phone(
+        self: &Arc<Self>,
+        user_id: Uuid,
+        otp: &str,
+    ) -> Result<String, AppError> {
+        let entry = self
+            .applications
+            .phone_updates
+            .get(&user_id)
+            .ok_or(AppError::BadReq("Please request a verification code first"))?;
+        if entry.otp != otp {
+            if entry.attempts.fetch_add(1, Ordering::SeqCst) + 1 >= LOGIN_OTP_ATTEMPTS {
+                self.applications.phone_updates.invalidate(&user_id);
+                tracing::warn!("[Phone OTP Discarded] User ID: {user_id}, too many attempts");
+            }
+            return Err(AppError::InvalidOTP);
+        }
+        self.applications.phone_updates.invalidate(&user_id);
+
+        sqlx::query!("UPDATE users SET phone = $1 WHERE id = $2", entry.new_phone, user_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                tracing::error!("{:?}", e);
+                AppError::ServerError
+            })?;
+        self.record_event(user_id, "phone_changed", None, None).await?;
+
+        tracing::info!("[Phone Updated] User ID: {user_id}, Phone: {}", entry.new_phone);
+        Ok(entry.new_phone)
+    }
+}
@@ -0,0 +1,66 @@
+use sqlx::types::{Uuid, ipnetwork::IpNetwork, time::OffsetDateTime};
+use std::{net::IpAddr, sync::Arc};
+use util::AppError;
+
+/// security relevant change made to an account, e.g. `sign_in` or `password_changed`
+#[derive(Clone, Debug)]
+pub struct AuditEvent {
+    pub kind: String,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<IpAddr>,
+    pub created_at: OffsetDateTime,
+}
+
+// implementation block for the audit trail of the accounts
+impl crate::Db {
+    /// records `kind` for the user, with the device it came from when it's known
+    pub async fn record_event(
+        self: &Arc<Self>,
+        user_id: Uuid,
+        kind: &str,
+        user_agent: Option<&str>,
+        ip_address: Option<IpAddr>,
+    ) -> Result<(), AppError> {
+        sqlx::query!(
+            "INSERT INTO audit_events (id, user_id, kind, user_agent, ip_address)
+            VALUES ($1, $2, $3, $4, $5)",
+            Uuid::new_v4(),
+            user_id,
+            kind,
+            user_agent,
+            ip_address.map(IpNetwork::from)
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+        Ok(())
+    }
+
+    /// the recorded events of the user, latest first
+    pub async fn get_events(self: &Arc<Self>, user_id: Uuid) -> Result<Vec<AuditEvent>, AppError> {
+        let rows = sqlx::query!(
+            "SELECT kind, user_agent, ip_address, created_at FROM audit_events
+            WHERE user_id = $1 ORDER BY created_at DESC",
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| AuditEvent {
+                kind: r.kind,
+                user_agent: r.user_agent,
+                ip_address: r.ip_address.map(|v| v.ip()),
+                created_at: r.created_at,
+            })
+            .collect())
+    }
+}
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use sha1::{Digest, Sha1};
 use tokio::sync::RwLock;
-use util::AppError;
+use util::{AppError, config::BucketConfig};
 
 pub struct BlackBlazeB2 {
     pub client: reqwest::Client,
@@ -37,23 +37,21 @@ struct GetUploadUrlResponse {
     authorization_token: String,
 }
 
-impl Default for BlackBlazeB2 {
-    fn default() -> Self {
+impl BlackBlazeB2 {
+    pub fn new(config: &BucketConfig) -> Self {
         Self {
             client: reqwest::Client::new(),
-            account_id: std::env::var("BUCKET_ACCESS_KEY").unwrap(),
-            application_key: std::env::var("BUCKET_SECRET_KEY").unwrap(),
-            endpoint: std::env::var("BUCKET_ENDPOINT").unwrap(),
+            account_id: config.access_key.clone(),
+            application_key: config.secret_key.clone(),
+            endpoint: config.endpoint.clone(),
             auth_token: RwLock::new(None),
-            api_url: RwLock::new(std::env::var("BUCKET_ENDPOINT").unwrap()),
-            name: std::env::var("BUCKET_NAME").unwrap(),
-            bucket_id: std::env::var("BUCKET_ID").unwrap(),
-            public_url: std::env::var("BUCKET_PUBLIC_URL").unwrap(),
+            api_url: RwLock::new(config.endpoint.clone()),
+            name: config.name.clone(),
+            bucket_id: config.id.clone(),
+            public_url: config.public_url.clone(),
         }
     }
-}
 
-impl BlackBlazeB2 {
     async fn get_auth_token(&self) -> Result<String, AppError> {
         // Check if we have a cached token
         {
@@ -195,6 +193,28 @@ impl BlackBlazeB2 {
         Ok(format!("{}/{}", self.public_url, filename))
     }
 
+    /// downloads `filename` through the public url, authorized so that it also works for files
+    /// that aren't public
+    pub async fn download_file(&self, filename: &str) -> Result<axum::body::Bytes, AppError> {
+        let auth_token = self.get_auth_token().await?;
+        let response = self
+            .client
+            .get(format!("{}/{}", self.public_url, filename))
+            .header("Authorization", &auth_token)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| {
+                tracing::error!("Failed to download {filename}: {e:#?}");
+                AppError::ServerError
+            })?;
+
+        response.bytes().await.map_err(|e| {
+            tracing::error!("Failed to read {filename}: {e:#?}");
+            AppError::ServerError
+        })
+    }
+
     pub async fn delete_file(&self, filename: &str) -> Result<(), AppError> {
         let auth_token = self.get_auth_token().await?;
         let api_url = self.api_url.read().await.clone();
@@ -31,6 +31,14 @@ impl crate::Db {
         filename = format!("banner/{_id}-{filename}");
         self.bucket.upload_file(data, &filename, &content_type).await
     }
+
+    /// deletes an uploaded icon or banner by its public url, other urls are left alone
+    pub async fn delete_media(self: &Arc<Self>, url: &str) -> Result<(), AppError> {
+        match url.strip_prefix(&format!("{}/", self.bucket.public_url)) {
+            Some(filename) => self.bucket.delete_file(filename).await,
+            None => Ok(()),
+        }
+    }
 }
 
 // TODO:
@@ -3,7 +3,7 @@
 use hmac::{Hmac, Mac};
 use sha2::{Digest, Sha256};
 use time::OffsetDateTime;
-use util::AppError;
+use util::{AppError, config::BucketConfig};
 
 pub struct CloudflareR2 {
     pub client: reqwest::Client,
@@ -15,21 +15,19 @@ pub struct CloudflareR2 {
     pub public_url: String,
 }
 
-impl Default for CloudflareR2 {
-    fn default() -> Self {
+impl CloudflareR2 {
+    pub fn new(config: &BucketConfig) -> Self {
         Self {
             client: reqwest::Client::new(),
-            access_key: std::env::var("BUCKET_ACCESS_KEY").unwrap(),
-            secret_key: std::env::var("BUCKET_SECRET_KEY").unwrap(),
-            endpoint: std::env::var("BUCKET_ENDPOINT").unwrap(),
-            region: std::env::var("BUCKET_REGION").unwrap(),
-            name: std::env::var("BUCKET_NAME").unwrap(),
-            public_url: std::env::var("BUCKET_PUBLIC_URL").unwrap(),
+            access_key: config.access_key.clone(),
+            secret_key: config.secret_key.clone(),
+            endpoint: config.endpoint.clone(),
+            region: config.region.clone(),
+            name: config.name.clone(),
+            public_url: config.public_url.clone(),
         }
     }
-}
 
-impl CloudflareR2 {
     fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
         let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC can take key of any size");
         mac.update(data);
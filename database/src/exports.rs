@@ -0,0 +1,184 @@
+use axum::body::Bytes;
+use sqlx::types::{Uuid, time::OffsetDateTime};
+use std::sync::Arc;
+use util::AppError;
+
+/// seconds a finished export can be downloaded for
+pub const EXPORT_TTL: u64 = 86400;
+
+// seconds after which an export still being prepared is considered lost, e.g. by a restart
+const PENDING_TTL: i64 = 3600;
+
+// implementation block for the personal data exports, one per user at a time
+//
+// the archives are kept in the bucket under `exports/`, named after the random id of the export
+impl crate::Db {
+    /// marks an export of the user as started, returns its id or `None` if one is already being
+    /// prepared
+    ///
+    /// the previous export of the user stays in the bucket until the new one replaces it
+    pub async fn start_export(self: &Arc<Self>, user_id: Uuid) -> Result<Option<String>, AppError> {
+        let id = util::generate::random_string(32);
+        let now = OffsetDateTime::now_utc();
+        // a single statement, so that concurrent requests can't both start an export
+        let started = sqlx::query_scalar!(
+            "INSERT INTO data_exports (user_id, id, status, created_at)
+            VALUES ($1, $2, 'pending', $3)
+            ON CONFLICT (user_id) DO UPDATE SET id = $2, status = 'pending', created_at = $3,
+                expires_at = NULL
+            WHERE data_exports.status <> 'pending' OR data_exports.created_at < $4
+            RETURNING id",
+            user_id,
+            id,
+            now,
+            now - time::Duration::seconds(PENDING_TTL)
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+        if started.is_none() {
+            return Ok(None);
+        }
+        self.record_event(user_id, "data_export_requested", None, None).await?;
+
+        tracing::info!("[Data Export Started] user_id: {user_id}");
+        Ok(started)
+    }
+
+    /// stores the finished `archive` of the export `id` in the bucket, replacing the previous
+    /// export of the user
+    pub async fn finish_export(
+        self: &Arc<Self>,
+        user_id: Uuid,
+        id: &str,
+        archive: Bytes,
+    ) -> Result<(), AppError> {
+        let size = archive.len();
+        let object = format!("exports/{user_id}-{id}.zip");
+        self.bucket.upload_file(archive, &object, "application/zip").await?;
+
+        let expires_at = OffsetDateTime::now_utc() + time::Duration::seconds(EXPORT_TTL as i64);
+        let mut tx = self.pool.begin().await.map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+        let previous = sqlx::query_scalar!(
+            "SELECT object FROM data_exports WHERE user_id = $1 AND id = $2 AND status = 'pending'
+            FOR UPDATE",
+            user_id,
+            id
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+        // the export was given up on in the meantime
+        let Some(previous) = previous else {
+            let _ = self.bucket.delete_file(&object).await;
+            return Err(AppError::NotFound);
+        };
+        sqlx::query!(
+            "UPDATE data_exports SET status = 'ready', object = $3, expires_at = $4
+            WHERE user_id = $1 AND id = $2",
+            user_id,
+            id,
+            object,
+            expires_at
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+        tx.commit().await.map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+
+        if let Some(previous) = previous {
+            self.delete_export_object(&previous).await;
+        }
+        tracing::info!("[Data Export Ready] user_id: {user_id}, size: {size}");
+        Ok(())
+    }
+
+    /// forgets the export `id` that couldn't be finished, so that it can be requested again
+    pub async fn fail_export(self: &Arc<Self>, user_id: Uuid, id: &str) -> Result<(), AppError> {
+        let object = sqlx::query_scalar!(
+            "DELETE FROM data_exports WHERE user_id = $1 AND id = $2 AND status = 'pending'
+            RETURNING object",
+            user_id,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+
+        if let Some(Some(object)) = object {
+            self.delete_export_object(&object).await;
+        }
+        Ok(())
+    }
+
+    /// returns the finished export `id` of the user, while it can still be downloaded
+    pub async fn get_export(self: &Arc<Self>, user_id: Uuid, id: &str) -> Result<Bytes, AppError> {
+        let object = sqlx::query_scalar!(
+            r#"SELECT object AS "object!" FROM data_exports
+            WHERE user_id = $1 AND id = $2 AND status = 'ready' AND expires_at > NOW()"#,
+            user_id,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?
+        .ok_or(AppError::NotFound)?;
+
+        self.bucket.download_file(&object).await
+    }
+
+    /// removes the exports that expired or were lost while being prepared, together with their
+    /// archives
+    ///
+    /// returns how many were removed
+    pub async fn purge_expired_exports(self: &Arc<Self>) -> Result<u64, AppError> {
+        let objects = sqlx::query_scalar!(
+            "DELETE FROM data_exports WHERE expires_at <= NOW()
+                OR (status = 'pending' AND created_at < $1)
+            RETURNING object",
+            OffsetDateTime::now_utc() - time::Duration::seconds(PENDING_TTL)
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+
+        for object in objects.iter().flatten() {
+            self.delete_export_object(object).await;
+        }
+        if !objects.is_empty() {
+            tracing::info!("[Expired Data Exports Purged] count: {}", objects.len());
+        }
+        Ok(objects.len() as u64)
+    }
+
+    // a leftover archive is only logged, it can't be downloaded without its row
+    pub(crate) async fn delete_export_object(self: &Arc<Self>, object: &str) {
+        if let Err(e) = self.bucket.delete_file(object).await {
+            tracing::error!("Failed to delete the data export {object}: {e:?}");
+        }
+    }
+}
@@ -1,17 +1,22 @@
 use moka::sync::Cache;
 use std::{sync::Arc, time::Duration};
-use tokio::sync::OnceCell;
-use util::session::Session;
+use util::{config::Config, session::Session};
 
 mod active;
 pub mod applications;
+pub mod audit;
 pub mod bucket;
+pub mod exports;
+pub mod migrations;
+pub mod outbox;
 pub mod sessions;
+pub mod signing_keys;
 pub mod users;
 
 pub type UserData = Arc<std::sync::Mutex<(users::User, Vec<Session>)>>;
 
 pub struct Db {
+    config: Arc<Config>,
     pool: sqlx::Pool<sqlx::Postgres>,
     bucket: bucket::BlackBlazeB2,
     // in memory stores
@@ -19,30 +24,36 @@ pub struct Db {
     applications: applications::Applications,
 }
 
-static DB: OnceCell<Arc<Db>> = OnceCell::const_new();
-
 impl Db {
-    pub async fn new() -> Arc<Self> {
-        DB.get_or_init(|| async {
-            // establishing connection with postgresql database
-            let pool = sqlx::postgres::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
-                .await
-                .unwrap();
-
-            sqlx::migrate!("../.migrations").run(&pool).await.unwrap();
-
-            Arc::new(Db {
-                pool,
-                bucket: bucket::BlackBlazeB2::default(),
-                active: Cache::builder()
-                    .max_capacity(32728)
-                    .time_to_live(Duration::from_secs(Session::MEM_CACHE_DURATION))
-                    .build(),
-                applications: applications::Applications::new(),
-            })
-        })
-        .await
-        .clone()
+    /// connects to `database.url` and applies the pending migrations, `config` must have been
+    /// validated
+    ///
+    /// the server shares the returned `Db` between its routers, as the in memory stores live in it
+    pub async fn new(config: Arc<Config>) -> Arc<Self> {
+        // establishing connection with postgresql database
+        let pool = sqlx::postgres::PgPool::connect(&config.database.url).await.unwrap();
+
+        migrations::MIGRATOR.run(&pool).await.unwrap();
+
+        let db = Arc::new(Db {
+            bucket: bucket::BlackBlazeB2::new(&config.bucket),
+            config,
+            pool,
+            active: Cache::builder()
+                .max_capacity(32728)
+                .time_to_live(Duration::from_secs(Session::MEM_CACHE_DURATION))
+                .build(),
+            applications: applications::Applications::new(),
+        });
+
+        // session cookies can't be verified before the signing keys are loaded
+        db.load_signing_keys().await.unwrap();
+        db
+    }
+
+    /// the configuration the server was started with
+    pub fn config(&self) -> &Config {
+        &self.config
     }
 
     pub fn logged_users_count(self: &Arc<Self>) -> u64 {
@@ -0,0 +1,65 @@
+use sqlx::{migrate::Migrator, types::time::OffsetDateTime};
+use util::{AppError, config::DatabaseConfig};
+
+/// migrations embedded from `.migrations`, `Db::new` applies the pending ones
+pub static MIGRATOR: Migrator = sqlx::migrate!("../.migrations");
+
+pub struct MigrationStatus {
+    pub version: i64,
+    pub description: String,
+    /// None if the migration is still pending
+    pub installed_on: Option<OffsetDateTime>,
+}
+
+/// connects to `database.url` without applying any migration
+pub async fn connect(config: &DatabaseConfig) -> Result<sqlx::PgPool, AppError> {
+    sqlx::postgres::PgPool::connect(&config.url).await.map_err(|e| {
+        tracing::error!("{:?}", e);
+        AppError::ServerError
+    })
+}
+
+/// applies the pending migrations
+pub async fn run(pool: &sqlx::PgPool) -> Result<(), AppError> {
+    MIGRATOR.run(pool).await.map_err(|e| {
+        tracing::error!("{:?}", e);
+        AppError::ServerError
+    })?;
+
+    tracing::info!("[Migrations Applied] latest: {:?}", MIGRATOR.iter().last().map(|m| m.version));
+    Ok(())
+}
+
+/// lists every embedded migration together with the time it was applied
+pub async fn status(pool: &sqlx::PgPool) -> Result<Vec<MigrationStatus>, AppError> {
+    let map_err = |e: sqlx::Error| {
+        tracing::error!("{:?}", e);
+        AppError::ServerError
+    };
+    // the table is only created by the first run
+    let is_tracked =
+        sqlx::query_scalar!(r#"SELECT to_regclass('_sqlx_migrations') IS NOT NULL AS "exists!""#)
+            .fetch_one(pool)
+            .await
+            .map_err(map_err)?;
+    let mut installed: Vec<(i64, OffsetDateTime)> = Vec::new();
+    if is_tracked {
+        installed =
+            sqlx::query!(r#"SELECT version, installed_on FROM _sqlx_migrations WHERE success"#)
+                .fetch_all(pool)
+                .await
+                .map_err(map_err)?
+                .into_iter()
+                .map(|row| (row.version, row.installed_on))
+                .collect();
+    }
+
+    Ok(MIGRATOR
+        .iter()
+        .map(|m| MigrationStatus {
+            version: m.version,
+            description: m.description.to_string(),
+            installed_on: installed.iter().find(|(v, _)| *v == m.version).map(|(_, t)| *t),
+        })
+        .collect())
+}
@@ -0,0 +1,274 @@
+use sqlx::types::{Uuid, time::OffsetDateTime};
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+use util::{
+    AppError,
+    mail::{Email, Template},
+};
+
+/// mails are dead-lettered after failing this many times
+pub const MAX_ATTEMPTS: i32 = 8;
+// number of mails claimed by the worker on every tick
+const BATCH_SIZE: i64 = 32;
+// claimed mails are retried by any worker after this lease runs out
+const LEASE_DURATION: time::Duration = time::Duration::minutes(5);
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+static WORKER_STARTED: AtomicBool = AtomicBool::new(false);
+
+/// a mail inside the outbox, returned to the admins
+#[derive(Debug)]
+pub struct OutboxEmail {
+    pub id: Uuid,
+    pub idempotency_key: String,
+    pub to_email: String,
+    pub subject: String,
+    pub status: String,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub created_at: OffsetDateTime,
+    pub next_attempt_at: OffsetDateTime,
+    pub sent_at: Option<OffsetDateTime>,
+}
+
+/// delay before the next attempt, doubling from 30 seconds up to 1 hour
+pub fn backoff(attempts: i32) -> time::Duration {
+    let secs = 30i64.saturating_mul(1 << attempts.clamp(0, 7));
+    time::Duration::seconds(secs.min(3600))
+}
+
+impl crate::Db {
+    /// renders `template` and queues it for delivery to `to_email`
+    ///
+    /// a mail with an already queued `idempotency_key` is ignored, so retried requests don't
+    /// send the same mail twice
+    pub async fn enqueue_email(
+        self: &Arc<Self>,
+        idempotency_key: String,
+        to_email: String,
+        template: Template,
+        locale: Option<&str>,
+        vars: &[(&str, &str)],
+    ) -> Result<(), AppError> {
+        shared::validation::is_email_valid(&to_email)?;
+        let email = util::mail::render(template, locale, vars);
+        let now = OffsetDateTime::now_utc();
+
+        let result = sqlx::query!(
+            r#"INSERT INTO email_outbox (
+                id, idempotency_key, to_email, subject, text_body, html_body, created_at, next_attempt_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $7)
+            ON CONFLICT (idempotency_key) DO NOTHING"#,
+            Uuid::new_v4(),
+            idempotency_key,
+            to_email,
+            email.subject,
+            email.text,
+            email.html,
+            now
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+
+        if result.rows_affected() == 0 {
+            tracing::info!("[Mail Already Queued] Key: {idempotency_key}");
+        } else {
+            tracing::info!("[Mail Queued] To: {to_email}, Template: {}", template.name());
+        }
+        Ok(())
+    }
+
+    /// delivers the pending mails that are due, returns the number of mails claimed
+    pub async fn process_outbox(self: &Arc<Self>) -> Result<usize, AppError> {
+        // claiming the mails by pushing their next attempt out, so that other workers skip them
+        let rows = sqlx::query!(
+            r#"UPDATE email_outbox SET next_attempt_at = $1
+            WHERE id IN (
+                SELECT id FROM email_outbox
+                WHERE status = 'pending' AND next_attempt_at <= NOW()
+                ORDER BY next_attempt_at
+                LIMIT $2
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, to_email, subject, text_body, html_body, attempts"#,
+            OffsetDateTime::now_utc() + LEASE_DURATION,
+            BATCH_SIZE
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+
+        let claimed = rows.len();
+        for row in rows {
+            let email = Email { subject: row.subject, text: row.text_body, html: row.html_body };
+            match util::mail::deliver(row.to_email.clone(), email).await {
+                Ok(()) => self.mark_email_sent(row.id).await?,
+                Err(e) => self.mark_email_failed(row.id, row.attempts + 1, &e).await?,
+            }
+        }
+        Ok(claimed)
+    }
+
+    // the bodies are cleared, they hold codes and sign-in links that must not outlive the mail
+    async fn mark_email_sent(self: &Arc<Self>, id: Uuid) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"UPDATE email_outbox
+            SET status = 'sent', attempts = attempts + 1, last_error = NULL, sent_at = NOW(),
+                text_body = '', html_body = ''
+            WHERE id = $1"#,
+            id
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+
+        tracing::info!("[Mail Sent] Id: {id}");
+        Ok(())
+    }
+
+    async fn mark_email_failed(
+        self: &Arc<Self>,
+        id: Uuid,
+        attempts: i32,
+        error: &str,
+    ) -> Result<(), AppError> {
+        let status = if attempts >= MAX_ATTEMPTS { "dead" } else { "pending" };
+        sqlx::query!(
+            r#"UPDATE email_outbox
+            SET status = $1, attempts = $2, last_error = $3, next_attempt_at = $4
+            WHERE id = $5"#,
+            status,
+            attempts,
+            error,
+            OffsetDateTime::now_utc() + backoff(attempts),
+            id
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+
+        if status == "dead" {
+            tracing::error!("[Mail Dead-Lettered] Id: {id}, Attempts: {attempts}, Error: {error}");
+        } else {
+            tracing::warn!("[Mail Failed] Id: {id}, Attempts: {attempts}, Error: {error}");
+        }
+        Ok(())
+    }
+
+    /// starts the background task delivering the queued mails, only once per process
+    pub fn spawn_outbox_worker(self: &Arc<Self>) {
+        if WORKER_STARTED.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let db = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(POLL_INTERVAL);
+            loop {
+                interval.tick().await;
+                // draining the outbox before waiting for the next tick
+                while let Ok(claimed) = db.process_outbox().await {
+                    if claimed < BATCH_SIZE as usize {
+                        break;
+                    }
+                }
+            }
+        });
+    }
+
+    /// returns the mails with the given status, most recent first
+    pub async fn get_outbox_emails(
+        self: &Arc<Self>,
+        status: &str,
+        limit: i64,
+    ) -> Result<Vec<OutboxEmail>, AppError> {
+        sqlx::query_as!(
+            OutboxEmail,
+            r#"SELECT id, idempotency_key, to_email, subject, status, attempts, last_error,
+                created_at, next_attempt_at, sent_at
+            FROM email_outbox WHERE status = $1
+            ORDER BY created_at DESC LIMIT $2"#,
+            status,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })
+    }
+
+    /// returns every mail addressed to `to_email`, oldest first
+    pub async fn get_emails_to(
+        self: &Arc<Self>,
+        to_email: &str,
+    ) -> Result<Vec<OutboxEmail>, AppError> {
+        sqlx::query_as!(
+            OutboxEmail,
+            r#"SELECT id, idempotency_key, to_email, subject, status, attempts, last_error,
+                created_at, next_attempt_at, sent_at
+            FROM email_outbox WHERE to_email = $1
+            ORDER BY created_at"#,
+            to_email
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })
+    }
+
+    /// moves a dead-lettered mail back to the queue with a fresh set of attempts
+    pub async fn retry_outbox_email(self: &Arc<Self>, id: Uuid) -> Result<(), AppError> {
+        let result = sqlx::query!(
+            r#"UPDATE email_outbox SET status = 'pending', attempts = 0, next_attempt_at = NOW()
+            WHERE id = $1 AND status = 'dead'"#,
+            id
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound);
+        }
+        tracing::info!("[Mail Requeued] Id: {id}");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_test() {
+        assert_eq!(backoff(0), time::Duration::seconds(30));
+        assert_eq!(backoff(1), time::Duration::seconds(60));
+        assert_eq!(backoff(3), time::Duration::seconds(240));
+        assert_eq!(backoff(7), time::Duration::hours(1));
+        assert_eq!(backoff(MAX_ATTEMPTS), time::Duration::hours(1));
+    }
+}
@@ -1,5 +1,8 @@
+mod origins;
+mod reports;
+
 use crate::users::User;
-use sqlx::types::{Uuid, ipnetwork::IpNetwork};
+use sqlx::types::{Uuid, ipnetwork::IpNetwork, time::OffsetDateTime};
 use std::sync::Arc;
 use util::{
     AppError,
@@ -31,6 +34,9 @@ impl crate::Db {
             created_at: row.created_at,
             last_used: row.last_used,
             expires_at: row.expires_at,
+            login_at: row.login_at,
+            authenticated_at: row.authenticated_at,
+            persistent: row.persistent,
         })
     }
 
@@ -43,8 +49,9 @@ impl crate::Db {
             r#"SELECT 
                 u.id as user_id, u.display_name, u.email, u.birth_date, u.password, 
                 u.username, u.banner, u.icon, u.bio, u.legal_name, u.gender, 
-                u.phone, u.country, u.oauth_provider, u.created, s.unsigned_ssid,
-                s.user_agent, s.ip_address, s.created_at, s.last_used, s.expires_at
+                u.phone, u.country, u.locale, u.oauth_provider, u.created, u.role, s.unsigned_ssid,
+                s.user_agent, s.ip_address, s.created_at, s.last_used, s.expires_at,
+                s.login_at, s.authenticated_at, s.persistent
             FROM users u
             INNER JOIN sessions s ON s.user_id = u.id
             WHERE s.unsigned_ssid = $1 AND s.expires_at > NOW()"#,
@@ -72,8 +79,10 @@ impl crate::Db {
             gender: row.gender,
             phone: row.phone,
             country: row.country,
+            locale: row.locale,
             oauth_provider: util::oauth::OAuthProvider::from(row.oauth_provider.as_str()),
             created: row.created,
+            role: row.role,
         };
 
         let session = Session {
@@ -83,6 +92,9 @@ impl crate::Db {
             created_at: row.created_at,
             last_used: row.last_used,
             expires_at: row.expires_at,
+            login_at: row.login_at,
+            authenticated_at: row.authenticated_at,
+            persistent: row.persistent,
         };
 
         Ok((user, session))
@@ -96,8 +108,9 @@ impl crate::Db {
     ) -> Result<(), AppError> {
         sqlx::query!(
             r#"INSERT INTO sessions (
-                unsigned_ssid, user_id, user_agent, ip_address, created_at, last_used, expires_at
-            ) VALUES ($1, $2, $3, $4, $5, $6, $7)"#,
+                unsigned_ssid, user_id, user_agent, ip_address, created_at, last_used, expires_at,
+                login_at, authenticated_at, persistent
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)"#,
             session.unsigned_ssid,
             user_id,
             session.user_agent,
@@ -105,6 +118,9 @@ impl crate::Db {
             session.created_at,
             session.last_used,
             session.expires_at,
+            session.login_at,
+            session.authenticated_at,
+            session.persistent,
         )
         .execute(&self.pool)
         .await
@@ -112,11 +128,75 @@ impl crate::Db {
             tracing::error!("{:?}", e);
             AppError::ServerError
         })?;
+        self.add_session_origin(user_id, &session).await?;
+        self.record_event(
+            user_id,
+            "sign_in",
+            session.user_agent.as_deref(),
+            Some(session.ip_address),
+        )
+        .await?;
 
         tracing::info!("[Session Added] user_id: {user_id}, session_id: {}", session.unsigned_ssid);
         Ok(())
     }
 
+    /// sets `last_used` of the session that matches `unsigned_ssid`
+    ///
+    /// returns false if the session doesn't exist anymore, e.g. it was revoked by another process
+    pub async fn touch_session(
+        self: &Arc<Self>,
+        unsigned_ssid: Uuid,
+        last_used: OffsetDateTime,
+    ) -> Result<bool, AppError> {
+        let result = sqlx::query!(
+            r#"UPDATE sessions SET last_used = $1 WHERE unsigned_ssid = $2"#,
+            last_used,
+            unsigned_ssid
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// marks the session as authenticated now, in the database and in `Db::active`
+    pub async fn reauthenticate_session(
+        self: &Arc<Self>,
+        user_id: Uuid,
+        unsigned_ssid: Uuid,
+    ) -> Result<OffsetDateTime, AppError> {
+        let now = OffsetDateTime::now_utc();
+        let result = sqlx::query!(
+            r#"UPDATE sessions SET authenticated_at = $1 WHERE unsigned_ssid = $2 AND user_id = $3"#,
+            now,
+            unsigned_ssid,
+            user_id
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+        if result.rows_affected() == 0 {
+            return Err(AppError::SessionExpired);
+        }
+
+        if let Some(arc_wrapped) = self.active.get(&user_id) {
+            let mut guard = arc_wrapped.lock().unwrap();
+            if let Some(s) = guard.1.iter_mut().find(|s| s.unsigned_ssid == unsigned_ssid) {
+                s.authenticated_at = now;
+            }
+        }
+
+        tracing::info!("[Session Re-authenticated] user_id: {user_id}, session_id: {unsigned_ssid}");
+        Ok(now)
+    }
+
     /// removes the session that matches `unsigned_ssid`
     pub async fn remove_session(
         self: &Arc<Self>,
@@ -187,6 +267,21 @@ impl crate::Db {
         Ok(())
     }
 
+    /// removes every session of User with `user_id` and drops the user from `Db::active`
+    pub async fn remove_every_session(self: &Arc<Self>, user_id: Uuid) -> Result<(), AppError> {
+        sqlx::query!(r#"DELETE FROM sessions WHERE user_id = $1"#, user_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                tracing::error!("{:?}", e);
+                AppError::ServerError
+            })?;
+        self.active.remove(&user_id);
+
+        tracing::info!("[Every Session Removed] user_id: {}", user_id);
+        Ok(())
+    }
+
     /// removes all the expired sessions of User with `user_id`
     pub async fn clear_expired_sessions(self: &Arc<Self>, user_id: Uuid) -> Result<(), AppError> {
         sqlx::query!(r#"DELETE FROM sessions WHERE user_id = $1 AND expires_at <= NOW()"#, user_id)
@@ -200,4 +295,81 @@ impl crate::Db {
         tracing::info!("[Expired Sessions Cleared] user_id: {}", user_id);
         Ok(())
     }
+
+    /// returns every unexpired session of User with `user_id`, latest first
+    pub async fn get_sessions(self: &Arc<Self>, user_id: Uuid) -> Result<Vec<Session>, AppError> {
+        let rows = sqlx::query!(
+            r#"SELECT * FROM sessions WHERE user_id = $1 AND expires_at > NOW()
+            ORDER BY last_used DESC"#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| Session {
+                unsigned_ssid: row.unsigned_ssid,
+                user_agent: row.user_agent,
+                ip_address: row.ip_address.ip(),
+                created_at: row.created_at,
+                last_used: row.last_used,
+                expires_at: row.expires_at,
+                login_at: row.login_at,
+                authenticated_at: row.authenticated_at,
+                persistent: row.persistent,
+            })
+            .collect())
+    }
+
+    /// returns every session of User with `user_id`, including the expired ones not purged yet,
+    /// latest first, for the data export
+    pub async fn get_all_sessions(
+        self: &Arc<Self>,
+        user_id: Uuid,
+    ) -> Result<Vec<Session>, AppError> {
+        let rows = sqlx::query!(
+            r#"SELECT * FROM sessions WHERE user_id = $1 ORDER BY last_used DESC"#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| Session {
+                unsigned_ssid: row.unsigned_ssid,
+                user_agent: row.user_agent,
+                ip_address: row.ip_address.ip(),
+                created_at: row.created_at,
+                last_used: row.last_used,
+                expires_at: row.expires_at,
+                login_at: row.login_at,
+                authenticated_at: row.authenticated_at,
+                persistent: row.persistent,
+            })
+            .collect())
+    }
+
+    /// removes the expired sessions of every user, returns how many were removed
+    pub async fn purge_expired_sessions(self: &Arc<Self>) -> Result<u64, AppError> {
+        let result = sqlx::query!(r#"DELETE FROM sessions WHERE expires_at <= NOW()"#)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                tracing::error!("{:?}", e);
+                AppError::ServerError
+            })?;
+
+        tracing::info!("[Expired Sessions Purged] count: {}", result.rows_affected());
+        Ok(result.rows_affected())
+    }
 }
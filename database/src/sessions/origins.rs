@@ -0,0 +1,68 @@
+use sqlx::types::{Uuid, ipnetwork::IpNetwork};
+use std::{net::IpAddr, sync::Arc};
+use util::{AppError, session::Session};
+
+// implementation block for the places users sign in from
+impl crate::Db {
+    /// returns the user agent and ip address the User with `user_id` signed in from, latest first
+    pub async fn get_session_origins(
+        self: &Arc<Self>,
+        user_id: Uuid,
+    ) -> Result<Vec<(Option<String>, IpAddr)>, AppError> {
+        let rows = sqlx::query!(
+            r#"SELECT user_agent, ip_address FROM sign_in_origins
+            WHERE user_id = $1 ORDER BY last_seen DESC LIMIT 64"#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| ((!r.user_agent.is_empty()).then_some(r.user_agent), r.ip_address.ip()))
+            .collect())
+    }
+
+    /// remembers where `session` was created from, called whenever a session is added
+    pub(super) async fn add_session_origin(
+        self: &Arc<Self>,
+        user_id: Uuid,
+        session: &Session,
+    ) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"INSERT INTO sign_in_origins (user_id, user_agent, ip_address, last_seen)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (user_id, user_agent, ip_address) DO UPDATE SET last_seen = $4"#,
+            user_id,
+            session.user_agent.as_deref().unwrap_or_default(),
+            IpNetwork::from(session.ip_address),
+            session.created_at
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+        Ok(())
+    }
+
+    /// forgets where the user signed in from, once the account may have been taken over, so that
+    /// every following sign-in is reported
+    pub async fn clear_session_origins(self: &Arc<Self>, user_id: Uuid) -> Result<(), AppError> {
+        sqlx::query!("DELETE FROM sign_in_origins WHERE user_id = $1", user_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                tracing::error!("{:?}", e);
+                AppError::ServerError
+            })?;
+
+        tracing::info!("[Session Origins Cleared] user_id: {user_id}");
+        Ok(())
+    }
+}
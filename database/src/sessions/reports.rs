@@ -0,0 +1,81 @@
+use sqlx::types::{Uuid, time::OffsetDateTime};
+use std::sync::Arc;
+use util::AppError;
+
+// implementation block for the "this wasn't me" links of unfamiliar sign-ins
+impl crate::Db {
+    /// stores a random nonce for the link reporting the session `unsigned_ssid`, and returns it
+    pub async fn create_sign_in_report(
+        self: &Arc<Self>,
+        user_id: Uuid,
+        unsigned_ssid: Uuid,
+        expires_at: OffsetDateTime,
+    ) -> Result<String, AppError> {
+        let nonce = util::generate::random_string(48);
+        // the links nobody used are dropped along the way
+        sqlx::query!("DELETE FROM sign_in_reports WHERE expires_at <= NOW()")
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                tracing::error!("{:?}", e);
+                AppError::ServerError
+            })?;
+        sqlx::query!(
+            "INSERT INTO sign_in_reports (nonce, user_id, unsigned_ssid, expires_at)
+            VALUES ($1, $2, $3, $4)",
+            nonce,
+            user_id,
+            unsigned_ssid,
+            expires_at
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+
+        Ok(nonce)
+    }
+
+    /// uses up the link with `nonce`, returns the user id and the reported session
+    pub async fn consume_sign_in_report(
+        self: &Arc<Self>,
+        nonce: &str,
+    ) -> Result<(Uuid, Uuid), AppError> {
+        let row = sqlx::query!(
+            "DELETE FROM sign_in_reports WHERE nonce = $1 AND expires_at > NOW()
+            RETURNING user_id, unsigned_ssid",
+            nonce
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?
+        .ok_or(AppError::BadReq("This link has expired or was already used"))?;
+
+        Ok((row.user_id, row.unsigned_ssid))
+    }
+
+    /// the sessions the user can still report with a sent link, and until when
+    pub async fn get_sign_in_reports(
+        self: &Arc<Self>,
+        user_id: Uuid,
+    ) -> Result<Vec<(Uuid, OffsetDateTime)>, AppError> {
+        let rows = sqlx::query!(
+            "SELECT unsigned_ssid, expires_at FROM sign_in_reports
+            WHERE user_id = $1 AND expires_at > NOW() ORDER BY expires_at DESC",
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+
+        Ok(rows.into_iter().map(|r| (r.unsigned_ssid, r.expires_at)).collect())
+    }
+}
@@ -0,0 +1,233 @@
+use ring::aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey};
+use sha2::{Digest, Sha256};
+use sqlx::types::time::OffsetDateTime;
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+use util::{
+    AppError,
+    session::keyring::{self, LEGACY_KEY_ID, SigningKey},
+};
+
+// other instances pick up rotated keys within this interval
+const REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+static REFRESHER_STARTED: AtomicBool = AtomicBool::new(false);
+
+/// a signing key without its secret, returned to the admins
+#[derive(Debug)]
+pub struct SigningKeyInfo {
+    pub id: String,
+    pub created_at: OffsetDateTime,
+    pub retired_at: Option<OffsetDateTime>,
+}
+
+impl crate::Db {
+    /// loads the active signing keys into `util::session::keyring`
+    ///
+    /// the keys stored before they were encrypted, retired ones included, are encrypted on the way
+    pub async fn load_signing_keys(self: &Arc<Self>) -> Result<(), AppError> {
+        let rows = sqlx::query!(
+            r#"SELECT id, secret, encrypted, retired_at FROM signing_keys ORDER BY created_at, id"#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+
+        let encryption_key = &self.config.service.signing_keys_key;
+        let retire_legacy = rows.iter().any(|r| r.id == LEGACY_KEY_ID && r.retired_at.is_some());
+        let mut keys = Vec::new();
+        for r in rows.into_iter().filter(|r| r.id != LEGACY_KEY_ID) {
+            if r.retired_at.is_some() {
+                if !r.encrypted {
+                    self.encrypt_signing_key(&r.id, &r.secret).await?;
+                }
+                continue;
+            }
+            let secret = if r.encrypted {
+                open(encryption_key, &r.id, &r.secret).ok_or_else(|| {
+                    tracing::error!(
+                        "Failed to decrypt the signing key {}, was the key changed?",
+                        r.id
+                    );
+                    AppError::ServerError
+                })?
+            } else {
+                self.encrypt_signing_key(&r.id, &r.secret).await?;
+                r.secret
+            };
+            keys.push(SigningKey { id: r.id, secret });
+        }
+        keyring::set_keys(keys, retire_legacy);
+        Ok(())
+    }
+
+    // replaces the plaintext secret of the key `id` stored before the keys were encrypted
+    async fn encrypt_signing_key(self: &Arc<Self>, id: &str, secret: &[u8]) -> Result<(), AppError> {
+        let sealed = seal(&self.config.service.signing_keys_key, id, secret);
+        sqlx::query!(
+            r#"UPDATE signing_keys SET secret = $2, encrypted = TRUE
+            WHERE id = $1 AND encrypted = FALSE"#,
+            id,
+            sealed
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+
+        tracing::info!("[Signing Key Encrypted] Id: {id}");
+        Ok(())
+    }
+
+    /// adds a new signing key that signs every new or re-signed cookie, returns its id
+    pub async fn rotate_signing_key(self: &Arc<Self>) -> Result<String, AppError> {
+        let id = util::generate::random_string(8);
+        let secret = util::generate::random_string(64).into_bytes();
+        sqlx::query!(
+            r#"INSERT INTO signing_keys (id, secret, encrypted, created_at)
+            VALUES ($1, $2, TRUE, $3)"#,
+            id,
+            seal(&self.config.service.signing_keys_key, &id, &secret),
+            OffsetDateTime::now_utc()
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+        self.load_signing_keys().await?;
+
+        tracing::info!("[Signing Key Rotated] Id: {id}");
+        Ok(id)
+    }
+
+    /// stops accepting cookies signed with the key `id`
+    pub async fn retire_signing_key(self: &Arc<Self>, id: &str) -> Result<(), AppError> {
+        if id == keyring::current_key_id() {
+            return Err(AppError::BadReq("The current signing key can't be retired, rotate first"));
+        }
+        let result = if id == LEGACY_KEY_ID {
+            // the legacy key isn't stored, so a row without secret marks it as retired
+            sqlx::query!(
+                r#"INSERT INTO signing_keys (id, secret, created_at, retired_at)
+                VALUES ($1, '', to_timestamp(0), NOW())
+                ON CONFLICT (id) DO NOTHING"#,
+                id
+            )
+            .execute(&self.pool)
+            .await
+        } else {
+            sqlx::query!(
+                r#"UPDATE signing_keys SET retired_at = NOW() WHERE id = $1 AND retired_at IS NULL"#,
+                id
+            )
+            .execute(&self.pool)
+            .await
+        }
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound);
+        }
+        self.load_signing_keys().await?;
+
+        tracing::info!("[Signing Key Retired] Id: {id}");
+        Ok(())
+    }
+
+    /// returns every stored signing key, oldest first
+    pub async fn get_signing_keys(self: &Arc<Self>) -> Result<Vec<SigningKeyInfo>, AppError> {
+        sqlx::query_as!(
+            SigningKeyInfo,
+            r#"SELECT id, created_at, retired_at FROM signing_keys ORDER BY created_at, id"#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })
+    }
+
+    /// starts the background task reloading the keys rotated by other instances
+    pub fn spawn_signing_key_refresher(self: &Arc<Self>) {
+        if REFRESHER_STARTED.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let db = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(REFRESH_INTERVAL);
+            loop {
+                interval.tick().await;
+                let _ = db.load_signing_keys().await;
+            }
+        });
+    }
+}
+
+// the cipher of the stored secrets, derived from `service.signing_keys_key`
+fn cipher(encryption_key: &str) -> LessSafeKey {
+    let key = Sha256::digest(format!("signing_keys:{encryption_key}"));
+    LessSafeKey::new(UnboundKey::new(&AES_256_GCM, &key).unwrap())
+}
+
+// encrypts the secret of the key `id`, returns the random nonce followed by the ciphertext and
+// its tag, the id is authenticated too so that secrets can't be swapped between rows
+fn seal(encryption_key: &str, id: &str, secret: &[u8]) -> Vec<u8> {
+    let nonce: [u8; NONCE_LEN] = rand::random();
+    let mut sealed = secret.to_vec();
+    cipher(encryption_key)
+        .seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(id.as_bytes()),
+            &mut sealed,
+        )
+        .unwrap();
+    [nonce.as_slice(), &sealed].concat()
+}
+
+// decrypts what `seal` returned, `None` if it was made with another key or for another id
+fn open(encryption_key: &str, id: &str, sealed: &[u8]) -> Option<Vec<u8>> {
+    let (nonce, sealed) = sealed.split_at_checked(NONCE_LEN)?;
+    let nonce = Nonce::try_assume_unique_for_key(nonce).ok()?;
+    let mut secret = sealed.to_vec();
+    let len = cipher(encryption_key)
+        .open_in_place(nonce, Aad::from(id.as_bytes()), &mut secret)
+        .ok()?
+        .len();
+    secret.truncate(len);
+    Some(secret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seal_test() {
+        let sealed = seal("key", "a1b2c3d4", b"secret");
+        assert!(!sealed.windows(6).any(|v| v == b"secret"));
+        assert_eq!(open("key", "a1b2c3d4", &sealed).as_deref(), Some(b"secret".as_slice()));
+        // a fresh nonce every time
+        assert_ne!(seal("key", "a1b2c3d4", b"secret"), sealed);
+
+        assert_eq!(open("other key", "a1b2c3d4", &sealed), None);
+        assert_eq!(open("key", "other id", &sealed), None);
+        assert_eq!(open("key", "a1b2c3d4", &sealed[..sealed.len() - 1]), None);
+        assert_eq!(open("key", "a1b2c3d4", &sealed[..4]), None);
+    }
+}
@@ -0,0 +1,190 @@
+use sqlx::types::{Uuid, time::OffsetDateTime};
+use std::sync::Arc;
+use util::AppError;
+
+/// how a user keeps away from another one
+#[derive(serde::Deserialize, serde::Serialize, Copy, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockKind {
+    Block, // both are hidden from each other
+    Mute,  // the muted user is hidden from the one muting, without noticing
+}
+
+impl From<&str> for BlockKind {
+    fn from(value: &str) -> Self {
+        match value {
+            "mute" => Self::Mute,
+            _ => Self::Block,
+        }
+    }
+}
+
+impl BlockKind {
+    pub fn get_str(&self) -> &'static str {
+        match self {
+            Self::Block => "block",
+            Self::Mute => "mute",
+        }
+    }
+}
+
+/// the relationship between a user and another one, in both directions
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct BlockStatus {
+    pub blocking: Option<BlockKind>,   // set by the user
+    pub blocked_by: Option<BlockKind>, // set by the other user
+}
+
+impl BlockStatus {
+    /// whether either of them blocked the other, muting doesn't count
+    pub fn is_blocked(&self) -> bool {
+        self.blocking == Some(BlockKind::Block) || self.blocked_by == Some(BlockKind::Block)
+    }
+
+    /// whether the other user should be hidden from the user
+    pub fn hides_other(&self) -> bool {
+        self.is_blocked() || self.blocking == Some(BlockKind::Mute)
+    }
+}
+
+pub struct BlockedUser {
+    pub id: Uuid,
+    pub username: String,
+    pub display_name: String,
+    pub kind: BlockKind,
+    pub created_at: OffsetDateTime,
+}
+
+// implementation block for blocking and muting users
+impl crate::Db {
+    /// blocks or mutes `blocked_id`, replacing what `blocker_id` set for it before
+    pub async fn block_user(
+        self: &Arc<Self>,
+        blocker_id: Uuid,
+        blocked_id: Uuid,
+        kind: BlockKind,
+    ) -> Result<(), AppError> {
+        sqlx::query!(
+            "INSERT INTO user_blocks (blocker_id, blocked_id, kind) VALUES ($1, $2, $3)
+            ON CONFLICT (blocker_id, blocked_id) DO UPDATE SET kind = $3, created_at = NOW()",
+            blocker_id,
+            blocked_id,
+            kind.get_str()
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+
+        tracing::info!("[User Blocked] {blocker_id} -> {blocked_id} ({})", kind.get_str());
+        Ok(())
+    }
+
+    pub async fn unblock_user(
+        self: &Arc<Self>,
+        blocker_id: Uuid,
+        blocked_id: Uuid,
+    ) -> Result<(), AppError> {
+        let result = sqlx::query!(
+            "DELETE FROM user_blocks WHERE blocker_id = $1 AND blocked_id = $2",
+            blocker_id,
+            blocked_id
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound);
+        }
+
+        tracing::info!("[User Unblocked] {blocker_id} -> {blocked_id}");
+        Ok(())
+    }
+
+    /// the users blocked or muted by `blocker_id`, latest first
+    pub async fn get_blocked_users(
+        self: &Arc<Self>,
+        blocker_id: Uuid,
+    ) -> Result<Vec<BlockedUser>, AppError> {
+        let rows = sqlx::query!(
+            "SELECT u.id, u.username, u.display_name, b.kind, b.created_at
+            FROM user_blocks b JOIN users u ON u.id = b.blocked_id
+            WHERE b.blocker_id = $1
+            ORDER BY b.created_at DESC",
+            blocker_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+
+        Ok(rows
+            .into_iter()
+            .map(|v| BlockedUser {
+                id: v.id,
+                username: v.username,
+                display_name: v.display_name,
+                kind: v.kind.as_str().into(),
+                created_at: v.created_at,
+            })
+            .collect())
+    }
+
+    /// how `user_id` and `other_id` block each other, for any feature that has to keep them apart
+    pub async fn get_block_status(
+        self: &Arc<Self>,
+        user_id: Uuid,
+        other_id: Uuid,
+    ) -> Result<BlockStatus, AppError> {
+        let rows = sqlx::query!(
+            "SELECT blocker_id, kind FROM user_blocks
+            WHERE (blocker_id = $1 AND blocked_id = $2) OR (blocker_id = $2 AND blocked_id = $1)",
+            user_id,
+            other_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+
+        let mut status = BlockStatus::default();
+        for row in rows {
+            if row.blocker_id == user_id {
+                status.blocking = Some(row.kind.as_str().into());
+            } else {
+                status.blocked_by = Some(row.kind.as_str().into());
+            }
+        }
+        Ok(status)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_status_test() {
+        let muting = BlockStatus { blocking: Some(BlockKind::Mute), blocked_by: None };
+        assert!(!muting.is_blocked());
+        assert!(muting.hides_other());
+
+        let muted = BlockStatus { blocking: None, blocked_by: Some(BlockKind::Mute) };
+        assert!(!muted.is_blocked());
+        assert!(!muted.hides_other());
+
+        let blocked = BlockStatus { blocking: None, blocked_by: Some(BlockKind::Block) };
+        assert!(blocked.is_blocked());
+        assert!(blocked.hides_other());
+        assert!(!BlockStatus::default().hides_other());
+    }
+}
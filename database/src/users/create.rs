@@ -8,8 +8,8 @@ impl crate::Db {
         let result = sqlx::query!(
             r#"INSERT INTO users (
                 id, display_name, email, birth_date, password, username, banner,
-                icon, bio, legal_name, gender, phone, country, oauth_provider, created
-            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)"#,
+                icon, bio, legal_name, gender, phone, country, locale, oauth_provider, created, role
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)"#,
             user.id,
             user.display_name,
             user.email,
@@ -23,8 +23,10 @@ impl crate::Db {
             user.gender,
             user.phone,
             user.country,
+            user.locale,
             user.oauth_provider.get_str(),
-            user.created
+            user.created,
+            user.role,
         )
         .execute(&self.pool)
         .await;
@@ -39,6 +41,8 @@ impl crate::Db {
                 if let Some(db_err) = e.as_database_error()
                     && db_err.code() == Some(std::borrow::Cow::Borrowed("23505"))
                 {
+                    // both `users_username_key` and the `users_username_skeleton_key` raised by
+                    // the skeleton trigger mean the username is taken
                     if db_err.message().contains("email") {
                         return Err(AppError::EmailTaken);
                     } else {
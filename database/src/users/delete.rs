@@ -1,9 +1,30 @@
-use crate::users::User;
-use std::sync::Arc;
+use crate::users::{DeletedUser, User};
+use sqlx::types::{Uuid, time::OffsetDateTime};
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
 use util::AppError;
 
+const PURGE_INTERVAL: Duration = Duration::from_secs(3600);
+
+static PURGER_STARTED: AtomicBool = AtomicBool::new(false);
+
 impl crate::Db {
-    pub async fn delete_user(self: &Arc<Self>, user: User) -> Result<(), AppError> {
+    /// moves the user to `deleted_users`, where it can be restored until the grace period ends
+    ///
+    /// the sessions of the user are removed with it, returns the purge time and the restore token
+    pub async fn delete_user(
+        self: &Arc<Self>,
+        user: User,
+    ) -> Result<(OffsetDateTime, String), AppError> {
+        let grace_days = self.config.service.deletion_grace_days as i64;
+        let purge_at = OffsetDateTime::now_utc() + time::Duration::days(grace_days);
+        let restore_token = util::generate::random_string(32);
+
         // Start a transaction for atomic operation
         let mut tx = self.pool.begin().await.map_err(|e| {
             tracing::error!("{:?}", e);
@@ -14,8 +35,11 @@ impl crate::Db {
         sqlx::query!(
             r#"INSERT INTO deleted_users (
                 id, display_name, email, birth_date, password, username, banner,
-                icon, bio, legal_name, gender, phone, country, oauth_provider, created
-            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)"#,
+                icon, bio, legal_name, gender, phone, country, locale, oauth_provider, created, role,
+                purge_at, restore_token
+            ) VALUES (
+                $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19
+            )"#,
             user.id,
             user.display_name,
             user.email,
@@ -29,8 +53,12 @@ impl crate::Db {
             user.gender,
             user.phone,
             user.country,
+            user.locale,
             user.oauth_provider.get_str(),
             user.created,
+            user.role,
+            purge_at,
+            restore_token,
         )
         .execute(&mut *tx)
         .await
@@ -52,8 +80,263 @@ impl crate::Db {
             tracing::error!("{:?}", e);
             AppError::ServerError
         })?;
+        self.record_event(user.id, "deletion_scheduled", None, None).await?;
+
+        tracing::info!(
+            "[User Deleted] Username: {}, Email: {}, Purge At: {purge_at}",
+            user.username,
+            user.email
+        );
+        Ok((purge_at, restore_token))
+    }
+
+    /// returns the deleted user that can still be restored, by its email or username
+    pub async fn get_restorable_user(
+        self: &Arc<Self>,
+        email_or_username: &str,
+    ) -> Result<DeletedUser, AppError> {
+        sqlx::query_as!(
+            DeletedUser,
+            // `oauth_provider` is nullable in `deleted_users` but always set when moved there
+            r#"SELECT id, display_name, email, birth_date, password, username, banner, icon, bio,
+                legal_name, gender, phone, country, locale, oauth_provider AS "oauth_provider!",
+                created, role, deleted, purge_at, restore_token
+            FROM deleted_users WHERE (email = $1 OR username = $1) AND purge_at > NOW()"#,
+            email_or_username
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?
+        .ok_or(AppError::UserNotFound)
+    }
+
+    /// moves a deleted user back to `users` before its grace period ends
+    pub async fn restore_user(self: &Arc<Self>, id: Uuid) -> Result<User, AppError> {
+        self.restore_user_where(Some(id), None).await
+    }
+
+    /// restores the deleted user `restore_token` was sent to
+    pub async fn restore_user_by_token(
+        self: &Arc<Self>,
+        restore_token: &str,
+    ) -> Result<User, AppError> {
+        self.restore_user_where(None, Some(restore_token)).await
+    }
+
+    async fn restore_user_where(
+        self: &Arc<Self>,
+        id: Option<Uuid>,
+        restore_token: Option<&str>,
+    ) -> Result<User, AppError> {
+        let mut tx = self.pool.begin().await.map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+
+        let user = sqlx::query_as!(
+            User,
+            r#"DELETE FROM deleted_users
+            WHERE (id = $1 OR restore_token = $2) AND purge_at > NOW()
+            RETURNING id, display_name, email, birth_date, password, username, banner, icon, bio,
+                legal_name, gender, phone, country, locale, oauth_provider AS "oauth_provider!",
+                created, role"#,
+            id,
+            restore_token
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?
+        .ok_or(AppError::UserNotFound)?;
+
+        // the email and username stay reserved during the grace period, see `is_email_available`
+        sqlx::query!(
+            r#"INSERT INTO users (
+                id, display_name, email, birth_date, password, username, banner,
+                icon, bio, legal_name, gender, phone, country, locale, oauth_provider, created, role
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)"#,
+            user.id,
+            user.display_name,
+            user.email,
+            user.birth_date,
+            user.password,
+            user.username,
+            user.banner,
+            user.icon,
+            user.bio,
+            user.legal_name,
+            user.gender,
+            user.phone,
+            user.country,
+            user.locale,
+            user.oauth_provider.get_str(),
+            user.created,
+            user.role,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(e) if e.constraint() == Some("users_email_key") => {
+                AppError::EmailTaken
+            }
+            sqlx::Error::Database(e)
+                if matches!(
+                    e.constraint(),
+                    Some("users_username_key" | "users_username_skeleton_key")
+                ) =>
+            {
+                AppError::UsernameTaken
+            }
+            _ => {
+                tracing::error!("{:?}", e);
+                AppError::ServerError
+            }
+        })?;
+
+        tx.commit().await.map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+        self.record_event(user.id, "account_restored", None, None).await?;
+
+        tracing::info!("[User Restored] Username: {}, Email: {}", user.username, user.email);
+        Ok(user)
+    }
+
+    /// permanently removes the users whose grace period has ended together with their media
+    ///
+    /// returns how many were removed
+    pub async fn purge_due_users(self: &Arc<Self>) -> Result<u64, AppError> {
+        let mut tx = self.pool.begin().await.map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+
+        let purged = sqlx::query!(
+            "DELETE FROM deleted_users WHERE purge_at <= NOW()
+            RETURNING id, username, email, icon, banner"
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+
+        // rows kept for a possible restore, they don't cascade from `users` like `sign_in_reports`
+        let ids = purged.iter().map(|v| v.id).collect::<Vec<_>>();
+        sqlx::query!("DELETE FROM sign_in_origins WHERE user_id = ANY($1)", &ids)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                tracing::error!("{:?}", e);
+                AppError::ServerError
+            })?;
+        sqlx::query!(
+            "DELETE FROM user_blocks WHERE blocker_id = ANY($1) OR blocked_id = ANY($1)",
+            &ids
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+        sqlx::query!("DELETE FROM username_history WHERE user_id = ANY($1)", &ids)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                tracing::error!("{:?}", e);
+                AppError::ServerError
+            })?;
+        sqlx::query!("DELETE FROM profile_visibility WHERE user_id = ANY($1)", &ids)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                tracing::error!("{:?}", e);
+                AppError::ServerError
+            })?;
+        sqlx::query!("DELETE FROM audit_events WHERE user_id = ANY($1)", &ids)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                tracing::error!("{:?}", e);
+                AppError::ServerError
+            })?;
+        sqlx::query!("DELETE FROM oauth_identities WHERE user_id = ANY($1)", &ids)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                tracing::error!("{:?}", e);
+                AppError::ServerError
+            })?;
+        let exports = sqlx::query_scalar!(
+            "DELETE FROM data_exports WHERE user_id = ANY($1) RETURNING object",
+            &ids
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+
+        // the mails are only keyed by address, which may belong to a new account by now
+        let emails = purged.iter().map(|v| v.email.clone()).collect::<Vec<_>>();
+        sqlx::query!(
+            "DELETE FROM email_outbox WHERE to_email = ANY($1)
+            AND NOT EXISTS (SELECT 1 FROM users WHERE users.email = email_outbox.to_email)",
+            &emails
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+
+        tx.commit().await.map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+
+        for object in exports.iter().flatten() {
+            self.delete_export_object(object).await;
+        }
+        for user in &purged {
+            for url in [&user.icon, &user.banner].into_iter().flatten() {
+                // a leftover file is only logged, the account itself is already gone
+                if let Err(e) = self.delete_media(url).await {
+                    tracing::error!("Failed to delete {url} of @{}: {e:?}", user.username);
+                }
+            }
+        }
+
+        if !purged.is_empty() {
+            tracing::info!("[Deleted Users Purged] count: {}", purged.len());
+        }
+        Ok(purged.len() as u64)
+    }
 
-        tracing::info!("[User Deleted] Username: {}, Email: {}", user.username, user.email);
-        Ok(())
+    /// starts the background task purging the users whose grace period has ended and the expired
+    /// data exports
+    pub fn spawn_deleted_user_purger(self: &Arc<Self>) {
+        if PURGER_STARTED.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let db = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(PURGE_INTERVAL);
+            loop {
+                interval.tick().await;
+                let _ = db.purge_due_users().await;
+                let _ = db.purge_expired_exports().await;
+            }
+        });
     }
 }
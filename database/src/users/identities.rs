@@ -0,0 +1,68 @@
+use sqlx::types::{Uuid, time::OffsetDateTime};
+use std::sync::Arc;
+use util::{AppError, oauth::OAuthProvider};
+
+/// account of an OpenID Connect provider linked to a user
+#[derive(Clone, Debug)]
+pub struct OAuthIdentity {
+    pub provider: String,
+    pub subject: String,
+    pub email: String,
+    pub linked_at: OffsetDateTime,
+    pub last_used: OffsetDateTime,
+}
+
+// implementation block for the provider accounts linked to users
+impl crate::Db {
+    /// remembers that the provider vouched for `subject` as the user, called on every sign-in with
+    /// the provider
+    pub async fn link_oauth_identity(
+        self: &Arc<Self>,
+        user_id: Uuid,
+        provider: OAuthProvider,
+        subject: &str,
+        email: &str,
+    ) -> Result<(), AppError> {
+        let linked = sqlx::query_scalar!(
+            r#"INSERT INTO oauth_identities (provider, subject, user_id, email)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (provider, subject) DO UPDATE SET user_id = $3, email = $4, last_used = NOW()
+            RETURNING (xmax = 0) AS "inserted!""#,
+            provider.get_str(),
+            subject,
+            user_id,
+            email
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+
+        if linked {
+            self.record_event(user_id, "identity_linked", None, None).await?;
+            tracing::info!("[Identity Linked] User ID: {user_id}, Provider: {}", provider.get_str());
+        }
+        Ok(())
+    }
+
+    /// the provider accounts linked to the user, latest first
+    pub async fn get_oauth_identities(
+        self: &Arc<Self>,
+        user_id: Uuid,
+    ) -> Result<Vec<OAuthIdentity>, AppError> {
+        sqlx::query_as!(
+            OAuthIdentity,
+            "SELECT provider, subject, email, linked_at, last_used FROM oauth_identities
+            WHERE user_id = $1 ORDER BY linked_at DESC",
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })
+    }
+}
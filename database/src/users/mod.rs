@@ -1,10 +1,19 @@
 use sqlx::types::time::OffsetDateTime;
 
+mod blocks;
 mod create;
 mod delete;
+mod identities;
 mod read;
+mod search;
 mod update_by_email;
 mod update_by_username;
+mod visibility;
+
+pub use blocks::{BlockKind, BlockStatus, BlockedUser};
+pub use identities::OAuthIdentity;
+pub use search::{SearchCursor, SearchFilters, SearchHit};
+pub use visibility::{ProfileVisibility, Visibility};
 
 macro_rules! user_struct {
     (
@@ -27,8 +36,10 @@ macro_rules! user_struct {
             pub gender: Option<String>,
             pub phone: Option<String>,
             pub country: Option<String>,
+            pub locale: Option<String>,
             pub oauth_provider: util::oauth::OAuthProvider,
             pub created: OffsetDateTime,
+            pub role: String,
             $(pub $extra_field: $extra_type,)*
         }
     };
@@ -36,4 +47,15 @@ macro_rules! user_struct {
 
 user_struct!(User {});
 
-user_struct!(DeletedUser { deleted: OffsetDateTime });
+impl User {
+    /// role used for authorization and session policies, `admin` or `user`
+    pub fn role(&self) -> &'static str {
+        if self.role == "admin" { "admin" } else { "user" }
+    }
+}
+
+user_struct!(DeletedUser {
+    deleted: OffsetDateTime,
+    purge_at: Option<OffsetDateTime>,
+    restore_token: Option<String>,
+});
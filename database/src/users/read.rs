@@ -1,4 +1,6 @@
 use super::User;
+use shared::validation::ValidationError;
+use sqlx::types::{Uuid, time::OffsetDateTime};
 use std::sync::Arc;
 use util::AppError;
 
@@ -10,28 +12,73 @@ impl crate::Db {
             return Err(AppError::EmailTaken);
         }
 
-        let exists =
-            sqlx::query_scalar!("SELECT EXISTS(SELECT 1 FROM users WHERE email = $1)", email)
-                .fetch_one(&self.pool)
-                .await
-                .map_err(|e| {
-                    tracing::error!("{:?}", e);
-                    AppError::ServerError
-                })?;
+        // a deleted account keeps its email until it can no longer be restored
+        let exists = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM users WHERE email = $1)
+                OR EXISTS(SELECT 1 FROM deleted_users WHERE email = $1 AND purge_at > NOW())"#,
+            email
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
 
         if exists.unwrap_or(false) { Err(AppError::EmailTaken) } else { Ok(()) }
     }
 
     // Check if username is available
     pub async fn is_username_available(&self, username: &str) -> Result<(), AppError> {
-        let exists =
-            sqlx::query_scalar!("SELECT EXISTS(SELECT 1 FROM users WHERE username = $1)", username)
-                .fetch_one(&self.pool)
-                .await
-                .map_err(|e| {
-                    tracing::error!("{:?}", e);
-                    AppError::ServerError
-                })?;
+        self.is_username_available_to(username, None).await
+    }
+
+    /// checks if `username` can be claimed by the user with `user_id` (None for new users), who
+    /// can take back the usernames it released during the cooldown
+    pub async fn is_username_available_to(
+        &self,
+        username: &str,
+        user_id: Option<Uuid>,
+    ) -> Result<(), AppError> {
+        let config = &self.config.username;
+        let skeleton = shared::validation::username_skeleton(username);
+        if shared::validation::is_username_reserved(username)
+            || config.reserved.iter().any(|v| shared::validation::username_skeleton(v) == skeleton)
+        {
+            return Err(
+                ValidationError::InvalidUsername("This username is reserved".to_string()).into()
+            );
+        }
+        let cooldown = time::Duration::days(config.reuse_cooldown_days as i64);
+
+        // usernames are compared by their skeleton, so a user can only change the case or the
+        // periods of their own. A deleted account keeps its username until it can no longer be
+        // restored, a renamed one until the cooldown ends
+        let exists = sqlx::query_scalar!(
+            r#"SELECT EXISTS(
+                    SELECT 1 FROM users
+                    WHERE username_skeleton(username) = username_skeleton($1)
+                        AND id IS DISTINCT FROM $3
+                )
+                OR EXISTS(
+                    SELECT 1 FROM deleted_users
+                    WHERE username_skeleton(username) = username_skeleton($1) AND purge_at > NOW()
+                )
+                OR EXISTS(
+                    SELECT 1 FROM username_history
+                    WHERE username_skeleton(username) = username_skeleton($1) AND released_at > $2
+                        AND user_id IS DISTINCT FROM $3
+                )"#,
+            username,
+            OffsetDateTime::now_utc() - cooldown,
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
 
         if exists.unwrap_or(false) { Err(AppError::UsernameTaken) } else { Ok(()) }
     }
@@ -54,7 +101,7 @@ impl crate::Db {
         match &user.password {
             Some(db_password) if db_password == password => Ok(user),
             Some(_) => Err(AppError::PasswordMismatch),
-            None => Err(AppError::BadReq("Password not set")),
+            None => Err(AppError::BadReq("Password not set, please login with an email code")),
         }
     }
 
@@ -76,7 +123,7 @@ impl crate::Db {
         match &user.password {
             Some(db_password) if db_password == password => Ok(user),
             Some(_) => Err(AppError::PasswordMismatch),
-            None => Err(AppError::BadReq("Password not set")),
+            None => Err(AppError::BadReq("Password not set, please login with an email code")),
         }
     }
 
@@ -105,4 +152,72 @@ impl crate::Db {
                 }
             })
     }
+
+    pub async fn get_user_by_id(self: &Arc<Self>, id: sqlx::types::Uuid) -> Result<User, AppError> {
+        sqlx::query_as!(User, "SELECT * FROM users WHERE id = $1", id)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::RowNotFound => AppError::UserNotFound,
+                _ => {
+                    tracing::error!("{:?}", e);
+                    AppError::ServerError
+                }
+            })
+    }
+
+    /// returns the user who last released `username` by renaming, lookups of it are redirected
+    pub async fn get_user_by_previous_username(
+        self: &Arc<Self>,
+        username: &str,
+    ) -> Result<User, AppError> {
+        sqlx::query_as!(
+            User,
+            r#"SELECT * FROM users WHERE id = (
+                SELECT user_id FROM username_history WHERE username = $1
+                ORDER BY released_at DESC LIMIT 1
+            )"#,
+            username
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => AppError::UserNotFound,
+            _ => {
+                tracing::error!("{:?}", e);
+                AppError::ServerError
+            }
+        })
+    }
+
+    /// returns the usernames the user released by renaming and when, latest first
+    pub async fn get_username_history(
+        self: &Arc<Self>,
+        user_id: Uuid,
+    ) -> Result<Vec<(String, OffsetDateTime)>, AppError> {
+        let rows = sqlx::query!(
+            "SELECT username, released_at FROM username_history
+            WHERE user_id = $1 ORDER BY released_at DESC",
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+
+        Ok(rows.into_iter().map(|r| (r.username, r.released_at)).collect())
+    }
+
+    /// returns every user, oldest first
+    pub async fn get_all_users(self: &Arc<Self>) -> Result<Vec<User>, AppError> {
+        sqlx::query_as!(User, "SELECT * FROM users ORDER BY created, id")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| {
+                tracing::error!("{:?}", e);
+                AppError::ServerError
+            })
+    }
 }
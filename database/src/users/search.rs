@@ -0,0 +1,159 @@
+use super::{ProfileVisibility, Visibility};
+use base64::Engine;
+use sqlx::types::{Uuid, time::OffsetDateTime};
+use std::sync::Arc;
+use util::AppError;
+
+/// narrows a user search down, only offered to admins
+#[derive(Default)]
+pub struct SearchFilters {
+    pub country: Option<String>,
+    pub created_after: Option<OffsetDateTime>,
+    pub created_before: Option<OffsetDateTime>,
+}
+
+/// a user found by `Db::search_users`, the visibility of its fields is left to the caller
+pub struct SearchHit {
+    pub id: Uuid,
+    pub username: String,
+    pub display_name: String,
+    pub icon: Option<String>,
+    pub country: Option<String>,
+    pub created: OffsetDateTime,
+    pub visibility: ProfileVisibility,
+    pub rank: f32,
+}
+
+/// position after the last hit of a page, results are ordered by rank and then by username
+#[derive(Debug, PartialEq)]
+pub struct SearchCursor {
+    rank: f32,
+    username: String,
+}
+
+impl SearchCursor {
+    pub fn after(hit: &SearchHit) -> Self {
+        Self { rank: hit.rank, username: hit.username.clone() }
+    }
+
+    pub fn encode(&self) -> String {
+        let raw = format!("{}:{}", self.rank, self.username);
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(raw)
+    }
+
+    /// `None` for anything `encode()` didn't return
+    pub fn decode(s: &str) -> Option<Self> {
+        let raw = base64::engine::general_purpose::URL_SAFE_NO_PAD.decode(s).ok()?;
+        let raw = String::from_utf8(raw).ok()?;
+        let (rank, username) = raw.split_once(':')?;
+        Some(Self { rank: rank.parse().ok()?, username: username.to_string() })
+    }
+}
+
+// escapes the wildcards of LIKE patterns
+fn escape_like(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+// implementation block for finding users
+impl crate::Db {
+    /// users whose username or display name match `query`, best matches first, or every user by
+    /// username when `query` is empty
+    ///
+    /// exact and prefix matches of the username rank above fuzzy ones. Users hidden from `viewer`
+    /// by a block or a mute are left out
+    pub async fn search_users(
+        self: &Arc<Self>,
+        viewer: Option<Uuid>,
+        query: &str,
+        filters: &SearchFilters,
+        cursor: Option<&SearchCursor>,
+        limit: i64,
+    ) -> Result<Vec<SearchHit>, AppError> {
+        let query = query.trim().to_lowercase();
+        let pattern = escape_like(&query);
+        let rows = sqlx::query!(
+            r#"SELECT id AS "id!", username AS "username!", display_name AS "display_name!", icon,
+                country, created AS "created!", icon_visibility, country_visibility,
+                rank AS "rank!"
+            FROM (
+                SELECT u.id, u.username, u.display_name, u.icon, u.country, u.created,
+                    v.icon AS icon_visibility, v.country AS country_visibility,
+                    (CASE WHEN $1 = '' THEN 0 ELSE
+                        GREATEST(similarity(u.username, $1), similarity(u.display_name, $1))
+                        + CASE WHEN u.username = $1 THEN 1
+                            WHEN u.username LIKE $2 || '%' THEN 0.5
+                            ELSE 0 END
+                    END)::REAL AS rank
+                FROM users u
+                LEFT JOIN profile_visibility v ON v.user_id = u.id
+                WHERE ($1 = ''
+                        OR u.username % $1 OR u.display_name % $1
+                        OR u.username LIKE '%' || $2 || '%'
+                        OR u.display_name ILIKE '%' || $2 || '%')
+                    AND ($3::TEXT IS NULL OR u.country = $3)
+                    AND ($4::TIMESTAMPTZ IS NULL OR u.created >= $4)
+                    AND ($5::TIMESTAMPTZ IS NULL OR u.created < $5)
+                    AND ($9::UUID IS NULL OR NOT EXISTS(
+                        SELECT 1 FROM user_blocks b
+                        WHERE (b.blocker_id = $9 AND b.blocked_id = u.id)
+                            OR (b.blocker_id = u.id AND b.blocked_id = $9 AND b.kind = 'block')
+                    ))
+            ) hits
+            WHERE $6::REAL IS NULL OR rank < $6 OR (rank = $6 AND username > $7)
+            ORDER BY rank DESC, username
+            LIMIT $8"#,
+            query,
+            pattern,
+            filters.country,
+            filters.created_after,
+            filters.created_before,
+            cursor.map(|v| v.rank),
+            cursor.map(|v| v.username.as_str()),
+            limit,
+            viewer
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+
+        let defaults = ProfileVisibility::default();
+        Ok(rows
+            .into_iter()
+            .map(|v| SearchHit {
+                id: v.id,
+                username: v.username,
+                display_name: v.display_name,
+                icon: v.icon,
+                country: v.country,
+                created: v.created,
+                // only the fields shown in results are needed
+                visibility: ProfileVisibility {
+                    icon: v.icon_visibility.as_deref().map_or(defaults.icon, Visibility::from),
+                    country: v
+                        .country_visibility
+                        .as_deref()
+                        .map_or(defaults.country, Visibility::from),
+                    ..defaults
+                },
+                rank: v.rank,
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_cursor_test() {
+        let cursor = SearchCursor { rank: 3.0 / 7.0, username: "john.doe".to_string() };
+        assert_eq!(SearchCursor::decode(&cursor.encode()), Some(cursor));
+        assert_eq!(SearchCursor::decode("not a cursor"), None);
+        assert_eq!(escape_like("50%_off\\"), "50\\%\\_off\\\\");
+    }
+}
@@ -9,21 +9,46 @@ impl crate::Db {
         email: &str,
         password: &str,
     ) -> Result<(), AppError> {
-        sqlx::query_as!(User, "UPDATE users SET password = $1 WHERE email = $2", password, email)
-            .fetch_one(&self.pool)
-            .await
-            .map_err(|e| match e {
-                sqlx::Error::RowNotFound => AppError::UserNotFound,
-                _ => {
-                    tracing::error!("{:?}", e);
-                    AppError::ServerError
-                }
-            })?;
+        let id = sqlx::query_scalar!(
+            "UPDATE users SET password = $1 WHERE email = $2 RETURNING id",
+            password,
+            email
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => AppError::UserNotFound,
+            _ => {
+                tracing::error!("{:?}", e);
+                AppError::ServerError
+            }
+        })?;
+        self.record_event(id, "password_changed", None, None).await?;
 
         tracing::info!("[Password Updated] Email: {email}");
         Ok(())
     }
 
+    // removes the password of the given user, so that it has to be reset before logging in
+    pub async fn remove_password(self: &Arc<Self>, email: &str) -> Result<(), AppError> {
+        let id = sqlx::query_scalar!(
+            "UPDATE users SET password = NULL WHERE email = $1 RETURNING id",
+            email
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+        if let Some(id) = id {
+            self.record_event(id, "password_removed", None, None).await?;
+        }
+
+        tracing::info!("[Password Removed] Email: {email}");
+        Ok(())
+    }
+
     pub async fn update_oauth_provider(
         self: &Arc<Self>,
         email: &str,
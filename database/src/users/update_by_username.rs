@@ -1,20 +1,67 @@
+use sqlx::types::{Uuid, time::OffsetDateTime};
 use std::sync::Arc;
 use util::AppError;
 
 // implementation block for checking and updating user attributes by username
 impl crate::Db {
+    /// renames the user, the old username is kept in its history for redirects and the cooldown
     pub async fn check_and_update_username(
         self: &Arc<Self>,
+        user_id: Uuid,
         username: &str,
         new_username: &str,
     ) -> Result<(), AppError> {
-        sqlx::query!("UPDATE users SET username = $1 WHERE username = $2", new_username, username)
-            .execute(&self.pool)
+        self.is_username_available_to(new_username, Some(user_id)).await?;
+
+        let last_rename = sqlx::query_scalar!(
+            "SELECT MAX(released_at) FROM username_history WHERE user_id = $1",
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+        let interval = time::Duration::days(self.config.username.rename_interval_days as i64);
+        if let Some(last_rename) = last_rename
+            && OffsetDateTime::now_utc() - last_rename < interval
+        {
+            return Err(AppError::BadReq(
+                "You've changed your username recently, please try again later",
+            ));
+        }
+
+        let mut tx = self.pool.begin().await.map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+        sqlx::query!("UPDATE users SET username = $1 WHERE id = $2", new_username, user_id)
+            .execute(&mut *tx)
             .await
-            .map_err(|e| {
-                tracing::error!("{:?}", e);
-                AppError::ServerError
+            .map_err(|e| match e {
+                sqlx::Error::Database(e) if e.is_unique_violation() => AppError::UsernameTaken,
+                _ => {
+                    tracing::error!("{:?}", e);
+                    AppError::ServerError
+                }
             })?;
+        sqlx::query!(
+            "INSERT INTO username_history (username, user_id) VALUES ($1, $2)",
+            username,
+            user_id
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+        tx.commit().await.map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+        self.record_event(user_id, "username_changed", None, None).await?;
 
         tracing::info!(
             "[Username Updated] Old Username: @{username}, New Username: @{new_username}"
@@ -90,6 +137,43 @@ impl crate::Db {
         Ok(())
     }
 
+    pub async fn update_locale(
+        self: &Arc<Self>,
+        username: &str,
+        locale: &str,
+    ) -> Result<(), AppError> {
+        sqlx::query!("UPDATE users SET locale = $1 WHERE username = $2", locale, username)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                tracing::error!("{:?}", e);
+                AppError::ServerError
+            })?;
+
+        tracing::info!("[Locale Updated] @{username}, Locale: {locale}");
+        Ok(())
+    }
+
+    /// sets the role of the user, `admin` or `user`
+    pub async fn update_role(self: &Arc<Self>, username: &str, role: &str) -> Result<(), AppError> {
+        let id = sqlx::query_scalar!(
+            "UPDATE users SET role = $1 WHERE username = $2 RETURNING id",
+            role,
+            username
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?
+        .ok_or(AppError::UserNotFound)?;
+        self.record_event(id, "role_changed", None, None).await?;
+
+        tracing::info!("[Role Updated] @{username}, Role: {role}");
+        Ok(())
+    }
+
     // Update profile (dynamic fields)
     pub async fn update_profile(
         &self,
@@ -0,0 +1,135 @@
+use sqlx::types::Uuid;
+use std::sync::Arc;
+use util::AppError;
+
+/// who can see a field of a profile
+#[derive(serde::Deserialize, serde::Serialize, Copy, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Visibility {
+    Public,
+    Users, // signed in users
+    Me,
+}
+
+impl From<&str> for Visibility {
+    fn from(value: &str) -> Self {
+        match value {
+            "public" => Self::Public,
+            "users" => Self::Users,
+            // unknown values hide the field rather than leaking it
+            _ => Self::Me,
+        }
+    }
+}
+
+impl Visibility {
+    pub fn get_str(&self) -> &'static str {
+        match self {
+            Self::Public => "public",
+            Self::Users => "users",
+            Self::Me => "me",
+        }
+    }
+
+    /// whether the profile of `owner` shows the field to `viewer` (None when signed out)
+    pub fn allows(&self, owner: Uuid, viewer: Option<Uuid>) -> bool {
+        match self {
+            Self::Public => true,
+            Self::Users => viewer.is_some(),
+            Self::Me => viewer == Some(owner),
+        }
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Copy, Clone, Debug)]
+pub struct ProfileVisibility {
+    pub icon: Visibility,
+    pub banner: Visibility,
+    pub country: Visibility,
+    pub birth_date: Visibility,
+    pub gender: Visibility,
+}
+
+impl Default for ProfileVisibility {
+    fn default() -> Self {
+        Self {
+            icon: Visibility::Public,
+            banner: Visibility::Public,
+            country: Visibility::Users,
+            birth_date: Visibility::Me,
+            gender: Visibility::Me,
+        }
+    }
+}
+
+// implementation block for the visibility of profile fields
+impl crate::Db {
+    pub async fn get_profile_visibility(
+        self: &Arc<Self>,
+        user_id: Uuid,
+    ) -> Result<ProfileVisibility, AppError> {
+        let row = sqlx::query!(
+            "SELECT icon, banner, country, birth_date, gender FROM profile_visibility
+            WHERE user_id = $1",
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+
+        Ok(row.map_or_else(ProfileVisibility::default, |v| ProfileVisibility {
+            icon: v.icon.as_str().into(),
+            banner: v.banner.as_str().into(),
+            country: v.country.as_str().into(),
+            birth_date: v.birth_date.as_str().into(),
+            gender: v.gender.as_str().into(),
+        }))
+    }
+
+    pub async fn update_profile_visibility(
+        self: &Arc<Self>,
+        user_id: Uuid,
+        visibility: &ProfileVisibility,
+    ) -> Result<(), AppError> {
+        sqlx::query!(
+            "INSERT INTO profile_visibility (user_id, icon, banner, country, birth_date, gender)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (user_id) DO UPDATE SET icon = $2, banner = $3, country = $4,
+                birth_date = $5, gender = $6",
+            user_id,
+            visibility.icon.get_str(),
+            visibility.banner.get_str(),
+            visibility.country.get_str(),
+            visibility.birth_date.get_str(),
+            visibility.gender.get_str(),
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("{:?}", e);
+            AppError::ServerError
+        })?;
+
+        tracing::info!("[Profile Visibility Updated] User ID: {user_id}");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn visibility_test() {
+        let (owner, other) = (Uuid::new_v4(), Uuid::new_v4());
+        assert!(Visibility::Public.allows(owner, None));
+        assert!(!Visibility::Users.allows(owner, None));
+        assert!(Visibility::Users.allows(owner, Some(other)));
+        assert!(!Visibility::Me.allows(owner, Some(other)));
+        assert!(Visibility::Me.allows(owner, Some(owner)));
+        assert_eq!(Visibility::from("friends"), Visibility::Me);
+    }
+}
@@ -0,0 +1,49 @@
+#![allow(dead_code)]
+
+use database::{Db, users::User};
+use sqlx::types::{Uuid, time::OffsetDateTime};
+use std::sync::Arc;
+use util::oauth::OAuthProvider;
+
+/// connects to the database of the config, with a separate pool to look at the tables directly
+pub async fn connect() -> (Arc<Db>, sqlx::PgPool) {
+    dotenv::dotenv().ok();
+    let config = util::config::load().unwrap();
+    util::init(&config).unwrap();
+    let pool = sqlx::PgPool::connect(&config.database.url).await.unwrap();
+    (Db::new(Arc::new(config)).await, pool)
+}
+
+/// a new user with a random username starting with `prefix`, not stored yet
+pub fn user(prefix: &str) -> User {
+    let id = Uuid::new_v4();
+    let username = format!("{prefix}_{}", &id.simple().to_string()[..12]);
+    User {
+        id,
+        display_name: username.clone(),
+        email: format!("{username}@example.com"),
+        birth_date: None,
+        password: None,
+        username,
+        banner: None,
+        icon: None,
+        bio: None,
+        legal_name: None,
+        gender: None,
+        phone: None,
+        country: None,
+        locale: None,
+        oauth_provider: OAuthProvider::None,
+        created: OffsetDateTime::now_utc(),
+        role: "user".to_string(),
+    }
+}
+
+/// the rows of `table` whose `column` is `value`
+pub async fn count(pool: &sqlx::PgPool, table: &str, column: &str, value: Uuid) -> i64 {
+    sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {table} WHERE {column} = $1"))
+        .bind(value)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use common::{count, user};
+
+// concurrent export requests of a user have to start a single export, which can be requested
+// again once it failed, every started export is recorded in the audit events
+//
+// runs against the database of the config: `cargo test -p database -- --ignored`
+#[tokio::test]
+#[ignore]
+async fn start_and_fail_export_test() {
+    let (db, pool) = common::connect().await;
+    let owner = user("export");
+    db.create_user(&owner).await.unwrap();
+
+    let requests = (0..8).map(|_| {
+        let db = db.clone();
+        tokio::spawn(async move { db.start_export(owner.id).await.unwrap() })
+    });
+    let mut started = Vec::new();
+    for request in requests.collect::<Vec<_>>() {
+        started.extend(request.await.unwrap());
+    }
+    assert_eq!(started.len(), 1);
+    assert!(db.get_export(owner.id, &started[0]).await.is_err());
+
+    // only the export being prepared can be given up on
+    db.fail_export(owner.id, "unknown").await.unwrap();
+    assert!(db.start_export(owner.id).await.unwrap().is_none());
+    db.fail_export(owner.id, &started[0]).await.unwrap();
+    assert_eq!(count(&pool, "data_exports", "user_id", owner.id).await, 0);
+
+    let id = db.start_export(owner.id).await.unwrap().unwrap();
+    assert_ne!(id, started[0]);
+    let requested = db
+        .get_events(owner.id)
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|v| v.kind == "data_export_requested")
+        .count();
+    assert_eq!(requested, 2);
+
+    db.fail_export(owner.id, &id).await.unwrap();
+    db.delete_user(owner).await.unwrap();
+}
@@ -0,0 +1,96 @@
+mod common;
+
+use common::{count, user};
+use database::users::{BlockKind, ProfileVisibility, Visibility};
+use sqlx::types::{Uuid, time::OffsetDateTime};
+use util::{mail::Template, oauth::OAuthProvider};
+
+// schedules the deletion of an account, restores it, then schedules it again and purges it, the
+// rows kept for the restore have to come back with the account and be gone after the purge
+//
+// runs against the database of the config: `cargo test -p database -- --ignored`
+#[tokio::test]
+#[ignore]
+async fn schedule_restore_purge_test() {
+    let (db, pool) = common::connect().await;
+
+    let (owner, other) = (user("purge"), user("purge_other"));
+    db.create_user(&owner).await.unwrap();
+    db.create_user(&other).await.unwrap();
+
+    db.block_user(owner.id, other.id, BlockKind::Block).await.unwrap();
+    db.update_profile_visibility(
+        owner.id,
+        &ProfileVisibility {
+            icon: Visibility::Me,
+            banner: Visibility::Me,
+            country: Visibility::Me,
+            birth_date: Visibility::Me,
+            gender: Visibility::Me,
+        },
+    )
+    .await
+    .unwrap();
+    let renamed = format!("{}_r", owner.username);
+    db.check_and_update_username(owner.id, &owner.username, &renamed).await.unwrap();
+    db.create_sign_in_report(
+        owner.id,
+        Uuid::new_v4(),
+        OffsetDateTime::now_utc() + time::Duration::hours(1),
+    )
+    .await
+    .unwrap();
+    db.enqueue_email(Uuid::new_v4().to_string(), owner.email.clone(), Template::Welcome, None, &[])
+        .await
+        .unwrap();
+    db.link_oauth_identity(owner.id, OAuthProvider::Google, &owner.id.to_string(), &owner.email)
+        .await
+        .unwrap();
+    assert!(db.start_export(owner.id).await.unwrap().is_some());
+    sqlx::query(
+        "INSERT INTO sign_in_origins (user_id, user_agent, ip_address, last_seen)
+        VALUES ($1, '', '127.0.0.1', NOW())",
+    )
+    .bind(owner.id)
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let owner = db.get_user_by_username(&renamed).await.unwrap();
+    let (_, restore_token) = db.delete_user(owner).await.unwrap();
+    let owner = db.restore_user_by_token(&restore_token).await.unwrap();
+    assert_eq!(db.get_blocked_users(owner.id).await.unwrap().len(), 1);
+    assert_eq!(count(&pool, "username_history", "user_id", owner.id).await, 1);
+    assert_eq!(count(&pool, "profile_visibility", "user_id", owner.id).await, 1);
+    assert_eq!(count(&pool, "sign_in_origins", "user_id", owner.id).await, 1);
+    assert_eq!(count(&pool, "oauth_identities", "user_id", owner.id).await, 1);
+    assert_eq!(count(&pool, "data_exports", "user_id", owner.id).await, 1);
+
+    let id = owner.id;
+    let email = owner.email.clone();
+    db.delete_user(owner).await.unwrap();
+    sqlx::query("UPDATE deleted_users SET purge_at = NOW() WHERE id = $1")
+        .bind(id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    assert!(db.purge_due_users().await.unwrap() >= 1);
+
+    assert_eq!(count(&pool, "deleted_users", "id", id).await, 0);
+    assert_eq!(count(&pool, "user_blocks", "blocker_id", id).await, 0);
+    assert_eq!(count(&pool, "username_history", "user_id", id).await, 0);
+    assert_eq!(count(&pool, "profile_visibility", "user_id", id).await, 0);
+    assert_eq!(count(&pool, "sign_in_origins", "user_id", id).await, 0);
+    assert_eq!(count(&pool, "sign_in_reports", "user_id", id).await, 0);
+    assert_eq!(count(&pool, "audit_events", "user_id", id).await, 0);
+    assert_eq!(count(&pool, "oauth_identities", "user_id", id).await, 0);
+    assert_eq!(count(&pool, "data_exports", "user_id", id).await, 0);
+    let mails: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM email_outbox WHERE to_email = $1")
+        .bind(email)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(mails, 0);
+
+    db.delete_user(db.get_user_by_username(&other.username).await.unwrap()).await.unwrap();
+}
@@ -1,17 +1,31 @@
-use axum::{Router, routing::get};
+use axum::{
+    Router,
+    routing::{get, post},
+};
+use database::Db;
 use std::sync::Arc;
 use sysinfo::{Disks, Networks, System};
 use tokio::sync::{Mutex, OnceCell};
 
 mod health;
+mod outbox;
+mod signing_keys;
+mod users;
 
 #[rustfmt::skip]
-pub async fn admin_routes() -> Router {
+pub fn admin_routes(db: Arc<Db>) -> Router {
     Router::new()
         .route("/api/health", get(health::health_handler))
-        .layer(axum::middleware::from_fn(crate::middleware::admin_middleware))
-        .layer(axum::middleware::from_fn(crate::middleware::auth_middleware))
-        .with_state(database::Db::new().await)
+        .route("/api/admin/outbox", get(outbox::list_emails))
+        .route("/api/admin/outbox/{id}/retry", post(outbox::retry_email))
+        .route("/api/admin/signing_keys", get(signing_keys::list_keys))
+        .route("/api/admin/signing_keys/rotate", post(signing_keys::rotate_key))
+        .route("/api/admin/signing_keys/{id}/retire", post(signing_keys::retire_key))
+        .route("/api/admin/users", get(users::list_users))
+        .layer(axum::middleware::from_fn_with_state(db.clone(), crate::middleware::admin_middleware))
+        .layer(axum::middleware::from_fn_with_state(db.clone(), crate::middleware::csrf_middleware))
+        .layer(axum::middleware::from_fn_with_state(db.clone(), crate::middleware::auth_middleware))
+        .with_state(db)
 }
 
 #[derive(Clone)]
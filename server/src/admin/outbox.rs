@@ -0,0 +1,54 @@
+use axum::extract::{Path, Query, State};
+use axum_extra::{json, response::ErasedJson};
+use database::Db;
+use std::sync::Arc;
+use util::AppError;
+
+#[derive(serde::Deserialize)]
+pub struct OutboxQuery {
+    status: Option<String>,
+    limit: Option<i64>,
+}
+
+pub async fn list_emails(
+    State(db): State<Arc<Db>>,
+    Query(q): Query<OutboxQuery>,
+) -> Result<ErasedJson, AppError> {
+    let status = q.status.unwrap_or_else(|| "dead".to_string());
+    if !["pending", "sent", "dead"].contains(&status.as_str()) {
+        return Err(AppError::BadReq("Status must be one of pending, sent or dead"));
+    }
+    let emails = db.get_outbox_emails(&status, q.limit.unwrap_or(50).clamp(1, 500)).await?;
+
+    let emails = emails
+        .iter()
+        .map(|v| {
+            serde_json::json!({
+                "id": v.id.to_string(),
+                "idempotency_key": v.idempotency_key,
+                "to_email": v.to_email,
+                "status": v.status,
+                "attempts": v.attempts,
+                "last_error": v.last_error,
+                "created_at": v.created_at.to_string(),
+                "next_attempt_at": v.next_attempt_at.to_string(),
+                "sent_at": v.sent_at.map(|v| v.to_string()),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Ok(json!({
+        "emails": emails,
+    }))
+}
+
+pub async fn retry_email(
+    State(db): State<Arc<Db>>,
+    Path(id): Path<String>,
+) -> Result<ErasedJson, AppError> {
+    let id = uuid::Uuid::parse_str(&id).map_err(|_| AppError::BadReq("Invalid email id"))?;
+    db.retry_outbox_email(id).await?;
+    Ok(json!({
+        "message": "The email has been queued again"
+    }))
+}
@@ -0,0 +1,45 @@
+use axum::extract::{Path, State};
+use axum_extra::{json, response::ErasedJson};
+use database::Db;
+use std::sync::Arc;
+use util::{AppError, session::keyring};
+
+pub async fn list_keys(State(db): State<Arc<Db>>) -> Result<ErasedJson, AppError> {
+    let stored = db.get_signing_keys().await?;
+    let active = keyring::key_ids();
+
+    let keys = stored
+        .iter()
+        .map(|v| {
+            serde_json::json!({
+                "id": v.id,
+                "created_at": v.created_at.to_string(),
+                "retired_at": v.retired_at.map(|v| v.to_string()),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Ok(json!({
+        "current": keyring::current_key_id(),
+        "active": active,
+        "keys": keys,
+    }))
+}
+
+pub async fn rotate_key(State(db): State<Arc<Db>>) -> Result<ErasedJson, AppError> {
+    let id = db.rotate_signing_key().await?;
+    Ok(json!({
+        "id": id,
+        "message": "New cookies are signed with the new key, older ones are re-signed on use"
+    }))
+}
+
+pub async fn retire_key(
+    State(db): State<Arc<Db>>,
+    Path(id): Path<String>,
+) -> Result<ErasedJson, AppError> {
+    db.retire_signing_key(&id).await?;
+    Ok(json!({
+        "message": format!("Cookies signed with the key {id} are no longer accepted")
+    }))
+}
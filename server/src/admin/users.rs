@@ -0,0 +1,67 @@
+use axum::extract::{Query, State};
+use axum_extra::{json, response::ErasedJson};
+use database::{
+    Db,
+    users::{SearchCursor, SearchFilters},
+};
+use std::sync::Arc;
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+use util::AppError;
+
+#[derive(serde::Deserialize)]
+pub struct UsersQuery {
+    #[serde(default)]
+    q: String,
+    country: Option<String>,
+    created_after: Option<String>,  // RFC 3339
+    created_before: Option<String>, // RFC 3339
+    cursor: Option<String>,
+    limit: Option<i64>,
+}
+
+/// the user directory, every user by username without a search and every field of them
+pub async fn list_users(
+    State(db): State<Arc<Db>>,
+    Query(q): Query<UsersQuery>,
+) -> Result<ErasedJson, AppError> {
+    let parse_date = |v: Option<String>| {
+        v.map(|v| OffsetDateTime::parse(&v, &Rfc3339))
+            .transpose()
+            .map_err(|_| AppError::BadReq("Dates must be in the RFC 3339 format"))
+    };
+    let filters = SearchFilters {
+        country: q.country,
+        created_after: parse_date(q.created_after)?,
+        created_before: parse_date(q.created_before)?,
+    };
+    let cursor = q
+        .cursor
+        .as_deref()
+        .map(|v| SearchCursor::decode(v).ok_or(AppError::BadReq("Invalid cursor")))
+        .transpose()?;
+    let limit = q.limit.unwrap_or(50).clamp(1, 500);
+    let hits = db.search_users(None, &q.q, &filters, cursor.as_ref(), limit).await?;
+
+    let next_cursor = (hits.len() as i64 == limit)
+        .then(|| hits.last().map(|v| SearchCursor::after(v).encode()))
+        .flatten();
+    let users = hits
+        .iter()
+        .map(|v| {
+            serde_json::json!({
+                "id": v.id.to_string(),
+                "username": v.username,
+                "display_name": v.display_name,
+                "icon": v.icon,
+                "country": v.country,
+                "created": v.created.to_string(),
+                "rank": v.rank,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Ok(json!({
+        "users": users,
+        "next_cursor": next_cursor,
+    }))
+}
@@ -1,11 +1,12 @@
+use crate::middleware::RecentAuth;
 use axum::{
     Extension, Json,
     extract::{ConnectInfo, State},
-    http::{HeaderMap, StatusCode},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
     response::IntoResponse,
 };
 use axum_extra::{json, response::ErasedJson};
-use database::{Db, UserData};
+use database::{Db, UserData, users::User};
 use std::sync::Arc;
 use util::{AppError, session::ParsedSession};
 
@@ -14,6 +15,12 @@ pub struct LoginRequest {
     email: Option<String>,
     username: Option<String>,
     password: String,
+    #[serde(default)]
+    remember_me: bool,
+    return_to: Option<String>,
+    /// restores the account if it's scheduled for deletion
+    #[serde(default)]
+    restore: bool,
 }
 
 pub async fn login(
@@ -22,16 +29,86 @@ pub async fn login(
     headers: HeaderMap,
     Json(body): Json<LoginRequest>,
 ) -> Result<impl IntoResponse, AppError> {
-    let user = match (&body.email, &body.username) {
-        (Some(email), None) => db.authenticate_user_by_email(email, &body.password).await?,
-        (None, Some(username)) => db.authenticate_user_by_username(username, &body.password).await?,
+    // stops other sites from signing the browser into an account of theirs
+    crate::middleware::check_origin(db.config(), &headers)?;
+    let (identifier, authenticated) = match (&body.email, &body.username) {
+        (Some(email), None) => (email, db.authenticate_user_by_email(email, &body.password).await),
+        (None, Some(username)) => {
+            (username, db.authenticate_user_by_username(username, &body.password).await)
+        }
         (Some(_), Some(_)) => return Err(AppError::BadReq("Either email or username is allowed")),
         (None, None) => return Err(AppError::BadReq("No email or username found")),
     };
+    let user = match authenticated {
+        // the account may be waiting for its deletion, which signing in can undo
+        Err(AppError::UserNotFound) => {
+            let deleted = db.get_restorable_user(identifier).await?;
+            if deleted.password.as_ref().is_none_or(|v| v != &body.password) {
+                return Err(AppError::PasswordMismatch);
+            }
+            if !body.restore {
+                return Ok((
+                    StatusCode::CONFLICT,
+                    HeaderMap::new(),
+                    restore_offer(deleted.purge_at),
+                ));
+            }
+            db.restore_user(deleted.id).await?
+        }
+        v => v?,
+    };
+
+    let (set_cookie_headermap, res_body) =
+        sign_in(&db, user, &headers, *conn_info, body.remember_me, body.return_to.as_deref())
+            .await?;
+    Ok((StatusCode::CREATED, set_cookie_headermap, res_body))
+}
 
+// answered instead of signing in, so that the client can offer to restore the account
+fn restore_offer(purge_at: Option<time::OffsetDateTime>) -> ErasedJson {
+    use time::format_description::well_known::Rfc3339;
+    json!({
+        "message": "This account is scheduled for deletion, sign in with `restore` to keep it",
+        "restorable": true,
+        "purge_at": purge_at.and_then(|v| v.format(&Rfc3339).ok()),
+    })
+}
+
+// lists the login methods enabled on this deployment
+pub async fn login_methods(State(db): State<Arc<Db>>) -> ErasedJson {
+    json!({
+        "password": true,
+        "email_otp": true,
+        "magic_link": super::magic_link::is_enabled(&db),
+    })
+}
+
+/// creates a new session for `user` and activates it, returns the cookies and user data
+///
+/// `remember_me` selects the longer session lifetime of the user's role, a trusted `return_to`
+/// is echoed back for the client to open
+pub(super) async fn sign_in(
+    db: &Arc<Db>,
+    user: User,
+    headers: &HeaderMap,
+    socket_addr: std::net::SocketAddr,
+    remember_me: bool,
+    return_to: Option<&str>,
+) -> Result<(HeaderMap, ErasedJson), AppError> {
+    let policy = util::session::policy(user.role());
     let (new_session, parsed_session, set_cookie_headermap) =
-        util::session::create_session(user.id, &headers, *conn_info);
-    let res_body = crate::user_data::arrange(&user, &vec![&new_session]);
+        util::session::create_session(user.id, headers, socket_addr, policy, remember_me);
+    let res_body = match return_to {
+        Some(v) => crate::user_data::arrange_returning(
+            &user,
+            &vec![&new_session],
+            crate::cors::return_to(db.config(), Some(v)),
+        ),
+        None => crate::user_data::arrange(&user, &vec![&new_session]),
+    };
+
+    // a failed alert doesn't block the sign-in, the error is already logged
+    let _ = super::new_sign_in::notify(db, &user, &new_session, headers).await;
 
     // adding `Session` to primary database
     db.add_session(user.id, new_session.clone()).await?;
@@ -45,7 +122,7 @@ pub async fn login(
         db.make_user_active(user, new_session);
     }
 
-    Ok((StatusCode::CREATED, set_cookie_headermap, res_body))
+    Ok((set_cookie_headermap, res_body))
 }
 
 pub async fn logout(
@@ -67,23 +144,28 @@ pub async fn logout(
     ))
 }
 
+// returns the CSRF token of the session, for clients that can't read the `CSRF` cookie
+pub async fn csrf(Extension(parsed_session): Extension<ParsedSession>) -> impl IntoResponse {
+    let token = util::session::csrf_token(&parsed_session.unsigned_ssid);
+    let cookie =
+        HeaderValue::from_str(&format!("CSRF={token}; SameSite=Strict; Secure; Path=/")).unwrap();
+    ([(header::SET_COOKIE, cookie)], json!({ "csrf_token": token }))
+}
+
 #[derive(serde::Deserialize)]
 pub struct LogoutDevicesRequest {
     sessions: Vec<String>,
-    password: String,
 }
 
 pub async fn logout_devices(
     State(db): State<Arc<Db>>,
     Extension(parsed_session): Extension<ParsedSession>,
     Extension(user): Extension<UserData>,
+    _: RecentAuth,
     Json(body): Json<LogoutDevicesRequest>,
 ) -> Result<impl IntoResponse, AppError> {
     let (user_id, mut session_list) = {
         let guard = user.lock().unwrap();
-        if guard.0.password.as_ref().is_none_or(|v| v != &body.password) {
-            return Err(AppError::PasswordMismatch);
-        }
         (guard.0.id, guard.1.clone())
     };
 
@@ -108,22 +190,14 @@ pub async fn logout_devices(
     }))
 }
 
-#[derive(serde::Deserialize)]
-pub struct LogoutAllRequest {
-    password: String,
-}
-
 pub async fn logout_all(
     State(db): State<Arc<Db>>,
     Extension(parsed_session): Extension<ParsedSession>,
     Extension(user): Extension<UserData>,
-    Json(body): Json<LogoutAllRequest>,
+    _: RecentAuth,
 ) -> Result<ErasedJson, AppError> {
     let (user_id, mut session_list) = {
         let guard = user.lock().unwrap();
-        if guard.0.password.as_ref().is_none_or(|v| v != &body.password) {
-            return Err(AppError::PasswordMismatch);
-        }
         (guard.0.id, guard.1.clone())
     };
 
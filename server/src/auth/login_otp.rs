@@ -0,0 +1,76 @@
+use crate::ClientSocket;
+use axum::{
+    Json,
+    extract::{ConnectInfo, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+};
+use axum_extra::{json, response::ErasedJson};
+use database::Db;
+use std::sync::Arc;
+use util::{AppError, mail::Template};
+
+#[derive(serde::Deserialize)]
+pub struct LoginOtpRequest {
+    email: String,
+}
+
+pub async fn request(
+    State(db): State<Arc<Db>>,
+    ConnectInfo(conn_info): ConnectInfo<ClientSocket>,
+    headers: HeaderMap,
+    Json(body): Json<LoginOtpRequest>,
+) -> Result<ErasedJson, AppError> {
+    shared::validation::is_email_valid(&body.email)?;
+    db.allow_login_otp(&body.email, conn_info.ip())?;
+
+    // the response is the same whether an account exists or not
+    if let Ok(user) = db.get_user_by_email(&body.email).await {
+        let otp = db.create_login_otp(user.email.clone());
+        let locale = user.locale.clone().or_else(|| util::mail::locale_from_headers(&headers));
+        db.enqueue_email(
+            format!("login_code:{}:{}", user.email, util::generate::random_string(16)),
+            user.email,
+            Template::LoginCode,
+            locale.as_deref(),
+            &[("otp", &otp)],
+        )
+        .await?;
+    }
+
+    Ok(json!({
+        "message": "If an account exists for this email, a login code has been sent"
+    }))
+}
+
+#[derive(serde::Deserialize)]
+pub struct VerifyLoginOtpRequest {
+    email: String,
+    otp: String,
+    #[serde(default)]
+    remember_me: bool,
+    return_to: Option<String>,
+}
+
+pub async fn verify(
+    State(db): State<Arc<Db>>,
+    ConnectInfo(conn_info): ConnectInfo<ClientSocket>,
+    headers: HeaderMap,
+    Json(body): Json<VerifyLoginOtpRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    // stops other sites from signing the browser into an account of theirs
+    crate::middleware::check_origin(db.config(), &headers)?;
+    db.verify_login_otp(&body.email, body.otp.trim())?;
+    let user = db.get_user_by_email(&body.email).await?;
+
+    let (set_cookie_headermap, res_body) = super::logging::sign_in(
+        &db,
+        user,
+        &headers,
+        *conn_info,
+        body.remember_me,
+        body.return_to.as_deref(),
+    )
+    .await?;
+    Ok((StatusCode::CREATED, set_cookie_headermap, res_body))
+}
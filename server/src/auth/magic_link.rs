@@ -0,0 +1,110 @@
+use crate::ClientSocket;
+use axum::{
+    Json,
+    extract::{ConnectInfo, Query, State},
+    http::{HeaderMap, HeaderValue, header},
+    response::{IntoResponse, Redirect},
+};
+use axum_extra::json;
+use database::{Db, applications::MAGIC_LINK_TTL};
+use std::sync::Arc;
+use util::{AppError, mail::Template};
+
+// random id of the browser that requested the link, the link only works alongside it
+const DEVICE_COOKIE: &str = "MLD";
+
+/// magic links are disabled unless `service.magic_link_login` (`MAGIC_LINK_LOGIN`) is set
+pub fn is_enabled(db: &Db) -> bool {
+    db.config().service.magic_link_login
+}
+
+#[derive(serde::Deserialize)]
+pub struct MagicLinkRequest {
+    email: String,
+}
+
+pub async fn request(
+    State(db): State<Arc<Db>>,
+    ConnectInfo(conn_info): ConnectInfo<ClientSocket>,
+    headers: HeaderMap,
+    Json(body): Json<MagicLinkRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    if !is_enabled(&db) {
+        return Err(AppError::NotFound);
+    }
+    shared::validation::is_email_valid(&body.email)?;
+    let device = util::session::get_cookie(&headers, DEVICE_COOKIE)
+        .unwrap_or_else(|| util::generate::random_string(32));
+
+    // the response is the same whether an account exists or not
+    if let Ok(user) = db.get_user_by_email(&body.email).await {
+        let id = util::generate::random_string(32);
+        let sig = util::generate::signature(&format!("magic_link:{id}:{device}"));
+        db.add_magic_link(*conn_info, user.email.clone(), id.clone());
+
+        let link =
+            format!("{}/api/login/magic_link/verify?id={id}&sig={sig}", db.config().service.domain);
+        let locale = user.locale.clone().or_else(|| util::mail::locale_from_headers(&headers));
+        db.enqueue_email(
+            format!("magic_link:{id}"),
+            user.email.clone(),
+            Template::MagicLink,
+            locale.as_deref(),
+            &[("email", &user.email), ("link", &link)],
+        )
+        .await?;
+    }
+
+    // `Lax` so that the cookie is sent when the link is opened from the mail client
+    let set_cookie_headermap = HeaderMap::from_iter([(
+        header::SET_COOKIE,
+        HeaderValue::from_str(&format!(
+            "{DEVICE_COOKIE}={device}; HttpOnly; SameSite=Lax; Secure; Path=/api/login/magic_link; Max-Age={MAGIC_LINK_TTL}"
+        ))
+        .unwrap(),
+    )]);
+    Ok((
+        set_cookie_headermap,
+        json!({
+            "message": "If an account exists for this email, a sign-in link has been sent"
+        }),
+    ))
+}
+
+#[derive(serde::Deserialize)]
+pub struct VerifyMagicLinkQuery {
+    id: String,
+    sig: String,
+}
+
+pub async fn verify(
+    State(db): State<Arc<Db>>,
+    ConnectInfo(conn_info): ConnectInfo<ClientSocket>,
+    headers: HeaderMap,
+    Query(q): Query<VerifyMagicLinkQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    if !is_enabled(&db) {
+        return Err(AppError::NotFound);
+    }
+    // the signature covers the device id, so a forwarded link fails on another browser
+    let device = util::session::get_cookie(&headers, DEVICE_COOKIE).unwrap_or_default();
+    if !util::generate::verify_signature(&format!("magic_link:{}:{device}", q.id), &q.sig) {
+        return Err(AppError::BadReq("Please open the link on the browser that requested it"));
+    }
+    let email = db
+        .take_magic_link(&q.id)
+        .ok_or(AppError::BadReq("The sign-in link has expired or was already used"))?;
+    let user = db.get_user_by_email(&email).await?;
+
+    // the link is bound to this browser, so the session is kept like a remembered one
+    let (mut set_cookie_headermap, _) =
+        super::logging::sign_in(&db, user, &headers, *conn_info, true, None).await?;
+    set_cookie_headermap.append(
+        header::SET_COOKIE,
+        HeaderValue::from_str(&format!(
+            "{DEVICE_COOKIE}=; HttpOnly; SameSite=Lax; Secure; Path=/api/login/magic_link; Max-Age=0"
+        ))
+        .unwrap(),
+    );
+    Ok((set_cookie_headermap, Redirect::to("/")))
+}
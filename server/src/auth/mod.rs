@@ -1,26 +1,42 @@
 use axum::routing::{get, post};
+use database::Db;
+use std::sync::Arc;
 
 mod logging;
+mod login_otp;
+mod magic_link;
+mod new_sign_in;
 mod oidc;
 mod recovery;
 mod register;
 
 #[rustfmt::skip]
-pub async fn auth_routes() -> axum::Router {
+pub fn auth_routes(db: Arc<Db>) -> axum::Router {
     axum::Router::new()
         .route("/api/logout_all", post(logging::logout_all))
         .route("/api/logout_devices", post(logging::logout_devices))
         .route("/api/logout", post(logging::logout))
-        .layer(axum::middleware::from_fn(crate::middleware::auth_middleware))
+        .route("/api/csrf", get(logging::csrf))
+        .route("/api/oauth2/reauth", post(oidc::reauth))
+        .layer(axum::middleware::from_fn_with_state(db.clone(), crate::middleware::csrf_middleware))
+        .layer(axum::middleware::from_fn_with_state(db.clone(), crate::middleware::auth_middleware))
         .route("/api/login", post(logging::login))
+        .route("/api/login/methods", get(logging::login_methods))
+        .route("/api/login/otp", post(login_otp::request))
+        .route("/api/login/otp/verify", post(login_otp::verify))
+        .route("/api/login/magic_link", post(magic_link::request))
+        .route("/api/login/magic_link/verify", get(magic_link::verify))
+        .route("/api/login/not_me", post(new_sign_in::not_me))
         .route("/api/forgot_password", post(recovery::forgot_password))
         .route("/api/reset_password", post(recovery::reset_password))
-        .route("/api/oauth2/login", get(oidc::login)) // change to post
-        .route("/api/oauth2/callback", get(oidc::callback)) // change to post
+        .route("/api/oauth2/login", post(oidc::login))
+        // the provider redirects back with a GET, the `state` parameter guards it against CSRF
+        .route("/api/oauth2/callback", get(oidc::callback))
         .route("/api/register", post(register::start))
         .route("/api/register/resend_otp", post(register::resend_otp))
         .route("/api/register/verify_email", post(register::verify_email))
         .route("/api/register/set_password", post(register::set_password))
+        .route("/api/register/suggest_usernames", get(register::suggest_usernames))
         .route("/api/register/set_username", post(register::set_username))
-        .with_state(database::Db::new().await)
+        .with_state(db)
 }
@@ -0,0 +1,87 @@
+use crate::ClientSocket;
+use axum::{
+    Json,
+    extract::{ConnectInfo, State},
+    response::IntoResponse,
+};
+use axum_extra::json;
+use database::{Db, users::User};
+use std::sync::Arc;
+use time::format_description::well_known::Rfc2822;
+use util::{AppError, mail::Template, session::Session};
+
+// the "this wasn't me" link stays usable for a week
+const REPORT_LINK_TTL: time::Duration = time::Duration::days(7);
+
+/// emails the user if `session` comes from a device or place missing from its earlier sessions
+///
+/// must be called before `session` is added to the primary database
+pub(super) async fn notify(
+    db: &Arc<Db>,
+    user: &User,
+    session: &Session,
+    headers: &axum::http::HeaderMap,
+) -> Result<(), AppError> {
+    let signals = util::session::sign_in_signals();
+    if !signals.is_enabled() {
+        return Ok(());
+    }
+    let known = db.get_session_origins(user.id).await?;
+    if !signals.is_unfamiliar(&known, session.user_agent.as_deref(), session.ip_address) {
+        return Ok(());
+    }
+
+    let agent = util::user_agent::parse(session.user_agent.as_deref().unwrap_or_default());
+    let device = match (agent.browser, agent.os) {
+        (Some(browser), Some(os)) => format!("{browser} on {os}"),
+        (browser, os) => browser.or(os).unwrap_or("Unknown device".to_string()),
+    };
+    let location =
+        util::geoip::locate(session.ip_address).unwrap_or_else(|| session.ip_address.to_string());
+    let time = session.created_at.format(&Rfc2822).unwrap_or_default();
+
+    let ssid = session.unsigned_ssid.to_string();
+    let token = db
+        .create_sign_in_report(user.id, session.unsigned_ssid, session.created_at + REPORT_LINK_TTL)
+        .await?;
+    // the link opens a page asking for confirmation, as mail scanners follow links on their own
+    let link = format!("{}/not-me?token={token}", db.config().service.domain);
+
+    let locale = user.locale.clone().or_else(|| util::mail::locale_from_headers(headers));
+    db.enqueue_email(
+        format!("new_sign_in:{ssid}"),
+        user.email.clone(),
+        Template::NewSignIn,
+        locale.as_deref(),
+        &[("device", &device), ("location", &location), ("time", &time), ("link", &link)],
+    )
+    .await
+}
+
+#[derive(serde::Deserialize)]
+pub struct ReportRequest {
+    token: String,
+}
+
+/// "this wasn't me": ends every session of the user and forces a password reset
+///
+/// each link works once, the response carries the code for resetting the password
+pub async fn not_me(
+    State(db): State<Arc<Db>>,
+    ConnectInfo(conn_info): ConnectInfo<ClientSocket>,
+    Json(body): Json<ReportRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let (user_id, ssid) = db.consume_sign_in_report(&body.token).await?;
+    let user = db.get_user_by_id(user_id).await?;
+
+    db.remove_every_session(user.id).await?;
+    db.clear_session_origins(user.id).await?;
+    db.remove_password(&user.email).await?;
+    tracing::warn!("[Sign-In Reported] Email: {}, Session: {ssid}", user.email);
+
+    // the link came from the user's inbox, so the reset can start right away
+    let code = util::generate::hex_64(&util::generate::random_string(32));
+    db.request_password_reset(*conn_info, user.email, code.clone());
+
+    Ok((util::session::expire_session(), json!({ "code": code })))
+}
@@ -1,40 +1,85 @@
 use crate::ClientSocket;
 use axum::{
+    Extension,
     extract::{ConnectInfo, Query, State},
     http::HeaderMap,
     response::{IntoResponse, Redirect},
 };
 use base64::Engine;
-use database::Db;
+use database::{Db, UserData, applications::OidcInfo};
 use std::sync::Arc;
-use util::{AppError, oauth::OAuthProvider};
+use util::{AppError, oauth::OAuthProvider, session::ParsedSession};
 
 #[derive(serde::Deserialize)]
 pub struct ProviderQuery {
     by: String,
+    return_to: Option<String>,
 }
 
 pub async fn login(
     State(db): State<Arc<Db>>,
     ConnectInfo(conn_info): ConnectInfo<ClientSocket>,
+    headers: HeaderMap,
     Query(q): Query<ProviderQuery>,
+) -> Result<Redirect, AppError> {
+    // stops other sites from signing the browser into an account of theirs
+    crate::middleware::check_origin(db.config(), &headers)?;
+    let provider = OAuthProvider::from(q.by);
+    authorize(&db, *conn_info, provider, q.return_to.as_deref(), None)
+}
+
+#[derive(serde::Deserialize)]
+pub struct ReauthQuery {
+    return_to: Option<String>,
+}
+
+/// re-authenticates the session with the provider the account signed up with
+pub async fn reauth(
+    State(db): State<Arc<Db>>,
+    ConnectInfo(conn_info): ConnectInfo<ClientSocket>,
+    Extension(parsed_session): Extension<ParsedSession>,
+    Extension(user): Extension<UserData>,
+    Query(q): Query<ReauthQuery>,
+) -> Result<Redirect, AppError> {
+    let provider = user.lock().unwrap().0.oauth_provider;
+    if let OAuthProvider::None = provider {
+        return Err(AppError::BadReq("Your account isn't connected with an OpenID provider"));
+    }
+    let reauth = Some((parsed_session.user_id, parsed_session.unsigned_ssid));
+    authorize(&db, *conn_info, provider, q.return_to.as_deref(), reauth)
+}
+
+// redirects to the authorization endpoint of `provider`, the callback continues the flow
+fn authorize(
+    db: &Arc<Db>,
+    socket_addr: std::net::SocketAddr,
+    provider: OAuthProvider,
+    return_to: Option<&str>,
+    reauth: Option<(uuid::Uuid, uuid::Uuid)>,
 ) -> Result<Redirect, AppError> {
     // Generate state, nonce, and PKCE
     let csrf_state = util::generate::random_string(32);
     let nonce = util::generate::random_string(32);
     let (code_verifier, code_challenge) = util::generate::pkce();
-    let oauth_cfg = util::oauth::get_oauth_provider(OAuthProvider::from(q.by))
-        .ok_or(AppError::InvalidOAuthProvider)?;
+    let oauth_cfg =
+        util::oauth::get_oauth_provider(provider).ok_or(AppError::InvalidOAuthProvider)?;
 
     db.add_oidc_info(
-        *conn_info,
         csrf_state.clone(),
-        code_verifier,
-        nonce.clone(),
-        oauth_cfg.provider,
+        OidcInfo {
+            socket_addr,
+            code_verifier,
+            nonce: nonce.clone(),
+            provider: oauth_cfg.provider,
+            // kept for the callback, so only trusted pages are stored
+            return_to: return_to
+                .and_then(|v| crate::cors::safe_return_to(db.config(), v))
+                .map(str::to_string),
+            reauth,
+        },
     );
 
-    let redirect_uri = format!("{}/api/oauth2/callback", &*shared::SERVICE_DOMAIN);
+    let redirect_uri = format!("{}/api/oauth2/callback", db.config().service.domain);
     let mut request_uri = oauth_cfg.authorization_endpoint.clone();
     request_uri
         .query_pairs_mut()
@@ -46,6 +91,10 @@ pub async fn login(
         .append_pair("nonce", &nonce)
         .append_pair("code_challenge", &code_challenge)
         .append_pair("code_challenge_method", "S256");
+    if reauth.is_some() {
+        // asks the provider to authenticate the user again instead of reusing its session
+        request_uri.query_pairs_mut().append_pair("max_age", "0");
+    }
 
     Ok(Redirect::to(request_uri.as_str()))
 }
@@ -82,7 +131,7 @@ pub async fn callback(
     let oauth_cfg =
         util::oauth::get_oauth_provider(oidc_info.provider).ok_or(AppError::InvalidOAuthProvider)?;
     let client = reqwest::Client::new();
-    let redirect_uri = format!("{}/api/oauth2/callback", &*shared::SERVICE_DOMAIN);
+    let redirect_uri = format!("{}/api/oauth2/callback", db.config().service.domain);
 
     // Exchange authorization code for tokens
     let token_response = match client
@@ -150,6 +199,22 @@ pub async fn callback(
         fetch_user_info().await?
     };
 
+    // the provider has to vouch for the same account that is re-authenticating
+    if let Some((user_id, unsigned_ssid)) = oidc_info.reauth {
+        let user = db.get_user_by_id(user_id).await?;
+        if user.email != user_info.email
+            || user.oauth_provider.get_str() != oidc_info.provider.get_str()
+        {
+            return Err(AppError::Forbidden("Please continue with the account you signed in with"));
+        }
+        db.reauthenticate_session(user_id, unsigned_ssid).await?;
+        db.link_oauth_identity(user_id, oidc_info.provider, &user_info.sub, &user_info.email)
+            .await?;
+        db.remove_oidc_info(&q.csrf_state);
+        let return_to = crate::cors::return_to(db.config(), oidc_info.return_to.as_deref());
+        return Ok(crate::cors::trusted_redirect(db.config(), &return_to).into_response());
+    }
+
     match db.get_user_by_email(&user_info.email).await {
         // if the user found inside database
         Ok(user) => match user.oauth_provider {
@@ -159,34 +224,31 @@ pub async fn callback(
             )),
             // login if the user is already registered with OIDC
             _ => {
-                let (new_session, parsed_session, set_cookie_headermap) =
-                    util::session::create_session(user.id, &headers, *conn_info);
-                db.add_session(user.id, new_session.clone()).await?;
-                // activating session by adding it to `Db::active`
-                if let Some((arc_wrapped, is_session_present)) = db.get_active_user(&parsed_session)
-                    && !is_session_present
-                {
-                    let mut guard = arc_wrapped.lock().unwrap();
-                    guard.1.push(new_session);
-                } else {
-                    db.make_user_active(user, new_session);
-                }
+                db.link_oauth_identity(user.id, oidc_info.provider, &user_info.sub, &user.email)
+                    .await?;
+                let (set_cookie_headermap, _) =
+                    super::logging::sign_in(&db, user, &headers, *conn_info, true, None).await?;
                 db.remove_oidc_info(&q.csrf_state);
-                Ok((set_cookie_headermap, Redirect::to("/")).into_response()) // REDIRECT ENDPOINT NEEDS TO BE CHECKED
+                let return_to = crate::cors::return_to(db.config(), oidc_info.return_to.as_deref());
+                Ok((set_cookie_headermap, crate::cors::trusted_redirect(db.config(), &return_to))
+                    .into_response())
             }
         },
         // create registrant if the user is trying to register using open id connect
         Err(AppError::UserNotFound) => {
             db.create_registrant_oidc(
                 *conn_info,
+                user_info.sub,
                 user_info.name,
                 user_info.email,
                 user_info.picture,
+                util::mail::locale_from_headers(&headers),
                 oidc_info.provider,
+                oidc_info.return_to,
             )
             .await?;
             db.remove_oidc_info(&q.csrf_state);
-            Ok(Redirect::to("/login").into_response())
+            Ok(crate::cors::trusted_redirect(db.config(), "/login").into_response())
         }
         Err(e) => Err(e),
     }
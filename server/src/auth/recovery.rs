@@ -2,11 +2,12 @@ use crate::ClientSocket;
 use axum::{
     Json,
     extract::{ConnectInfo, Query, State},
+    http::HeaderMap,
 };
 use axum_extra::{json, response::ErasedJson};
 use database::Db;
 use std::sync::Arc;
-use util::AppError;
+use util::{AppError, mail::Template};
 
 #[derive(serde::Deserialize)]
 pub struct ForgotPasswordRequest {
@@ -17,22 +18,23 @@ pub struct ForgotPasswordRequest {
 pub async fn forgot_password(
     State(db): State<Arc<Db>>,
     ConnectInfo(conn_info): ConnectInfo<ClientSocket>,
+    headers: HeaderMap,
     Json(body): Json<ForgotPasswordRequest>,
 ) -> Result<ErasedJson, AppError> {
     shared::validation::is_email_valid(&body.email)?;
     let code = util::generate::hex_64(&body.email);
     db.request_password_reset(*conn_info, body.email.clone(), code.clone());
 
-    util::mail::send(
+    let link = format!("{}/reset-password?code={code}", db.config().service.domain);
+    let locale = user_locale(&db, &body.email, &headers).await;
+    db.enqueue_email(
+        format!("password_reset:{}:{}", body.email, util::generate::random_string(16)),
         body.email.clone(),
-        format!("{} password reset request", &*shared::SERVICE_NAME),
-        format!(
-            "<h1>Reset your password?</h1>\nIf you requested a password reset for {} press on this link {}\nIf you didn't make the request, please ignore this email.\nThanks, {}\n",
-            body.email,
-            format_args!("{}/reset_password?code={code}", &*shared::SERVICE_DOMAIN),
-            &*shared::SERVICE_NAME
-        ),
-    ).await?;
+        Template::PasswordReset,
+        locale.as_deref(),
+        &[("email", &body.email), ("link", &link)],
+    )
+    .await?;
 
     Ok(json!({
         "message": format!("Check your email to reset password")
@@ -52,16 +54,25 @@ pub struct ResetPasswordRequest {
 pub async fn reset_password(
     State(db): State<Arc<Db>>,
     ConnectInfo(conn_info): ConnectInfo<ClientSocket>,
+    headers: HeaderMap,
     Query(q): Query<ResetPasswordQuery>,
     Json(body): Json<ResetPasswordRequest>,
 ) -> Result<ErasedJson, AppError> {
     shared::validation::is_password_strong(&body.password)?;
     let email = db.reset_password(*conn_info, &q.code, &body.password).await?;
 
-    util::mail::send(
+    // the account may have been taken over, so every session is ended
+    let user = db.get_user_by_email(&email).await?;
+    db.remove_every_session(user.id).await?;
+    db.clear_session_origins(user.id).await?;
+
+    let locale = user.locale.or_else(|| util::mail::locale_from_headers(&headers));
+    db.enqueue_email(
+        format!("password_changed:{}", q.code),
         email.clone(),
-        format!("Your {} password has been changed", &*shared::SERVICE_NAME),
-        format!("Your password for {email} has been changed.\nThanks, {}\n", &*shared::SERVICE_NAME),
+        Template::PasswordChanged,
+        locale.as_deref(),
+        &[("email", &email)],
     )
     .await?;
 
@@ -69,3 +80,11 @@ pub async fn reset_password(
         "message": format!("Your password for {email} has been changed")
     }))
 }
+
+// locale selected by the user or else the one requested by the client
+async fn user_locale(db: &Arc<Db>, email: &str, headers: &HeaderMap) -> Option<String> {
+    match db.get_user_by_email(email).await {
+        Ok(user) if user.locale.is_some() => user.locale,
+        _ => util::mail::locale_from_headers(headers),
+    }
+}
@@ -1,21 +1,23 @@
 use crate::ClientSocket;
-use axum::extract::ConnectInfo;
+use axum::extract::{ConnectInfo, Query};
 use axum::http::{StatusCode, header::HeaderMap};
 use axum::{Json, extract::State, response::IntoResponse};
 use axum_extra::{json, response::ErasedJson};
 use database::Db;
 use std::sync::Arc;
-use util::AppError;
+use util::{AppError, mail::Template};
 
 #[derive(serde::Deserialize)]
 pub struct CreateUserRequest {
     name: String,
     email: String,
+    return_to: Option<String>,
 }
 
 pub async fn start(
     State(db): State<Arc<Db>>,
     ConnectInfo(conn_info): ConnectInfo<ClientSocket>,
+    headers: HeaderMap,
     Json(body): Json<CreateUserRequest>,
 ) -> Result<ErasedJson, AppError> {
     // validating user sent data
@@ -25,16 +27,26 @@ pub async fn start(
     let otp = util::generate::otp(&body.email);
     tracing::info!("Email: {}, OTP: {}", body.email, otp);
 
-    db.create_registrant(*conn_info, body.name, body.email.clone(), otp.clone()).await?;
-
-    // sending otp to the email
-    util::mail::send(
-        body.email,
-        format!("{otp} is your {} verification code", &*shared::SERVICE_NAME),
-        format!("Confirm your email address\n {otp}\n Thanks,\n {}", &*shared::SERVICE_NAME),
+    let locale = util::mail::locale_from_headers(&headers);
+    let return_to = body
+        .return_to
+        .as_deref()
+        .and_then(|v| crate::cors::safe_return_to(db.config(), v))
+        .map(str::to_string);
+    db.create_registrant(
+        *conn_info,
+        body.name,
+        body.email.clone(),
+        otp.clone(),
+        locale.clone(),
+        return_to,
     )
     .await?;
 
+    // queueing otp mail to the email
+    let key = format!("otp:{}:{}", body.email, util::generate::random_string(16));
+    db.enqueue_email(key, body.email, Template::Otp, locale.as_deref(), &[("otp", &otp)]).await?;
+
     Ok(json!({
         "message": "Your information has been accepted"
     }))
@@ -47,18 +59,16 @@ pub struct ResendOtpRequest {
 
 pub async fn resend_otp(
     State(db): State<Arc<Db>>,
+    headers: HeaderMap,
     Json(body): Json<ResendOtpRequest>,
 ) -> Result<ErasedJson, AppError> {
     let otp = util::generate::otp(&body.email);
     db.update_registrant_otp(&body.email, otp.clone()).await?;
 
     // resending otp to the email
-    util::mail::send(
-        body.email,
-        format!("{otp} is your {} verification code", &*shared::SERVICE_NAME),
-        format!("Confirm your email address\n {otp}\n Thanks,\n {}", &*shared::SERVICE_NAME),
-    )
-    .await?;
+    let locale = util::mail::locale_from_headers(&headers);
+    let key = format!("otp:{}:{}", body.email, util::generate::random_string(16));
+    db.enqueue_email(key, body.email, Template::Otp, locale.as_deref(), &[("otp", &otp)]).await?;
 
     Ok(json!({
         "message": "The email has been sent"
@@ -73,20 +83,20 @@ pub struct VerifyEmailRequest {
 
 pub async fn verify_email(
     State(db): State<Arc<Db>>,
+    headers: HeaderMap,
     Json(body): Json<VerifyEmailRequest>,
 ) -> Result<ErasedJson, AppError> {
     // verifying email by checking if the otp sent by user matches the original one
     db.verify_registrant_email(&body.email, &body.otp).await?;
 
     // sending email verification success
-    util::mail::send(
+    let locale = util::mail::locale_from_headers(&headers);
+    db.enqueue_email(
+        format!("welcome:{}:{}", body.email, body.otp),
         body.email.clone(),
-        format!("Your email {} has been verified successfully", body.email),
-        format!(
-            "Your email {} has been verified successfully\n Thanks,\n {}",
-            body.email,
-            &*shared::SERVICE_NAME
-        ),
+        Template::Welcome,
+        locale.as_deref(),
+        &[("email", &body.email)],
     )
     .await?;
 
@@ -114,6 +124,19 @@ pub async fn set_password(
     }))
 }
 
+#[derive(serde::Deserialize)]
+pub struct SuggestUsernamesQuery {
+    email: String,
+}
+
+pub async fn suggest_usernames(
+    State(db): State<Arc<Db>>,
+    Query(q): Query<SuggestUsernamesQuery>,
+) -> Result<ErasedJson, AppError> {
+    let suggestions = db.suggest_registrant_usernames(&q.email).await?;
+    Ok(json!({ "suggestions": suggestions }))
+}
+
 #[derive(serde::Deserialize)]
 pub struct SetUsernameRequest {
     email: String,
@@ -129,12 +152,15 @@ pub async fn set_username(
     shared::validation::is_username_valid(&body.username)?;
 
     // registering user to primary database
+    let return_to = db.get_registrant_return_to(&body.email);
     let user = db.set_registrant_username(body.email, body.username).await?;
 
+    let policy = util::session::policy(user.role());
     let (new_session, _, set_cookie_headermap) =
-        util::session::create_session(user.id, &headers, *conn_info);
+        util::session::create_session(user.id, &headers, *conn_info, policy, true);
 
-    let res_body = crate::user_data::arrange(&user, &vec![&new_session]);
+    let return_to = crate::cors::return_to(db.config(), return_to.as_deref());
+    let res_body = crate::user_data::arrange_returning(&user, &vec![&new_session], return_to);
     db.add_session(user.id, new_session.clone()).await?;
     db.make_user_active(user, new_session);
 
@@ -0,0 +1,136 @@
+use axum::http::{HeaderName, HeaderValue, Method, header};
+use std::time::Duration;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use util::config::Config;
+
+/// checks whether `url` (an origin or a full url) belongs to a trusted origin
+///
+/// besides `SERVICE_DOMAIN`, the origins of `cors.allowed_origins` (`CORS_ALLOWED_ORIGINS`) are
+/// trusted, the same list allows cross-origin requests, passes the CSRF origin check and
+/// validates the redirects after signing in
+pub fn is_trusted_origin(config: &Config, url: &str) -> bool {
+    let allowed = &config.cors.allowed_origins;
+    std::iter::once(&config.service.domain).chain(allowed).any(|origin| {
+        let origin = origin.trim_end_matches('/');
+        url == origin || url.strip_prefix(origin).is_some_and(|rest| rest.starts_with('/'))
+    })
+}
+
+/// returns `target` if it's a local path or belongs to a trusted origin
+pub fn safe_return_to<'a>(config: &Config, target: &'a str) -> Option<&'a str> {
+    // `//host` and `/\host` are read as other hosts by browsers
+    let is_path = target.starts_with('/') && !target.starts_with("//") && !target.contains('\\');
+    let is_safe =
+        (is_path || is_trusted_origin(config, target)) && !target.chars().any(char::is_control);
+    if !is_safe {
+        tracing::warn!("[Untrusted Redirect] Target: {target}");
+    }
+    is_safe.then_some(target)
+}
+
+/// returns the validated `return_to`, or `/` if it's missing or untrusted
+pub fn return_to(config: &Config, target: Option<&str>) -> String {
+    target.and_then(|v| safe_return_to(config, v)).unwrap_or("/").to_string()
+}
+
+/// redirects to `target` if it's a path or belongs to a trusted origin, otherwise to `/`
+pub fn trusted_redirect(config: &Config, target: &str) -> axum::response::Redirect {
+    axum::response::Redirect::to(safe_return_to(config, target).unwrap_or("/"))
+}
+
+/// builds the CORS policy from the `[cors]` config
+///
+/// no origin is allowed unless listed in `cors.allowed_origins`, the values were validated
+/// at startup
+pub fn cors_layer(config: &Config) -> CorsLayer {
+    let config = &config.cors;
+    let origins = config
+        .allowed_origins
+        .iter()
+        .filter_map(|v| HeaderValue::from_str(v.trim_end_matches('/')).ok())
+        .collect::<Vec<_>>();
+    let methods = config
+        .allowed_methods
+        .iter()
+        .filter_map(|v| v.trim().to_uppercase().parse::<Method>().ok())
+        .collect::<Vec<_>>();
+
+    CorsLayer::new()
+        .allow_origin(AllowOrigin::list(origins))
+        .allow_credentials(config.allow_credentials)
+        .allow_methods(methods)
+        .allow_headers([header::CONTENT_TYPE, HeaderName::from_static("x-csrf-token")])
+        .max_age(Duration::from_secs(config.max_age))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    macro_rules! return_to_test {
+        ($($name:ident: $exp:expr,)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    let (target, safe) = $exp;
+                    let config = crate::test_config();
+                    assert_eq!(safe_return_to(&config, target), safe.then_some(target));
+                }
+            )*
+        };
+    }
+
+    macro_rules! trusted_origin_test {
+        ($($name:ident: $exp:expr,)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    let (url, trusted) = $exp;
+                    assert_eq!(is_trusted_origin(&crate::test_config(), url), trusted);
+                }
+            )*
+        };
+    }
+
+    #[test]
+    fn return_to_falls_back_to_root() {
+        let config = crate::test_config();
+        assert_eq!(return_to(&config, None), "/");
+        assert_eq!(return_to(&config, Some("//evil.com")), "/");
+        assert_eq!(return_to(&config, Some("/settings")), "/settings");
+    }
+
+    return_to_test! {
+        local_path: ("/settings?tab=security#sessions", true),
+        root_path: ("/", true),
+        service_url: ("https://stronghold.example.com/settings", true),
+        service_origin: ("https://stronghold.example.com", true),
+        allowed_origin_url: ("https://app.example.com/done", true),
+        protocol_relative: ("//evil.com", false),
+        protocol_relative_path: ("//evil.com/settings", false),
+        backslash_host: ("/\\evil.com", false),
+        backslashes_only: ("\\\\evil.com", false),
+        service_as_subdomain: ("https://stronghold.example.com.evil.com", false),
+        other_subdomain: ("https://stronghold.evil.com/settings", false),
+        service_as_userinfo: ("https://stronghold.example.com@evil.com", false),
+        userinfo_with_path: ("https://stronghold.example.com@evil.com/settings", false),
+        other_scheme: ("http://stronghold.example.com/settings", false),
+        javascript_url: ("javascript:alert(1)", false),
+        empty: ("", false),
+        header_injection: ("/settings\r\nSet-Cookie: a=b", false),
+        null_byte: ("/settings\0", false),
+        tab_in_url: ("https://stronghold.example.com/\tsettings", false),
+    }
+
+    trusted_origin_test! {
+        service_domain: ("https://stronghold.example.com", true),
+        service_page: ("https://stronghold.example.com/login", true),
+        allowed_origin: ("https://app.example.com", true),
+        trailing_dot_subdomain: ("https://stronghold.example.com.evil.com", false),
+        userinfo: ("https://stronghold.example.com@evil.com", false),
+        port: ("https://stronghold.example.com:8443", false),
+        insecure_scheme: ("http://stronghold.example.com", false),
+        parent_domain: ("https://example.com", false),
+        null_origin: ("null", false),
+    }
+}
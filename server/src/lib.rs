@@ -1,6 +1,7 @@
 mod admin;
 mod auth;
 mod client_socket;
+mod cors;
 mod middleware;
 mod settings;
 mod stream_drop;
@@ -9,19 +10,57 @@ mod user_data;
 
 pub use client_socket::ClientSocket;
 
-/// main router for server routes
-pub async fn routes() -> axum::Router {
+use database::Db;
+use std::sync::Arc;
+
+/// main router for server routes, also starts the background workers
+///
+/// every router shares `db`, together with the config it was created with
+pub fn routes(db: Arc<Db>) -> axum::Router {
+    db.spawn_outbox_worker();
+    db.spawn_signing_key_refresher();
+    db.spawn_deleted_user_purger();
+    let cors = cors::cors_layer(db.config());
     axum::Router::new()
-        .merge(admin::admin_routes().await)
-        .merge(auth::auth_routes().await)
-        .merge(settings::settings_routes().await)
-        .merge(user::user_routes().await)
+        .merge(admin::admin_routes(db.clone()))
+        .merge(auth::auth_routes(db.clone()))
+        .merge(settings::settings_routes(db.clone()))
+        .merge(user::user_routes(db))
+        .layer(cors)
 }
 
-pub async fn get_custom_listener(addr: std::net::SocketAddr) -> stream_drop::CustomListener {
+pub async fn get_custom_listener(
+    addr: std::net::SocketAddr,
+    db: Arc<Db>,
+) -> stream_drop::CustomListener {
     use axum::serve::Listener;
     let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
-    let custom_listener = stream_drop::CustomListener::from(listener);
+    let custom_listener = stream_drop::CustomListener::new(listener, db);
     tracing::info!("[+] listening on {}", custom_listener.local_addr().unwrap());
     custom_listener
 }
+
+// config of the unit tests, trusting `https://app.example.com` besides the service domain
+//
+// sets up `util` for it on the first call, as the CSRF tokens are signed with its secret key
+#[cfg(test)]
+pub(crate) fn test_config() -> util::config::Config {
+    static INIT: std::sync::Once = std::sync::Once::new();
+    let config = util::config::Config::from_toml(
+        r#"
+        [service]
+        name = "Stronghold"
+        domain = "https://stronghold.example.com"
+        secret_key = "secret"
+
+        [mail]
+        transport = "log"
+
+        [cors]
+        allowed_origins = ["https://app.example.com"]
+        "#,
+    )
+    .unwrap();
+    INIT.call_once(|| util::init(&config).unwrap());
+    config
+}
@@ -1,13 +1,32 @@
-use axum::{extract::Request, middleware::Next, response::Response};
-use database::users::User;
-use util::AppError;
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use database::Db;
+use std::sync::Arc;
+use util::{AppError, session::ParsedSession};
 
-pub async fn admin_middleware(req: Request, next: Next) -> Result<Response, AppError> {
-    if let Some(u) = req.extensions().get::<User>()
-        && u.username == "admin"
-    {
-        Ok(next.run(req).await)
-    } else {
-        Err(AppError::NotFound)
+/// lets only admins through, the other users get a 404
+///
+/// the role and the session are read from the database instead of `Db::active`, so that an admin
+/// revoked by another process (e.g. `stronghold-admin user revoke-admin`) loses access right away
+/// rather than once the cached session is found to be gone
+///
+/// must be layered inside `auth_middleware`, as it reads the `ParsedSession` set by it
+pub async fn admin_middleware(
+    State(db): State<Arc<Db>>,
+    req: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let parsed_session = req.extensions().get::<ParsedSession>().ok_or(AppError::NotFound)?;
+    match db.get_all_by_parsed_session(parsed_session).await {
+        Ok((user, _)) if user.role() == "admin" => Ok(next.run(req).await),
+        Ok(_) => Err(AppError::NotFound),
+        Err(AppError::SessionExpired) => {
+            db.remove_active_user(parsed_session);
+            Err(AppError::NotFound)
+        }
+        Err(e) => Err(e),
     }
 }
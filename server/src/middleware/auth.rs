@@ -1,20 +1,72 @@
 use axum::{
-    extract::{ConnectInfo, Request},
+    extract::{ConnectInfo, Request, State},
+    http::{HeaderMap, header},
     middleware::Next,
     response::{IntoResponse, Response},
 };
+use database::Db;
+use std::sync::Arc;
+use time::OffsetDateTime;
 use util::{
     AppError,
-    session::{ParsedSession, SessionStatus},
+    session::{ParsedSession, SessionStatus, keyring},
 };
 
+// what the session cookie of a request turned out to be
+enum Auth {
+    // the user and its session were added to the request, the response has to set the cookies
+    // (e.g. when re-signed)
+    Signed(HeaderMap),
+    // the session was replaced by a refreshed one, the response has to set its cookies
+    Refreshed(HeaderMap),
+}
+
 pub async fn auth_middleware(
+    State(db): State<Arc<Db>>,
+    ConnectInfo(conn_info): ConnectInfo<crate::ClientSocket>,
+    mut req: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    match authenticate(&db, conn_info, &mut req).await? {
+        Auth::Signed(set_cookie) => Ok(with_cookies(next.run(req).await, set_cookie)),
+        // the CSRF token of the request belongs to the old session, so the handler isn't run,
+        // in the case of `Expiring` the new ssid will override the old one
+        Auth::Refreshed(set_cookie) => Ok(set_cookie.into_response()),
+    }
+}
+
+/// `auth_middleware` for routes signed out visitors can use too, the user is only added to the
+/// request when it has a valid session
+///
+/// invalid, expired and idle sessions are treated as signed out, refreshed sessions still reach
+/// the handler
+pub async fn optional_auth_middleware(
+    State(db): State<Arc<Db>>,
     ConnectInfo(conn_info): ConnectInfo<crate::ClientSocket>,
     mut req: Request,
     next: Next,
 ) -> Result<Response, AppError> {
+    let set_cookie = match authenticate(&db, conn_info, &mut req).await {
+        Ok(Auth::Signed(v) | Auth::Refreshed(v)) => v,
+        Err(AppError::ServerError) => return Err(AppError::ServerError),
+        // the cookies of an invalid session are cleared along the way
+        Err(AppError::InvalidSession(v)) => v,
+        Err(_) => HeaderMap::new(),
+    };
+    Ok(with_cookies(next.run(req).await, set_cookie))
+}
+
+// checks the session cookie of `req`, adding the `ParsedSession` and `UserData` of a valid session
+// to its extensions
+async fn authenticate(
+    db: &Arc<Db>,
+    conn_info: crate::ClientSocket,
+    req: &mut Request,
+) -> Result<Auth, AppError> {
     let parsed_session = ParsedSession::parse_and_verify_from_headers(req.headers())?;
-    let db = database::Db::new().await;
+
+    // cookies signed with an older key are re-signed with the current one
+    let resign = util::session::key_id(&parsed_session.ssid) != keyring::current_key_id();
 
     // Check if user is already in cache (found inside `Db::active`)
     if let Some((arc_wrapped, is_session_present)) = db.get_active_user(&parsed_session) {
@@ -24,42 +76,99 @@ pub async fn auth_middleware(
             let mut guard = arc_wrapped.lock().unwrap();
             guard.1.push(session);
         }
+        let unsigned_ssid = parsed_session.unsigned_ssid;
+        let session = {
+            let mut guard = arc_wrapped.lock().unwrap();
+            let policy = util::session::policy(guard.0.role());
+            match guard.1.iter_mut().find(|s| s.unsigned_ssid == unsigned_ssid) {
+                // idle or past the maximum lifetime
+                Some(s) if matches!(s.session_status(policy), SessionStatus::Invalid) => None,
+                Some(s) => {
+                    if s.touch() {
+                        touch_session(db, &parsed_session, s.last_used);
+                    }
+                    Some(s.clone())
+                }
+                None => None,
+            }
+        };
+        let Some(session) = session else {
+            db.remove_session(parsed_session.user_id, unsigned_ssid).await?;
+            db.remove_active_user(&parsed_session);
+            return Err(AppError::InvalidSession(util::session::expire_session()));
+        };
         req.extensions_mut().insert(parsed_session);
         req.extensions_mut().insert(arc_wrapped);
-        return Ok(next.run(req).await);
+        let mut set_cookie = HeaderMap::new();
+        if resign {
+            set_cookie.append(header::SET_COOKIE, util::session::resign_session(&session));
+        }
+        return Ok(Auth::Signed(set_cookie));
     }
 
     // User not cached, fetch from database (not found inside `Db::active`)
-    let (user, session) = db.get_all_by_parsed_session(&parsed_session).await?;
+    let (user, mut session) = db.get_all_by_parsed_session(&parsed_session).await?;
 
-    match session.session_status() {
+    let policy = util::session::policy(user.role());
+
+    match session.session_status(policy) {
         SessionStatus::Valid(_) => {
+            if session.touch() {
+                touch_session(db, &parsed_session, session.last_used);
+            }
+            let mut set_cookie = HeaderMap::new();
+            if resign {
+                set_cookie.append(header::SET_COOKIE, util::session::resign_session(&session));
+            }
             // adding session and `User` to `Db::active`
             let arc_wrapped = db.make_user_active(user, session);
             req.extensions_mut().insert(parsed_session);
             req.extensions_mut().insert(arc_wrapped);
+            Ok(Auth::Signed(set_cookie))
         }
 
         SessionStatus::Expiring(_) | SessionStatus::Refreshable(_) => {
-            // automatic session refresh code block
-            let (new_session, _, set_cookie_headermap) =
-                util::session::create_session(user.id, req.headers(), *conn_info);
+            // automatic session refresh code block, keeping the sign-in time of the old session
+            let (new_session, new_parsed_session, set_cookie_headermap) =
+                util::session::refresh_session(user.id, &session, req.headers(), *conn_info, policy);
 
             // replacing the old session with new session
             db.add_session(user.id, new_session.clone()).await?;
             db.remove_session(user.id, session.unsigned_ssid).await?;
-            db.make_user_active(user, new_session);
+            let arc_wrapped = db.make_user_active(user, new_session);
+            req.extensions_mut().insert(new_parsed_session);
+            req.extensions_mut().insert(arc_wrapped);
 
-            // in the case of `Expiring` the new ssid will override the old one
-            return Ok(set_cookie_headermap.into_response());
+            Ok(Auth::Refreshed(set_cookie_headermap))
         }
 
         SessionStatus::Invalid => {
+            // idle sessions aren't expired yet, so they are removed separately
+            db.remove_session(user.id, session.unsigned_ssid).await?;
             db.clear_expired_sessions(user.id).await?;
 
-            return Err(AppError::InvalidSession(util::session::expire_session()));
+            Err(AppError::InvalidSession(util::session::expire_session()))
         }
     }
+}
+
+// appends `set_cookie` to the headers of `res`, keeping the cookies set by the handler
+fn with_cookies(mut res: Response, set_cookie: HeaderMap) -> Response {
+    for (name, value) in set_cookie.iter() {
+        res.headers_mut().append(name, value.clone());
+    }
+    res
+}
 
-    Ok(next.run(req).await)
+// persists `last_used` without holding up the request
+//
+// sessions revoked outside of this process (e.g. by `stronghold-admin`) are only found here,
+// they're dropped from `Db::active` so the next request reads them from the database
+fn touch_session(db: &Arc<Db>, parsed_session: &ParsedSession, last_used: OffsetDateTime) {
+    let (db, parsed_session) = (db.clone(), parsed_session.clone());
+    tokio::spawn(async move {
+        if let Ok(false) = db.touch_session(parsed_session.unsigned_ssid, last_used).await {
+            db.remove_active_user(&parsed_session);
+        }
+    });
 }
@@ -0,0 +1,135 @@
+use axum::{
+    extract::{Request, State},
+    http::{HeaderMap, Method, header},
+    middleware::Next,
+    response::Response,
+};
+use database::Db;
+use std::sync::Arc;
+use util::{AppError, config::Config, session::ParsedSession};
+
+/// header carrying the token returned by `util::session::csrf_token`
+pub const CSRF_HEADER: &str = "x-csrf-token";
+
+/// rejects state-changing requests without the CSRF token of the session or from other origins
+///
+/// must be layered inside `auth_middleware`, as it reads the `ParsedSession` set by it
+pub async fn csrf_middleware(
+    State(db): State<Arc<Db>>,
+    req: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    check_csrf(db.config(), req.method(), req.headers(), req.extensions().get::<ParsedSession>())?;
+    Ok(next.run(req).await)
+}
+
+// the checks of `csrf_middleware`, `parsed_session` is the session the request was made with
+fn check_csrf(
+    config: &Config,
+    method: &Method,
+    headers: &HeaderMap,
+    parsed_session: Option<&ParsedSession>,
+) -> Result<(), AppError> {
+    if matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS) {
+        return Ok(());
+    }
+    check_origin(config, headers)?;
+
+    let parsed_session = parsed_session.ok_or(AppError::Forbidden("Missing session"))?;
+    let token = headers
+        .get(CSRF_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .ok_or(AppError::Forbidden("Missing CSRF token"))?;
+    let expected = format!("csrf:{}", parsed_session.unsigned_ssid);
+    if !util::generate::verify_signature(&expected, token) {
+        return Err(AppError::Forbidden("Invalid CSRF token"));
+    }
+    Ok(())
+}
+
+/// rejects requests whose `Origin` (or `Referer`) isn't trusted
+///
+/// browsers send one of them with every cross-site POST, so a missing one can only come from
+/// non-browser clients
+pub fn check_origin(config: &Config, headers: &HeaderMap) -> Result<(), AppError> {
+    let source = headers
+        .get(header::ORIGIN)
+        .or_else(|| headers.get(header::REFERER))
+        .map(|v| v.to_str().unwrap_or_default());
+    match source {
+        Some(source) if !crate::cors::is_trusted_origin(config, source) => {
+            Err(AppError::Forbidden("Untrusted origin"))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn session() -> ParsedSession {
+        let unsigned_ssid = uuid::Uuid::new_v4();
+        ParsedSession { ssid: String::new(), unsigned_ssid, user_id: uuid::Uuid::new_v4() }
+    }
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn csrf_token_test() {
+        let config = crate::test_config();
+        let session = session();
+        let token = util::session::csrf_token(&session.unsigned_ssid);
+        let post = |headers: &HeaderMap, session: Option<&ParsedSession>| {
+            check_csrf(&config, &Method::POST, headers, session).err()
+        };
+
+        assert_eq!(post(&headers(&[(CSRF_HEADER, &token)]), Some(&session)), None);
+        // safe methods don't change anything
+        assert!(check_csrf(&config, &Method::GET, &HeaderMap::new(), None).is_ok());
+
+        let missing = post(&HeaderMap::new(), Some(&session));
+        assert_eq!(missing, Some(AppError::Forbidden("Missing CSRF token")));
+        let no_session = post(&headers(&[(CSRF_HEADER, &token)]), None);
+        assert_eq!(no_session, Some(AppError::Forbidden("Missing session")));
+
+        let invalid = || Some(AppError::Forbidden("Invalid CSRF token"));
+        // a signature of the session made for another purpose
+        let forged = util::generate::signature(&session.unsigned_ssid.to_string());
+        assert_eq!(post(&headers(&[(CSRF_HEADER, &forged)]), Some(&session)), invalid());
+        assert_eq!(post(&headers(&[(CSRF_HEADER, "not-a-token")]), Some(&session)), invalid());
+        // the token of another session
+        let other = util::session::csrf_token(&uuid::Uuid::new_v4());
+        assert_eq!(post(&headers(&[(CSRF_HEADER, &other)]), Some(&session)), invalid());
+    }
+
+    #[test]
+    fn check_origin_test() {
+        let config = crate::test_config();
+        let untrusted = || Some(AppError::Forbidden("Untrusted origin"));
+        let check = |pairs: &[(&'static str, &str)]| check_origin(&config, &headers(pairs)).err();
+
+        assert_eq!(check(&[]), None);
+        assert_eq!(check(&[("origin", "https://stronghold.example.com")]), None);
+        assert_eq!(check(&[("origin", "https://app.example.com")]), None);
+        assert_eq!(check(&[("referer", "https://stronghold.example.com/login")]), None);
+
+        assert_eq!(check(&[("origin", "https://evil.com")]), untrusted());
+        assert_eq!(check(&[("origin", "https://stronghold.example.com.evil.com")]), untrusted());
+        assert_eq!(check(&[("origin", "null")]), untrusted());
+        assert_eq!(
+            check(&[("referer", "https://evil.com/https://stronghold.example.com")]),
+            untrusted()
+        );
+        // the origin wins over the referer
+        let pairs = [("origin", "https://evil.com"), ("referer", "https://stronghold.example.com/")];
+        assert_eq!(check(&pairs), untrusted());
+    }
+}
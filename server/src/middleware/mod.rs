@@ -1,5 +1,9 @@
 mod admin;
 mod auth;
+mod csrf;
+mod reauth;
 
 pub use admin::admin_middleware;
-pub use auth::auth_middleware;
+pub use auth::{auth_middleware, optional_auth_middleware};
+pub use csrf::{check_origin, csrf_middleware};
+pub use reauth::RecentAuth;
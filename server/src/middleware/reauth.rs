@@ -0,0 +1,32 @@
+use axum::{extract::FromRequestParts, http::request::Parts};
+use database::UserData;
+use util::{AppError, session::ParsedSession};
+
+/// guards sensitive operations, the session must have been authenticated within
+/// `Session::REAUTH_WINDOW`, e.g. with `/api/settings/reauth`
+///
+/// only usable behind `auth_middleware`, as it reads the session set by it
+pub struct RecentAuth;
+
+impl<S: Send + Sync> FromRequestParts<S> for RecentAuth {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+        let parsed_session =
+            parts.extensions.get::<ParsedSession>().ok_or(AppError::Forbidden("Missing session"))?;
+        let user =
+            parts.extensions.get::<UserData>().ok_or(AppError::Forbidden("Missing session"))?;
+
+        let guard = user.lock().unwrap();
+        let is_recent = guard
+            .1
+            .iter()
+            .find(|s| s.unsigned_ssid == parsed_session.unsigned_ssid)
+            .is_some_and(|s| s.is_recently_authenticated());
+        if is_recent {
+            Ok(Self)
+        } else {
+            Err(AppError::Forbidden("Please confirm it's you to continue"))
+        }
+    }
+}
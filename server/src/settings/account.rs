@@ -1,18 +1,62 @@
-use axum::{Extension, extract::State};
-use axum_extra::{json, response::ErasedJson};
+use crate::middleware::RecentAuth;
+use axum::{Extension, Json, extract::State, http::HeaderMap, response::IntoResponse};
+use axum_extra::json;
 use database::{Db, UserData};
 use std::sync::Arc;
-use util::{AppError, session::ParsedSession};
+use time::format_description::well_known::Rfc3339;
+use util::{AppError, mail::Template};
 
+/// schedules the deletion of the account, it can be restored until the grace period ends
 pub async fn delete_account(
     State(db): State<Arc<Db>>,
-    Extension(parsed_session): Extension<ParsedSession>,
     Extension(user): Extension<UserData>,
-) -> Result<ErasedJson, AppError> {
-    db.remove_active_user(&parsed_session);
-    let u = user.lock().unwrap().0.clone(); // this clone can be avoided
-    db.delete_user(u).await?;
+    _: RecentAuth,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, AppError> {
+    let u = user.lock().unwrap().0.clone();
+    let (purge_at, restore_token) = db.delete_user(u.clone()).await?;
+    db.remove_every_session(u.id).await?;
+
+    // the link opens a page asking for confirmation, as mail scanners follow links on their own
+    let link = format!("{}/restore-account?token={restore_token}", db.config().service.domain);
+    let date = purge_at.date().to_string();
+    let locale = u.locale.clone().or_else(|| util::mail::locale_from_headers(&headers));
+    db.enqueue_email(
+        format!("account_deletion:{}:{restore_token}", u.id),
+        u.email.clone(),
+        Template::AccountDeletion,
+        locale.as_deref(),
+        &[("email", &u.email), ("date", &date), ("link", &link)],
+    )
+    .await?;
+
+    Ok((
+        util::session::expire_session(),
+        json!({
+            "message": "Your account will be deleted, sign in again before then to restore it",
+            "purge_at": purge_at.format(&Rfc3339).unwrap_or_default(),
+        }),
+    ))
+}
+
+#[derive(serde::Deserialize)]
+pub struct CancelDeletionRequest {
+    token: String,
+}
+
+/// restores an account, confirmed from the page opened by the link sent when its deletion was
+/// scheduled
+pub async fn cancel_deletion(
+    State(db): State<Arc<Db>>,
+    Json(body): Json<CancelDeletionRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    db.restore_user_by_token(&body.token).await.map_err(|e| match e {
+        AppError::UserNotFound => {
+            AppError::BadReq("The account was already restored or can no longer be restored")
+        }
+        e => e,
+    })?;
     Ok(json!({
-        "message": "Your account has been deleted"
+        "message": "Your account has been restored, you can sign in again"
     }))
 }
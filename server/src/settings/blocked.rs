@@ -0,0 +1,79 @@
+use axum::{Extension, Json, extract::State};
+use axum_extra::{json, response::ErasedJson};
+use database::{Db, UserData, users::BlockKind};
+use std::sync::Arc;
+use util::AppError;
+
+/// the users blocked or muted by the user
+pub async fn list_blocked(
+    State(db): State<Arc<Db>>,
+    Extension(user): Extension<UserData>,
+) -> Result<ErasedJson, AppError> {
+    let user_id = user.lock().unwrap().0.id;
+    let blocked = db.get_blocked_users(user_id).await?;
+
+    let blocked = blocked
+        .iter()
+        .map(|v| {
+            serde_json::json!({
+                "username": v.username,
+                "display_name": v.display_name,
+                "kind": v.kind,
+                "created_at": v.created_at.to_string(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Ok(json!({
+        "blocked": blocked,
+    }))
+}
+
+#[derive(serde::Deserialize)]
+pub struct BlockRequest {
+    username: String,
+    kind: Option<BlockKind>, // `block` by default
+}
+
+pub async fn block_user(
+    State(db): State<Arc<Db>>,
+    Extension(user): Extension<UserData>,
+    Json(body): Json<BlockRequest>,
+) -> Result<ErasedJson, AppError> {
+    let user_id = user.lock().unwrap().0.id;
+    let other = db.get_user_by_username(&body.username).await?;
+    if other.id == user_id {
+        return Err(AppError::BadReq("You can't block yourself"));
+    }
+    let kind = body.kind.unwrap_or(BlockKind::Block);
+    db.block_user(user_id, other.id, kind).await?;
+
+    let message = match kind {
+        BlockKind::Block => format!("@{} has been blocked", other.username),
+        BlockKind::Mute => format!("@{} has been muted", other.username),
+    };
+    Ok(json!({
+        "username": other.username,
+        "kind": kind,
+        "message": message
+    }))
+}
+
+#[derive(serde::Deserialize)]
+pub struct UnblockRequest {
+    username: String,
+}
+
+/// removes a block or a mute
+pub async fn unblock_user(
+    State(db): State<Arc<Db>>,
+    Extension(user): Extension<UserData>,
+    Json(body): Json<UnblockRequest>,
+) -> Result<ErasedJson, AppError> {
+    let user_id = user.lock().unwrap().0.id;
+    let other = db.get_user_by_username(&body.username).await?;
+    db.unblock_user(user_id, other.id).await?;
+    Ok(json!({
+        "message": format!("@{} is no longer blocked", other.username)
+    }))
+}
@@ -1,4 +1,4 @@
-use crate::ClientSocket;
+use crate::{ClientSocket, middleware::RecentAuth};
 use axum::{
     Extension, Json,
     extract::{ConnectInfo, State},
@@ -7,26 +7,23 @@ use axum_extra::{json, response::ErasedJson};
 use database::{Db, UserData};
 use serde::Deserialize;
 use std::sync::Arc;
-use util::{AppError, oauth::OAuthProvider};
+use util::{AppError, mail::Template, oauth::OAuthProvider, session::ParsedSession};
 
 #[derive(Deserialize)]
 pub struct UpdateEmailRequest {
     new_email: String,
-    password: String,
 }
 
 pub async fn update_email(
     State(db): State<Arc<Db>>,
     ConnectInfo(conn_info): ConnectInfo<ClientSocket>,
     Extension(user): Extension<UserData>,
+    _: RecentAuth,
     Json(body): Json<UpdateEmailRequest>,
 ) -> Result<ErasedJson, AppError> {
-    let email = {
+    let (email, locale) = {
         let guard = user.lock().unwrap();
-        if guard.0.password.as_ref().is_none_or(|v| v != &body.password) {
-            return Err(AppError::PasswordMismatch);
-        }
-        guard.0.email.clone()
+        (guard.0.email.clone(), guard.0.locale.clone())
     };
     // checking whether the new email is same as original email or not
     if email == body.new_email {
@@ -41,12 +38,9 @@ pub async fn update_email(
     db.request_email_update(*conn_info, email, body.new_email.clone(), otp.clone()).await?;
 
     // sending mail to the new email for verification
-    util::mail::send(
-        body.new_email,
-        format!("{otp} is your {} verification code", &*shared::SERVICE_NAME),
-        format!("Confirm your email address\n {otp}\n Thanks,\n {}", &*shared::SERVICE_NAME),
-    )
-    .await?;
+    let key = format!("otp:{}:{}", body.new_email, util::generate::random_string(16));
+    db.enqueue_email(key, body.new_email, Template::Otp, locale.as_deref(), &[("otp", &otp)])
+        .await?;
 
     Ok(json!({
         "message": "Please verify your email",
@@ -57,19 +51,38 @@ pub async fn update_email(
 pub struct VerifyEmailRequest {
     new_email: String,
     otp: String,
+    #[serde(default)]
+    keep_other_sessions: bool,
+    return_to: Option<String>,
 }
 
 pub async fn verify_email(
     State(db): State<Arc<Db>>,
+    Extension(parsed_session): Extension<ParsedSession>,
     Extension(user): Extension<UserData>,
     Json(body): Json<VerifyEmailRequest>,
 ) -> Result<ErasedJson, AppError> {
-    let old_email = user.lock().unwrap().0.email.clone();
+    let (old_email, locale) = {
+        let guard = user.lock().unwrap();
+        (guard.0.email.clone(), guard.0.locale.clone())
+    };
     db.update_email(&old_email, body.new_email.clone(), &body.otp).await?;
     user.lock().unwrap().0.email = body.new_email.clone();
+    super::end_other_sessions(&db, &parsed_session, &user, body.keep_other_sessions).await?;
+
+    // notifying the old email about the change
+    db.enqueue_email(
+        format!("email_changed:{old_email}:{}:{}", body.new_email, body.otp),
+        old_email.clone(),
+        Template::EmailChanged,
+        locale.as_deref(),
+        &[("old_email", &old_email), ("new_email", &body.new_email)],
+    )
+    .await?;
     Ok(json!({
         "email": body.new_email,
         "message": "Your email has been verified",
+        "return_to": crate::cors::return_to(db.config(), body.return_to.as_deref()),
     }))
 }
 
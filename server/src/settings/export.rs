@@ -0,0 +1,262 @@
+use axum::{
+    Extension,
+    body::Bytes,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode, header},
+    response::IntoResponse,
+};
+use axum_extra::{json, response::ErasedJson};
+use database::{Db, UserData, users::User};
+use std::{io::Write, sync::Arc};
+use util::{AppError, mail::Template};
+use zip::{ZipWriter, write::SimpleFileOptions};
+
+/// starts assembling a copy of everything stored about the user, mailed when it's ready
+pub async fn request_export(
+    State(db): State<Arc<Db>>,
+    Extension(user): Extension<UserData>,
+    headers: HeaderMap,
+) -> Result<(StatusCode, ErasedJson), AppError> {
+    let user = user.lock().unwrap().0.clone();
+    let Some(id) = db.start_export(user.id).await? else {
+        return Err(AppError::BadReq("Your data export is already being prepared"));
+    };
+    let locale = user.locale.clone().or_else(|| util::mail::locale_from_headers(&headers));
+
+    // fetching the media can take a while, so the archive is built in the background
+    tokio::spawn(async move {
+        let finished = match build_archive(&db, &user).await {
+            Ok(archive) => db.finish_export(user.id, &id, archive).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = finished {
+            tracing::error!("Failed to export the data of @{}: {e:?}", user.username);
+            let _ = db.fail_export(user.id, &id).await;
+            let _ = db
+                .enqueue_email(
+                    format!("data_export_failed:{}:{id}", user.id),
+                    user.email,
+                    Template::DataExportFailed,
+                    locale.as_deref(),
+                    &[],
+                )
+                .await;
+            return;
+        }
+        let link = format!("{}/api/settings/export/{id}", db.config().service.domain);
+        let _ = db
+            .enqueue_email(
+                format!("data_export:{}:{id}", user.id),
+                user.email,
+                Template::DataExport,
+                locale.as_deref(),
+                &[("link", &link)],
+            )
+            .await;
+    });
+
+    Ok((
+        StatusCode::ACCEPTED,
+        json!({
+            "message": "Your data is being prepared, you'll get an email when it's ready"
+        }),
+    ))
+}
+
+/// downloads a finished export, only by the user it belongs to
+pub async fn download_export(
+    State(db): State<Arc<Db>>,
+    Extension(user): Extension<UserData>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let (user_id, username) = {
+        let guard = user.lock().unwrap();
+        (guard.0.id, guard.0.username.clone())
+    };
+    let archive = db.get_export(user_id, &id).await?;
+    let disposition = format!("attachment; filename=\"{username}-data.zip\"");
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/zip".to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        archive,
+    ))
+}
+
+// `account.json`, `identities.json`, `usernames.json`, `sessions.json`, `sign_ins.json`,
+// `reports.json`, `events.json`, `emails.json`, `blocked.json` and the uploaded media, with
+// `missing_media.json` listing the media that couldn't be fetched
+async fn build_archive(db: &Arc<Db>, user: &User) -> Result<Bytes, AppError> {
+    let identities = db.get_oauth_identities(user.id).await?;
+    let usernames = db.get_username_history(user.id).await?;
+    let sessions = db.get_all_sessions(user.id).await?;
+    let sign_ins = db.get_session_origins(user.id).await?;
+    let reports = db.get_sign_in_reports(user.id).await?;
+    let events = db.get_events(user.id).await?;
+    let emails = db.get_emails_to(&user.email).await?;
+    let blocked = db.get_blocked_users(user.id).await?;
+
+    let account = serde_json::json!({
+        "id": user.id.to_string(),
+        "email": &user.email,
+        "username": &user.username,
+        "display_name": &user.display_name,
+        "has_password": user.password.is_some(),
+        "birth_date": user.birth_date.map(|v| v.to_string()),
+        "icon": &user.icon,
+        "banner": &user.banner,
+        "bio": &user.bio,
+        "legal_name": &user.legal_name,
+        "gender": &user.gender,
+        "phone": &user.phone,
+        "country": &user.country,
+        "locale": &user.locale,
+        "oauth_provider": user.oauth_provider.get_str(),
+        "role": user.role(),
+        "created": user.created.to_string(),
+    });
+    let identities = identities
+        .iter()
+        .map(|v| {
+            serde_json::json!({
+                "provider": &v.provider,
+                "subject": &v.subject,
+                "email": &v.email,
+                "linked_at": v.linked_at.to_string(),
+                "last_used": v.last_used.to_string(),
+            })
+        })
+        .collect::<Vec<_>>();
+    let usernames = usernames
+        .iter()
+        .map(|(username, released_at)| {
+            serde_json::json!({ "username": username, "released_at": released_at.to_string() })
+        })
+        .collect::<Vec<_>>();
+    let sessions = sessions
+        .iter()
+        .map(|s| {
+            serde_json::json!({
+                "unsigned_ssid": s.unsigned_ssid.to_string(),
+                "user_agent": &s.user_agent,
+                "ip_address": s.ip_address.to_string(),
+                "location": util::geoip::locate(s.ip_address),
+                "created_at": s.created_at.to_string(),
+                "last_used": s.last_used.to_string(),
+                "expires_at": s.expires_at.to_string(),
+                "login_at": s.login_at.to_string(),
+                "authenticated_at": s.authenticated_at.to_string(),
+                "persistent": s.persistent,
+            })
+        })
+        .collect::<Vec<_>>();
+    let sign_ins = sign_ins
+        .iter()
+        .map(|(user_agent, ip_address)| {
+            serde_json::json!({
+                "user_agent": user_agent,
+                "ip_address": ip_address.to_string(),
+                "location": util::geoip::locate(*ip_address),
+            })
+        })
+        .collect::<Vec<_>>();
+    let reports = reports
+        .iter()
+        .map(|(unsigned_ssid, expires_at)| {
+            serde_json::json!({
+                "unsigned_ssid": unsigned_ssid.to_string(),
+                "expires_at": expires_at.to_string(),
+            })
+        })
+        .collect::<Vec<_>>();
+    let events = events
+        .iter()
+        .map(|v| {
+            serde_json::json!({
+                "kind": &v.kind,
+                "user_agent": &v.user_agent,
+                "ip_address": v.ip_address.map(|v| v.to_string()),
+                "created_at": v.created_at.to_string(),
+            })
+        })
+        .collect::<Vec<_>>();
+    let emails = emails
+        .iter()
+        .map(|e| {
+            serde_json::json!({
+                "subject": &e.subject,
+                "status": &e.status,
+                "created_at": e.created_at.to_string(),
+                "sent_at": e.sent_at.map(|v| v.to_string()),
+            })
+        })
+        .collect::<Vec<_>>();
+    let blocked = blocked
+        .iter()
+        .map(|v| {
+            serde_json::json!({
+                "username": &v.username,
+                "kind": v.kind,
+                "created_at": v.created_at.to_string(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut files = vec![
+        ("account.json".to_string(), to_pretty(&account)),
+        ("identities.json".to_string(), to_pretty(&identities)),
+        ("usernames.json".to_string(), to_pretty(&usernames)),
+        ("sessions.json".to_string(), to_pretty(&sessions)),
+        ("sign_ins.json".to_string(), to_pretty(&sign_ins)),
+        ("reports.json".to_string(), to_pretty(&reports)),
+        ("events.json".to_string(), to_pretty(&events)),
+        ("emails.json".to_string(), to_pretty(&emails)),
+        ("blocked.json".to_string(), to_pretty(&blocked)),
+    ];
+    // media the bucket can't serve is listed in `missing_media.json` rather than failing the export
+    let mut missing = Vec::new();
+    for (kind, url) in [("icon", &user.icon), ("banner", &user.banner)] {
+        let Some(url) = url else { continue };
+        let name = url.rsplit('/').next().unwrap_or(kind);
+        match fetch(url).await {
+            Ok(data) => files.push((format!("media/{kind}-{name}"), data)),
+            Err(_) => missing.push(serde_json::json!({ "kind": kind, "url": url })),
+        }
+    }
+    if !missing.is_empty() {
+        files.push(("missing_media.json".to_string(), to_pretty(&missing)));
+    }
+
+    let archive = write_zip(files).map_err(|e| {
+        tracing::error!("{:?}", e);
+        AppError::ServerError
+    })?;
+    Ok(Bytes::from(archive))
+}
+
+fn write_zip(files: Vec<(String, Vec<u8>)>) -> zip::result::ZipResult<Vec<u8>> {
+    let mut zip = ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    for (name, data) in files {
+        zip.start_file(name, SimpleFileOptions::default())?;
+        zip.write_all(&data)?;
+    }
+    Ok(zip.finish()?.into_inner())
+}
+
+fn to_pretty<T: serde::Serialize>(value: &T) -> Vec<u8> {
+    serde_json::to_vec_pretty(value).unwrap_or_default()
+}
+
+// uploaded media is served from the public bucket url
+async fn fetch(url: &str) -> Result<Vec<u8>, AppError> {
+    let res = reqwest::get(url).await.and_then(|r| r.error_for_status()).map_err(|e| {
+        tracing::error!("Failed to fetch {url}: {e:?}");
+        AppError::ServerError
+    })?;
+    let bytes = res.bytes().await.map_err(|e| {
+        tracing::error!("Failed to read {url}: {e:?}");
+        AppError::ServerError
+    })?;
+    Ok(bytes.to_vec())
+}
@@ -93,3 +93,26 @@ pub async fn update_country(
         "message": "Your country has been updated"
     }))
 }
+
+#[derive(serde::Deserialize)]
+pub struct UpdateLocaleRequest {
+    locale: String,
+}
+
+pub async fn update_locale(
+    State(db): State<Arc<Db>>,
+    Extension(user): Extension<UserData>,
+    Json(body): Json<UpdateLocaleRequest>,
+) -> Result<ErasedJson, AppError> {
+    let locale = body.locale.trim().to_lowercase();
+    if !util::mail::is_locale_supported(&locale) {
+        return Err(AppError::BadReq("Unsupported locale"));
+    }
+    let username = user.lock().unwrap().0.username.clone();
+    db.update_locale(&username, &locale).await?;
+    user.lock().unwrap().0.locale = Some(locale.clone());
+    Ok(json!({
+        "locale": locale,
+        "message": "Your language has been updated"
+    }))
+}
@@ -1,14 +1,20 @@
 use axum::routing::{get, post};
+use database::Db;
+use std::sync::Arc;
 
 mod account;
+mod blocked;
 mod email;
+mod export;
 mod metadata;
 mod password;
 mod phone;
+mod reauth;
 mod username;
+mod visibility;
 
 #[rustfmt::skip]
-pub async fn settings_routes() -> axum::Router {
+pub fn settings_routes(db: Arc<Db>) -> axum::Router {
     axum::Router::new()
         .route("/api/settings", get(fetch_settings))
         .route("/api/settings/email", post(email::update_email))
@@ -23,10 +29,20 @@ pub async fn settings_routes() -> axum::Router {
         .route("/api/settings/phone", post(phone::update_phone))
         .route("/api/settings/verify_phone", post(phone::verify_phone))
         .route("/api/settings/country", post(metadata::update_country))
+        .route("/api/settings/locale", post(metadata::update_locale))
+        .route("/api/settings/profile_visibility", get(visibility::get_visibility).post(visibility::update_visibility))
+        .route("/api/settings/blocked", get(blocked::list_blocked).post(blocked::block_user))
+        .route("/api/settings/blocked/remove", post(blocked::unblock_user))
         .route("/api/settings/delete_account", post(account::delete_account))
-        .layer(axum::middleware::from_fn(crate::middleware::auth_middleware))
+        .route("/api/settings/export", post(export::request_export))
+        .route("/api/settings/export/{id}", get(export::download_export))
+        .route("/api/settings/reauth", post(reauth::reauthenticate))
+        .route("/api/settings/reauth/otp", post(reauth::request_otp))
+        .layer(axum::middleware::from_fn_with_state(db.clone(), crate::middleware::csrf_middleware))
+        .layer(axum::middleware::from_fn_with_state(db.clone(), crate::middleware::auth_middleware))
         .route("/api/settings/is_username_available", get(username::validate_username))
-        .with_state(database::Db::new().await)
+        .route("/api/settings/delete_account/cancel", post(account::cancel_deletion))
+        .with_state(db)
 }
 
 pub async fn fetch_settings(
@@ -36,3 +52,19 @@ pub async fn fetch_settings(
 
     crate::user_data::arrange(&guard.0, &guard.1)
 }
+
+// ends every other session of the user after a credential change, unless `keep` is set
+async fn end_other_sessions(
+    db: &std::sync::Arc<database::Db>,
+    parsed_session: &util::session::ParsedSession,
+    user: &database::UserData,
+    keep: bool,
+) -> Result<(), util::AppError> {
+    if keep {
+        return Ok(());
+    }
+    let user_id = user.lock().unwrap().0.id;
+    db.remove_all_sessions(user_id, parsed_session.unsigned_ssid).await?;
+    user.lock().unwrap().1.retain(|v| v.unsigned_ssid == parsed_session.unsigned_ssid);
+    Ok(())
+}
@@ -2,16 +2,19 @@ use axum::{Extension, Json, extract::State};
 use axum_extra::{json, response::ErasedJson};
 use database::{Db, UserData};
 use std::sync::Arc;
-use util::AppError;
+use util::{AppError, session::ParsedSession};
 
 #[derive(serde::Deserialize)]
 pub struct UpdatePasswordRequest {
     old_password: String,
     new_password: String,
+    #[serde(default)]
+    keep_other_sessions: bool,
 }
 
 pub async fn update_password(
     State(db): State<Arc<Db>>,
+    Extension(parsed_session): Extension<ParsedSession>,
     Extension(user): Extension<UserData>,
     Json(body): Json<UpdatePasswordRequest>,
 ) -> Result<ErasedJson, AppError> {
@@ -25,6 +28,7 @@ pub async fn update_password(
     shared::validation::is_password_strong(&body.new_password)?;
     db.update_password(&email, &body.new_password).await?;
     user.lock().unwrap().0.password = Some(body.new_password);
+    super::end_other_sessions(&db, &parsed_session, &user, body.keep_other_sessions).await?;
     Ok(json!({
         "message": "Your password has been changed"
     }))
@@ -1,6 +1,5 @@
-#![allow(dead_code, unused_variables)]
-
 use axum::{Extension, Json, extract::State};
+use axum_extra::{json, response::ErasedJson};
 use database::{Db, UserData};
 use std::sync::Arc;
 use util::AppError;
@@ -14,8 +13,27 @@ pub async fn update_phone(
     State(db): State<Arc<Db>>,
     Extension(user): Extension<UserData>,
     Json(body): Json<UpdatePhoneRequest>,
-) -> Result<String, AppError> {
-    todo!()
+) -> Result<ErasedJson, AppError> {
+    let phone = shared::validation::is_phone_valid(&body.phone)?;
+    let (user_id, old_phone) = {
+        let guard = user.lock().unwrap();
+        (guard.0.id, guard.0.phone.clone())
+    };
+    if old_phone.as_ref() == Some(&phone) {
+        return Err(AppError::BadReq("Your new phone cannot be same as of your original phone"));
+    }
+
+    // adding an entry to database for further checking, limited per user as every code is a text
+    let otp = db.request_phone_update(user_id, phone.clone())?;
+
+    // sending sms to the new phone for verification
+    let sms = format!("{otp} is your {} verification code", db.config().service.name);
+    util::sms::send(&phone, &sms).await?;
+
+    Ok(json!({
+        "phone": phone,
+        "message": "Please verify your phone",
+    }))
 }
 
 #[derive(serde::Deserialize)]
@@ -27,6 +45,13 @@ pub async fn verify_phone(
     State(db): State<Arc<Db>>,
     Extension(user): Extension<UserData>,
     Json(body): Json<VerifyPhoneRequest>,
-) -> Result<String, AppError> {
-    todo!()
+) -> Result<ErasedJson, AppError> {
+    let user_id = user.lock().unwrap().0.id;
+    let phone = db.update_phone(user_id, &body.otp).await?;
+    user.lock().unwrap().0.phone = Some(phone.clone());
+
+    Ok(json!({
+        "phone": phone,
+        "message": "Your phone has been verified",
+    }))
 }
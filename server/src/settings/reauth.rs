@@ -0,0 +1,68 @@
+use axum::{Extension, Json, extract::State, http::HeaderMap};
+use axum_extra::{json, response::ErasedJson};
+use database::{Db, UserData};
+use std::sync::Arc;
+use time::format_description::well_known::Rfc3339;
+use util::{
+    AppError,
+    mail::Template,
+    session::{ParsedSession, Session},
+};
+
+#[derive(serde::Deserialize)]
+pub struct ReauthRequest {
+    password: Option<String>,
+    otp: Option<String>,
+}
+
+/// confirms the identity of the signed in user with the password or an emailed code, accounts
+/// signed up with an OpenID provider can also use `/api/oauth2/reauth`
+pub async fn reauthenticate(
+    State(db): State<Arc<Db>>,
+    Extension(parsed_session): Extension<ParsedSession>,
+    Extension(user): Extension<UserData>,
+    Json(body): Json<ReauthRequest>,
+) -> Result<ErasedJson, AppError> {
+    let password = user.lock().unwrap().0.password.clone();
+    match (body.password, body.otp) {
+        (Some(v), None) => {
+            db.verify_reauth_password(parsed_session.user_id, password.as_deref(), &v)?
+        }
+        (None, Some(otp)) => db.verify_reauth_otp(parsed_session.user_id, otp.trim())?,
+        _ => return Err(AppError::BadReq("Either password or otp is required")),
+    }
+
+    let authenticated_at =
+        db.reauthenticate_session(parsed_session.user_id, parsed_session.unsigned_ssid).await?;
+    let valid_until = authenticated_at + time::Duration::seconds(Session::REAUTH_WINDOW as i64);
+    Ok(json!({
+        "message": "Thanks for confirming it's you",
+        "valid_until": valid_until.format(&Rfc3339).unwrap_or_default(),
+    }))
+}
+
+/// emails a code to re-authenticate with
+pub async fn request_otp(
+    State(db): State<Arc<Db>>,
+    Extension(user): Extension<UserData>,
+    headers: HeaderMap,
+) -> Result<ErasedJson, AppError> {
+    let (user_id, email, locale) = {
+        let guard = user.lock().unwrap();
+        (guard.0.id, guard.0.email.clone(), guard.0.locale.clone())
+    };
+    let otp = db.create_reauth_otp(user_id)?;
+    let locale = locale.or_else(|| util::mail::locale_from_headers(&headers));
+    db.enqueue_email(
+        format!("reauth_code:{user_id}:{}", util::generate::random_string(16)),
+        email,
+        Template::ReauthCode,
+        locale.as_deref(),
+        &[("otp", &otp)],
+    )
+    .await?;
+
+    Ok(json!({
+        "message": "A verification code has been sent to your email"
+    }))
+}
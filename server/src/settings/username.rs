@@ -1,3 +1,4 @@
+use crate::middleware::RecentAuth;
 use axum::{Extension, Json, extract::State};
 use axum_extra::{json, response::ErasedJson};
 use database::{Db, UserData};
@@ -7,20 +8,17 @@ use util::AppError;
 #[derive(serde::Deserialize)]
 pub struct UpdateUsernameRequest {
     new_username: String,
-    password: String,
 }
 
 pub async fn update_username(
     State(db): State<Arc<Db>>,
     Extension(user): Extension<UserData>,
+    _: RecentAuth,
     Json(body): Json<UpdateUsernameRequest>,
 ) -> Result<ErasedJson, AppError> {
-    let username = {
+    let (user_id, username) = {
         let guard = user.lock().unwrap();
-        if guard.0.password.as_ref().is_none_or(|v| v != &body.password) {
-            return Err(AppError::PasswordMismatch);
-        }
-        guard.0.username.clone()
+        (guard.0.id, guard.0.username.clone())
     };
     // checking if the new username is valid or not
     shared::validation::is_username_valid(&body.new_username)?;
@@ -31,7 +29,7 @@ pub async fn update_username(
         ));
     }
     // updating username in the primary database
-    db.check_and_update_username(&username, &body.new_username).await?;
+    db.check_and_update_username(user_id, &username, &body.new_username).await?;
     user.lock().unwrap().0.username = body.new_username.clone();
     Ok(json!({
         "username": body.new_username,
@@ -52,12 +50,15 @@ pub async fn validate_username(
     // checking if the new username is valid or not
     shared::validation::is_username_valid(&body.username)?;
     // checking whether the new username is same as original username or not
-    let username = user.lock().unwrap().0.username.clone();
+    let (user_id, username) = {
+        let guard = user.lock().unwrap();
+        (guard.0.id, guard.0.username.clone())
+    };
     if username == body.username {
         return Err(AppError::BadReq(
             "Your new username cannot be same as of your original username",
         ));
     }
-    db.is_username_available(&username).await?;
+    db.is_username_available_to(&body.username, Some(user_id)).await?;
     Ok(json!({ "available": true }))
 }
@@ -0,0 +1,43 @@
+use axum::{Extension, Json, extract::State};
+use axum_extra::{json, response::ErasedJson};
+use database::{Db, UserData, users::Visibility};
+use std::sync::Arc;
+use util::AppError;
+
+pub async fn get_visibility(
+    State(db): State<Arc<Db>>,
+    Extension(user): Extension<UserData>,
+) -> Result<ErasedJson, AppError> {
+    let user_id = user.lock().unwrap().0.id;
+    let visibility = db.get_profile_visibility(user_id).await?;
+    Ok(json!({ "visibility": visibility }))
+}
+
+// fields left out keep their visibility
+#[derive(serde::Deserialize)]
+pub struct UpdateVisibilityRequest {
+    icon: Option<Visibility>,
+    banner: Option<Visibility>,
+    country: Option<Visibility>,
+    birth_date: Option<Visibility>,
+    gender: Option<Visibility>,
+}
+
+pub async fn update_visibility(
+    State(db): State<Arc<Db>>,
+    Extension(user): Extension<UserData>,
+    Json(body): Json<UpdateVisibilityRequest>,
+) -> Result<ErasedJson, AppError> {
+    let user_id = user.lock().unwrap().0.id;
+    let mut visibility = db.get_profile_visibility(user_id).await?;
+    visibility.icon = body.icon.unwrap_or(visibility.icon);
+    visibility.banner = body.banner.unwrap_or(visibility.banner);
+    visibility.country = body.country.unwrap_or(visibility.country);
+    visibility.birth_date = body.birth_date.unwrap_or(visibility.birth_date);
+    visibility.gender = body.gender.unwrap_or(visibility.gender);
+    db.update_profile_visibility(user_id, &visibility).await?;
+    Ok(json!({
+        "visibility": visibility,
+        "message": "Your profile visibility has been updated"
+    }))
+}
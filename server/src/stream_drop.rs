@@ -1,4 +1,7 @@
-pub struct CustomStream(tokio::net::TcpStream);
+use database::Db;
+use std::sync::Arc;
+
+pub struct CustomStream(tokio::net::TcpStream, Arc<Db>);
 
 impl tokio::io::AsyncRead for CustomStream {
     fn poll_read(
@@ -41,14 +44,11 @@ impl Drop for CustomStream {
             Err(_) => return, // might cause problem
         };
 
-        tokio::spawn(async move {
-            let db = database::Db::new().await;
-            db.drop_application(&socket_addr);
-        });
+        self.1.drop_application(&socket_addr);
     }
 }
 
-pub struct CustomListener(tokio::net::TcpListener);
+pub struct CustomListener(tokio::net::TcpListener, Arc<Db>);
 
 impl axum::serve::Listener for CustomListener {
     type Io = CustomStream;
@@ -56,7 +56,7 @@ impl axum::serve::Listener for CustomListener {
 
     async fn accept(&mut self) -> (Self::Io, Self::Addr) {
         let (io, addr) = self.0.accept().await.unwrap();
-        (CustomStream(io), addr)
+        (CustomStream(io, self.1.clone()), addr)
     }
 
     fn local_addr(&self) -> tokio::io::Result<Self::Addr> {
@@ -64,8 +64,9 @@ impl axum::serve::Listener for CustomListener {
     }
 }
 
-impl From<tokio::net::TcpListener> for CustomListener {
-    fn from(value: tokio::net::TcpListener) -> Self {
-        Self(value)
+impl CustomListener {
+    /// the applications of `db` started from a connection are dropped with it
+    pub fn new(listener: tokio::net::TcpListener, db: Arc<Db>) -> Self {
+        Self(listener, db)
     }
 }
@@ -0,0 +1,53 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::header,
+    response::{IntoResponse, Redirect, Response},
+};
+use database::{Db, users::Visibility};
+use std::sync::Arc;
+use util::{AppError, avatar::AvatarSource};
+
+#[derive(serde::Deserialize)]
+pub struct AvatarQuery {
+    // skips the configured source and serves the generated avatar
+    #[serde(default)]
+    generated: bool,
+}
+
+pub async fn get_avatar(
+    State(db): State<Arc<Db>>,
+    Path(p): Path<String>,
+    Query(q): Query<AvatarQuery>,
+) -> Result<Response, AppError> {
+    let u = match db.get_user_by_username(&p).await {
+        Err(AppError::UserNotFound) => {
+            let u = db.get_user_by_previous_username(&p).await?;
+            let query = if q.generated { "?generated=true" } else { "" };
+            let location = format!("/api/user/@{}/avatar{query}", u.username);
+            return Ok(Redirect::temporary(&location).into_response());
+        }
+        v => v?,
+    };
+
+    // redirecting to the uploaded icon if the user has one and shows it to everyone, the avatar
+    // is cached publicly
+    let visibility = db.get_profile_visibility(u.id).await?;
+    if let Some(icon) = u.icon.filter(|_| visibility.icon == Visibility::Public) {
+        return Ok(([(header::CACHE_CONTROL, "public, max-age=3600")], Redirect::temporary(&icon))
+            .into_response());
+    }
+
+    if !q.generated && util::avatar::source() == AvatarSource::Gravatar {
+        // gravatar falls back to the generated avatar if the email has no gravatar
+        let default = format!("{}?generated=true", util::avatar::fallback_url(&u.username));
+        let url = util::avatar::gravatar_url(&u.email, &default);
+        return Ok(([(header::CACHE_CONTROL, "public, max-age=3600")], Redirect::temporary(&url))
+            .into_response());
+    }
+
+    Ok((
+        [(header::CONTENT_TYPE, "image/svg+xml"), (header::CACHE_CONTROL, "public, max-age=86400")],
+        util::avatar::generate_svg(&u.id, &u.display_name),
+    )
+        .into_response())
+}
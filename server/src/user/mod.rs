@@ -1,12 +1,21 @@
 use axum::routing::{get, post};
+use database::Db;
+use std::sync::Arc;
 
+mod avatar;
 mod profile;
+mod search;
+
+pub use profile::icon_or_fallback;
 
 #[rustfmt::skip]
-pub async fn user_routes() -> axum::Router {
+pub fn user_routes(db: Arc<Db>) -> axum::Router {
     axum::Router::new()
-        .route("/api/user/@{id}", get(profile::get_user_profile))
         .route("/api/user/profile", post(profile::update_profile))
-        .layer(axum::middleware::from_fn(crate::middleware::auth_middleware))
-        .with_state(database::Db::new().await)
+        .route("/api/user/search", get(search::search_users))
+        .layer(axum::middleware::from_fn_with_state(db.clone(), crate::middleware::csrf_middleware))
+        .layer(axum::middleware::from_fn_with_state(db.clone(), crate::middleware::auth_middleware))
+        .route("/api/user/@{id}", get(profile::get_user_profile).layer(axum::middleware::from_fn_with_state(db.clone(), crate::middleware::optional_auth_middleware)))
+        .route("/api/user/@{id}/avatar", get(avatar::get_avatar))
+        .with_state(db)
 }
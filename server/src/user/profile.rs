@@ -1,51 +1,80 @@
 use axum::{
     Extension,
     extract::{Multipart, Path, State},
+    response::{IntoResponse, Redirect, Response},
 };
 use axum_extra::{json, response::ErasedJson};
-use database::{Db, users::User};
+use database::{
+    Db, UserData,
+    users::{BlockKind, User, Visibility},
+};
 use shared::validation::ValidationError;
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
 use util::AppError;
 
+/// the profile of a user, the optional fields only when their visibility allows the viewer
 pub async fn get_user_profile(
     State(db): State<Arc<Db>>,
-    Extension(user): Extension<Arc<Mutex<User>>>,
+    viewer: Option<Extension<UserData>>,
     Path(p): Path<String>,
-) -> Result<ErasedJson, AppError> {
-    let res = {
-        let guard = user.lock().unwrap();
-        if guard.username == p {
-            Some(json!({
-                "username": guard.username.clone(),
-                "display_name": guard.display_name.clone(),
-                "bio": guard.bio.clone(),
-            }))
-        } else {
-            None
-        }
+) -> Result<Response, AppError> {
+    let viewer = viewer.map(|Extension(v)| v.lock().unwrap().0.clone());
+    let u = match viewer.as_ref().filter(|v| v.username == p) {
+        Some(v) => v.clone(),
+        None => match db.get_user_by_username(&p).await {
+            // links to a previous username keep working after a rename
+            Err(AppError::UserNotFound) => {
+                let u = db.get_user_by_previous_username(&p).await?;
+                let location = format!("/api/user/@{}", u.username);
+                return Ok(Redirect::temporary(&location).into_response());
+            }
+            v => v?,
+        },
     };
+    let viewer = viewer.map(|v| v.id);
+    // blocked users can't tell the profile apart from a missing one
+    if let Some(viewer) = viewer.filter(|&v| v != u.id)
+        && db.get_block_status(u.id, viewer).await?.blocking == Some(BlockKind::Block)
+    {
+        return Err(AppError::UserNotFound);
+    }
+    let visibility = db.get_profile_visibility(u.id).await?;
+    let shows = |v: Visibility| v.allows(u.id, viewer);
 
-    if let Some(res) = res {
-        Ok(res)
-    } else {
-        let u = db.get_user_by_username(&p).await?;
-        Ok(json!({
-            "username": u.username,
-            "display_name": u.display_name,
-            "bio": u.bio,
-        }))
+    // a hidden icon is replaced with the generated avatar, which the avatar route serves then
+    let icon = match shows(visibility.icon) {
+        true => icon_or_fallback(&u),
+        false => util::avatar::fallback_url(&u.username),
+    };
+    let mut res = serde_json::json!({
+        "username": u.username,
+        "display_name": u.display_name,
+        "bio": u.bio,
+        "icon": icon,
+        "banner": u.banner.filter(|_| shows(visibility.banner)),
+        "country": u.country.filter(|_| shows(visibility.country)),
+        "birth_date": u.birth_date.filter(|_| shows(visibility.birth_date)).map(|v| v.date().to_string()),
+        "gender": u.gender.filter(|_| shows(visibility.gender)),
+    });
+    if viewer == Some(u.id) {
+        res["visibility"] = serde_json::json!(visibility);
     }
+    Ok(ErasedJson::new(res).into_response())
+}
+
+/// returns the uploaded icon of the user or the url of its generated avatar
+pub fn icon_or_fallback(u: &User) -> String {
+    u.icon.clone().unwrap_or_else(|| util::avatar::fallback_url(&u.username))
 }
 
 pub async fn update_profile(
     State(db): State<Arc<Db>>,
-    Extension(user): Extension<Arc<Mutex<User>>>,
+    Extension(user): Extension<UserData>,
     mut multipart: Multipart,
 ) -> Result<ErasedJson, AppError> {
     let (username, _id) = {
         let guard = user.lock().unwrap();
-        (guard.username.clone(), guard.id.clone().to_string())
+        (guard.0.username.clone(), guard.0.id.to_string())
     };
 
     let (mut banner, mut icon, mut display_name, mut bio) = (None, None, None, None);
@@ -115,18 +144,18 @@ pub async fn update_profile(
     let res = {
         let mut guard = user.lock().unwrap();
         if icon.is_some() {
-            guard.icon = icon;
+            guard.0.icon = icon;
         }
         if let Some(display_name) = display_name {
-            guard.display_name = display_name;
+            guard.0.display_name = display_name;
         }
         if bio.is_some() {
-            guard.bio = bio;
+            guard.0.bio = bio;
         }
         json!({
-            "icon": guard.icon.clone(),
-            "display_name": guard.display_name.clone(),
-            "bio": guard.bio.clone(),
+            "icon": icon_or_fallback(&guard.0),
+            "display_name": guard.0.display_name.clone(),
+            "bio": guard.0.bio.clone(),
         })
     };
 
@@ -0,0 +1,67 @@
+use axum::{
+    Extension,
+    extract::{Query, State},
+};
+use axum_extra::{json, response::ErasedJson};
+use database::{
+    Db, UserData,
+    users::{SearchCursor, SearchFilters, Visibility},
+};
+use std::sync::Arc;
+use util::AppError;
+
+#[derive(serde::Deserialize)]
+pub struct SearchQuery {
+    #[serde(default)]
+    q: String,
+    cursor: Option<String>,
+    limit: Option<i64>,
+}
+
+/// finds users by username or display name, without blocked and muted ones, the icon and country
+/// only when the user shows them
+pub async fn search_users(
+    State(db): State<Arc<Db>>,
+    Extension(user): Extension<UserData>,
+    Query(q): Query<SearchQuery>,
+) -> Result<ErasedJson, AppError> {
+    let viewer = user.lock().unwrap().0.id;
+    // listing everyone is left to admins
+    if q.q.trim().chars().count() < 2 {
+        return Err(AppError::BadReq("Search for at least 2 characters"));
+    }
+    let cursor = q
+        .cursor
+        .as_deref()
+        .map(|v| SearchCursor::decode(v).ok_or(AppError::BadReq("Invalid cursor")))
+        .transpose()?;
+    let limit = q.limit.unwrap_or(20).clamp(1, 50);
+    let hits = db
+        .search_users(Some(viewer), &q.q, &SearchFilters::default(), cursor.as_ref(), limit)
+        .await?;
+
+    let next_cursor = (hits.len() as i64 == limit)
+        .then(|| hits.last().map(|v| SearchCursor::after(v).encode()))
+        .flatten();
+    let users = hits
+        .into_iter()
+        .map(|v| {
+            let shows = |visibility: Visibility| visibility.allows(v.id, Some(viewer));
+            let icon = match (v.icon, shows(v.visibility.icon)) {
+                (Some(icon), true) => icon,
+                _ => util::avatar::fallback_url(&v.username),
+            };
+            serde_json::json!({
+                "username": v.username,
+                "display_name": v.display_name,
+                "icon": icon,
+                "country": v.country.filter(|_| shows(v.visibility.country)),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Ok(json!({
+        "users": users,
+        "next_cursor": next_cursor,
+    }))
+}
@@ -1,4 +1,25 @@
 pub fn arrange<S>(user: &database::users::User, sessions: &[S]) -> axum_extra::response::ErasedJson
+where
+    S: AsRef<util::session::Session>,
+{
+    axum_extra::response::ErasedJson::new(to_value(user, sessions))
+}
+
+/// same as `arrange`, with the page the client should open next
+pub fn arrange_returning<S>(
+    user: &database::users::User,
+    sessions: &[S],
+    return_to: String,
+) -> axum_extra::response::ErasedJson
+where
+    S: AsRef<util::session::Session>,
+{
+    let mut value = to_value(user, sessions);
+    value["return_to"] = serde_json::Value::String(return_to);
+    axum_extra::response::ErasedJson::new(value)
+}
+
+fn to_value<S>(user: &database::users::User, sessions: &[S]) -> serde_json::Value
 where
     S: AsRef<util::session::Session>,
 {
@@ -6,9 +27,15 @@ where
         .iter()
         .map(|session| {
             let session = session.as_ref();
+            let agent = util::user_agent::parse(session.user_agent.as_deref().unwrap_or_default());
             serde_json::json!({
                 "unsigned_ssid": session.unsigned_ssid.to_string(),
                 "user_agent": session.user_agent,
+                "browser": agent.browser,
+                "os": agent.os,
+                "device": agent.device.get_str(),
+                "ip_address": session.ip_address.to_string(),
+                "location": util::geoip::locate(session.ip_address),
                 "created_at": session.created_at.to_string(),
                 "last_used": session.last_used.to_string(),
             })
@@ -17,18 +44,19 @@ where
 
     let birth_date = if let Some(v) = &user.birth_date { v.to_string() } else { "".to_string() };
 
-    axum_extra::json!({
+    serde_json::json!({
         "email": &user.email,
         "birth_date": birth_date,
         "username": &user.username,
         "display_name": &user.display_name,
-        "icon": &user.icon,
+        "icon": crate::user::icon_or_fallback(user),
         "banner": &user.banner,
         "bio": &user.bio,
         "legal_name": &user.legal_name,
         "gender": &user.gender,
         "phone": &user.phone,
         "country": &user.country,
+        "locale": &user.locale,
         "created": user.created.to_string(),
         "sessions": session_list,
     })
@@ -3,3 +3,25 @@ pub use scanner::Scanner;
 
 mod printer;
 pub use printer::Printer;
+
+/// returns the `CSRF` cookie inside the cookies entered by the tester
+#[allow(dead_code)]
+pub fn csrf_token(cookies: &str) -> String {
+    cookies.split(';').find_map(|c| c.trim().strip_prefix("CSRF=")).unwrap_or_default().to_string()
+}
+
+/// confirms the password of the session, routes changing the account or its sessions need it
+#[allow(dead_code)]
+pub fn reauthenticate(
+    client: &reqwest::blocking::Client,
+    socket: &str,
+    cookies: &str,
+    password: &str,
+) -> Result<reqwest::blocking::Response, reqwest::Error> {
+    client
+        .post(format!("{socket}/api/settings/reauth"))
+        .header(reqwest::header::COOKIE, cookies)
+        .header("x-csrf-token", csrf_token(cookies))
+        .json(&serde_json::json!({ "password": password }))
+        .send()
+}
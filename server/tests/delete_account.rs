@@ -21,12 +21,18 @@ fn main() -> Result<(), reqwest::Error> {
     out.write("Enter cookie: ");
     let cookies = token.next_line::<String>();
 
+    out.write("Enter password: ");
+    let password = token.next_line::<String>();
+    let res = common::reauthenticate(&client, SOCKET, &cookies, &password)?;
+    writeln!(out.inner, "{:?}", res.text()?);
+
     loop {
         let endpoint1 = format!("{}/api/settings/delete_account", SOCKET);
         let res1 = client
             .post(&endpoint1)
             .header(header::CONTENT_TYPE, "application/json")
             .header(header::COOKIE, &cookies)
+            .header("x-csrf-token", common::csrf_token(&cookies))
             .send();
         match res1 {
             Ok(v) => {
@@ -0,0 +1,59 @@
+#![allow(unused_must_use)]
+mod common;
+
+use common::{Printer, Scanner};
+use fake::Fake;
+use reqwest::header;
+use std::io::{Read, Write};
+
+// requests a data export twice, the second request has to be refused while the first is being
+// prepared, then downloads the export from the mailed link and lists the files of the archive
+#[test]
+fn main() -> Result<(), reqwest::Error> {
+    const SOCKET: &str = "http://127.0.0.1:8080";
+    let client = reqwest::blocking::Client::builder()
+        .user_agent(fake::faker::internet::en::UserAgent().fake::<String>())
+        .build()
+        .unwrap_or_default();
+
+    // for io
+    let mut token = Scanner::new(std::io::stdin().lock());
+    let mut out = Printer::new();
+
+    out.write("Enter cookie: ");
+    let cookies = token.next_line::<String>();
+
+    for _ in 0..2 {
+        let res = client
+            .post(format!("{SOCKET}/api/settings/export"))
+            .header(header::COOKIE, &cookies)
+            .header("x-csrf-token", common::csrf_token(&cookies))
+            .send()?;
+        writeln!(out.inner, "{} {:?}", res.status(), res.text()?);
+    }
+
+    out.write("Enter the export id from the mailed link: ");
+    let id = token.next_line::<String>();
+    let res = client
+        .get(format!("{SOCKET}/api/settings/export/{id}"))
+        .header(header::COOKIE, &cookies)
+        .send()?;
+    writeln!(out.inner, "{} {:?}", res.status(), res.headers().get(header::CONTENT_DISPOSITION));
+    let archive = res.bytes()?;
+
+    match zip::ZipArchive::new(std::io::Cursor::new(archive)) {
+        Ok(mut zip) => {
+            for i in 0..zip.len() {
+                let mut file = zip.by_index(i).unwrap();
+                let mut data = Vec::new();
+                file.read_to_end(&mut data);
+                writeln!(out.inner, "{} ({} bytes)", file.name(), data.len());
+            }
+        }
+        Err(e) => {
+            writeln!(out.inner, "{e:?}");
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,59 @@
+#![allow(unused_must_use)]
+mod common;
+
+use common::{Printer, Scanner};
+use fake::Fake;
+use reqwest::header;
+use std::io::Write;
+
+// needs `MAGIC_LINK_LOGIN=true` on the server
+#[test]
+fn main() -> Result<(), reqwest::Error> {
+    const SOCKET: &str = "http://127.0.0.1:8080";
+    let client = reqwest::blocking::Client::builder()
+        .user_agent(fake::faker::internet::en::UserAgent().fake::<String>())
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .unwrap_or_default();
+
+    // for io
+    let mut token = Scanner::new(std::io::stdin().lock());
+    let mut out = Printer::new();
+
+    out.write("Enter email: ");
+    let email = token.next_line::<String>();
+
+    let body = format!(r#"{{"email": "{email}"}}"#);
+    let res = client
+        .post(format!("{}/api/login/magic_link", SOCKET))
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(body)
+        .send()?;
+    let device_cookie = res
+        .headers()
+        .get(reqwest::header::SET_COOKIE)
+        .map(|s| {
+            let v = s.to_str().unwrap();
+            v[..v.find(';').unwrap()].to_string()
+        })
+        .unwrap_or_default();
+    writeln!(out.inner, "{:?}", res.text()?);
+
+    out.write("Enter link: ");
+    let link = token.next_line::<String>();
+    let res = client.get(link).header(header::COOKIE, device_cookie).send()?;
+    let cookies = res
+        .headers()
+        .get_all(reqwest::header::SET_COOKIE)
+        .into_iter()
+        .map(|s| {
+            let v = s.to_str().unwrap();
+            v[..v.find(';').unwrap()].to_string()
+        })
+        .collect::<Vec<String>>()
+        .join(";");
+    writeln!(out.inner, "{cookies}");
+    writeln!(out.inner, "{:?}", res.status());
+
+    Ok(())
+}
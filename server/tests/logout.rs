@@ -20,8 +20,11 @@ fn main() -> Result<(), reqwest::Error> {
 
     out.write("Enter cookie: ");
     let cookies = token.next_line::<String>();
-    let res =
-        client.post(format!("{}/api/logout", SOCKET)).header(header::COOKIE, cookies).send()?;
+    let res = client
+        .post(format!("{}/api/logout", SOCKET))
+        .header("x-csrf-token", common::csrf_token(&cookies))
+        .header(header::COOKIE, cookies)
+        .send()?;
     writeln!(out.inner, "{:?}", res.text()?);
 
     Ok(())
@@ -23,12 +23,13 @@ fn main() -> Result<(), reqwest::Error> {
 
     out.write("Enter password: ");
     let password = token.next_line::<String>();
+    let res = common::reauthenticate(&client, SOCKET, &cookies, &password)?;
+    writeln!(out.inner, "{:?}", res.text()?);
 
-    let body = format!(r#"{{"password": "{password}"}}"#);
     let res = client
         .post(format!("{}/api/logout_all", SOCKET))
+        .header("x-csrf-token", common::csrf_token(&cookies))
         .header(header::COOKIE, cookies)
-        .body(body)
         .send()?;
     writeln!(out.inner, "{:?}", res.text()?);
 
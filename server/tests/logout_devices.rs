@@ -23,6 +23,8 @@ fn main() -> Result<(), reqwest::Error> {
 
     out.write("Enter password: ");
     let password = token.next_line::<String>();
+    let res = common::reauthenticate(&client, SOCKET, &cookies, &password)?;
+    writeln!(out.inner, "{:?}", res.text()?);
 
     let res =
         client.get(format!("{}/api/settings", SOCKET)).header(header::COOKIE, &cookies).send()?;
@@ -37,7 +39,8 @@ fn main() -> Result<(), reqwest::Error> {
     let res = client
         .post(format!("{}/api/logout_devices", SOCKET))
         .header(header::COOKIE, &cookies)
-        .json(&LogoutDevicesRequest { sessions, password })
+        .header("x-csrf-token", common::csrf_token(&cookies))
+        .json(&LogoutDevicesRequest { sessions })
         .send()?;
     writeln!(out.inner, "{:?}", res.text()?);
 
@@ -47,5 +50,4 @@ fn main() -> Result<(), reqwest::Error> {
 #[derive(serde::Serialize)]
 pub struct LogoutDevicesRequest {
     sessions: Vec<String>,
-    password: String,
 }
@@ -0,0 +1,72 @@
+#![allow(unused_must_use)]
+mod common;
+
+use common::{Printer, Scanner};
+use fake::Fake;
+use reqwest::header;
+use std::io::Write;
+
+// blocks an user, schedules the deletion of the account and restores it, the block has to be
+// listed again afterwards
+#[test]
+fn main() -> Result<(), reqwest::Error> {
+    const SOCKET: &str = "http://127.0.0.1:8080";
+    let client = reqwest::blocking::Client::builder()
+        .user_agent(fake::faker::internet::en::UserAgent().fake::<String>())
+        .build()
+        .unwrap_or_default();
+
+    // for io
+    let mut token = Scanner::new(std::io::stdin().lock());
+    let mut out = Printer::new();
+
+    out.write("Enter cookie: ");
+    let cookies = token.next_line::<String>();
+
+    out.write("Enter username to block: ");
+    let username = token.next_line::<String>();
+    let res = client
+        .post(format!("{SOCKET}/api/settings/blocked"))
+        .header(header::COOKIE, &cookies)
+        .header("x-csrf-token", common::csrf_token(&cookies))
+        .json(&serde_json::json!({ "username": username }))
+        .send()?;
+    writeln!(out.inner, "{:?}", res.text()?);
+
+    out.write("Enter password: ");
+    let password = token.next_line::<String>();
+    let res = common::reauthenticate(&client, SOCKET, &cookies, &password)?;
+    writeln!(out.inner, "{:?}", res.text()?);
+
+    let res = client
+        .post(format!("{SOCKET}/api/settings/delete_account"))
+        .header(header::COOKIE, &cookies)
+        .header("x-csrf-token", common::csrf_token(&cookies))
+        .send()?;
+    writeln!(out.inner, "{:?}", res.text()?);
+
+    loop {
+        out.write("Enter restore token: ");
+        let restore_token = token.next_line::<String>();
+        let res = client
+            .post(format!("{SOCKET}/api/settings/delete_account/cancel"))
+            .json(&serde_json::json!({ "token": restore_token }))
+            .send()?;
+        if res.status().is_client_error() {
+            writeln!(out.inner, "{:?}", res.text()?);
+        } else {
+            writeln!(out.inner, "{:?}", res.text()?);
+            break;
+        }
+    }
+
+    out.write("Enter cookie after signing in again: ");
+    let cookies = token.next_line::<String>();
+    let res = client
+        .get(format!("{SOCKET}/api/settings/blocked"))
+        .header(header::COOKIE, &cookies)
+        .send()?;
+    writeln!(out.inner, "{:?}", res.text()?);
+
+    Ok(())
+}
@@ -23,6 +23,11 @@ fn main() -> Result<(), reqwest::Error> {
     out.write("Enter cookie: ");
     let cookies = token.next_line::<String>();
 
+    out.write("Enter password: ");
+    let password = token.next_line::<String>();
+    let res = common::reauthenticate(&client, SOCKET, &cookies, &password)?;
+    writeln!(out.inner, "{:?}", res.text()?);
+
     loop {
         if is_auto {
             new_email = FreeEmail().fake::<String>()
@@ -30,10 +35,8 @@ fn main() -> Result<(), reqwest::Error> {
             out.write("Enter email: ");
             new_email = token.next_line::<String>();
         };
-        out.write("Enter password: ");
-        let password = token.next_line::<String>();
 
-        let body1 = format!(r#"{{"new_email": "{new_email}", "password": "{password}"}}"#);
+        let body1 = format!(r#"{{"new_email": "{new_email}"}}"#);
         if is_auto {
             out.write(&body1);
             out.write("\n");
@@ -42,6 +45,7 @@ fn main() -> Result<(), reqwest::Error> {
             .post(format!("{}/api/settings/email", SOCKET))
             .header(header::CONTENT_TYPE, "application/json")
             .header(header::COOKIE, &cookies)
+            .header("x-csrf-token", common::csrf_token(&cookies))
             .body(body1)
             .send();
         match res1 {
@@ -66,6 +70,7 @@ fn main() -> Result<(), reqwest::Error> {
             .post(format!("{}/api/settings/verify_email", SOCKET))
             .header(header::CONTENT_TYPE, "application/json")
             .header(header::COOKIE, &cookies)
+            .header("x-csrf-token", common::csrf_token(&cookies))
             .body(body2)
             .send();
         match res2 {
@@ -127,6 +127,7 @@ fn main() -> Result<(), reqwest::Error> {
             .post(&endpoint)
             .header(header::CONTENT_TYPE, "application/json")
             .header(header::COOKIE, &cookies)
+            .header("x-csrf-token", common::csrf_token(&cookies))
             .body(body)
             .send();
         match res1 {
@@ -38,6 +38,7 @@ fn main() -> Result<(), reqwest::Error> {
             .post(format!("{}/api/settings/password", SOCKET))
             .header(header::CONTENT_TYPE, "application/json")
             .header(header::COOKIE, &cookies)
+            .header("x-csrf-token", common::csrf_token(&cookies))
             .body(body1)
             .send();
         match res1 {
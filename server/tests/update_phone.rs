@@ -0,0 +1,77 @@
+#![allow(unused_must_use)]
+mod common;
+
+use common::{Printer, Scanner};
+use fake::Fake;
+use reqwest::header;
+use std::io::Write;
+
+#[test]
+fn main() -> Result<(), reqwest::Error> {
+    const SOCKET: &str = "http://127.0.0.1:8080";
+    let client = reqwest::blocking::Client::builder()
+        .user_agent(fake::faker::internet::en::UserAgent().fake::<String>())
+        .build()
+        .unwrap_or_default();
+
+    // for io
+    let mut token = Scanner::new(std::io::stdin().lock());
+    let mut out = Printer::new();
+
+    out.write("Enter cookie: ");
+    let cookies = token.next_line::<String>();
+
+    loop {
+        out.write("Enter phone (e.g. +14155552671): ");
+        let phone = token.next_line::<String>();
+
+        let body1 = format!(r#"{{"phone": "{phone}"}}"#);
+        let res1 = client
+            .post(format!("{}/api/settings/phone", SOCKET))
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::COOKIE, &cookies)
+            .header("x-csrf-token", common::csrf_token(&cookies))
+            .body(body1)
+            .send();
+        match res1 {
+            Ok(v) => {
+                if v.status().is_client_error() {
+                    writeln!(out.inner, "{:?}", v.text()?);
+                } else {
+                    break;
+                }
+            }
+            Err(e) => {
+                writeln!(out.inner, "{e:?}");
+            }
+        }
+    }
+
+    loop {
+        out.write("Enter otp: ");
+        let otp = token.next_line::<String>();
+        let body2 = format!(r#"{{"otp": "{otp}"}}"#);
+        let res2 = client
+            .post(format!("{}/api/settings/verify_phone", SOCKET))
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::COOKIE, &cookies)
+            .header("x-csrf-token", common::csrf_token(&cookies))
+            .body(body2)
+            .send();
+        match res2 {
+            Ok(v) => {
+                if v.status().is_client_error() {
+                    writeln!(out.inner, "{:?}", v.text()?);
+                } else {
+                    writeln!(out.inner, "{:?}", v.text()?);
+                    break;
+                }
+            }
+            Err(e) => {
+                writeln!(out.inner, "{e:?}");
+            }
+        }
+    }
+
+    Ok(())
+}
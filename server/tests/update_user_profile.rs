@@ -65,7 +65,12 @@ fn main() -> Result<(), reqwest::Error> {
     }
 
     let endpoint = format!("{}/api/user/profile", SOCKET);
-    let res = client.post(&endpoint).header(header::COOKIE, cookies).multipart(form).send()?;
+    let res = client
+        .post(&endpoint)
+        .header("x-csrf-token", common::csrf_token(&cookies))
+        .header(header::COOKIE, cookies)
+        .multipart(form)
+        .send()?;
     writeln!(out.inner, "{:?}", res.text()?);
 
     Ok(())
@@ -22,6 +22,11 @@ fn main() -> Result<(), reqwest::Error> {
     out.write("Enter cookie: ");
     let cookies = token.next_line::<String>();
 
+    out.write("Enter password: ");
+    let password = token.next_line::<String>();
+    let res = common::reauthenticate(&client, SOCKET, &cookies, &password)?;
+    writeln!(out.inner, "{:?}", res.text()?);
+
     loop {
         let new_username = if is_auto {
             Username().fake::<String>().replace("_", ".")
@@ -29,10 +34,8 @@ fn main() -> Result<(), reqwest::Error> {
             out.write("Enter username: ");
             token.next_line::<String>()
         };
-        out.write("Enter password: ");
-        let password = token.next_line::<String>();
 
-        let body1 = format!(r#"{{"new_username": "{new_username}", "password": "{password}"}}"#);
+        let body1 = format!(r#"{{"new_username": "{new_username}"}}"#);
         if is_auto {
             out.write(&body1);
             out.write("\n");
@@ -41,6 +44,7 @@ fn main() -> Result<(), reqwest::Error> {
             .post(format!("{}/api/settings/username", SOCKET))
             .header(header::CONTENT_TYPE, "application/json")
             .header(header::COOKIE, &cookies)
+            .header("x-csrf-token", common::csrf_token(&cookies))
             .body(body1)
             .send();
         match res1 {
@@ -1,7 +1,21 @@
 pub mod validation;
 
-pub static SERVICE_NAME: std::sync::LazyLock<String> =
-    std::sync::LazyLock::new(|| std::env::var("SERVICE_NAME").unwrap());
+use std::sync::{LazyLock, OnceLock};
 
-pub static SERVICE_DOMAIN: std::sync::LazyLock<String> =
-    std::sync::LazyLock::new(|| std::env::var("SERVICE_DOMAIN").unwrap());
+// set from the validated config at startup by `util::init`
+static SERVICE: OnceLock<(String, String)> = OnceLock::new();
+
+/// sets `SERVICE_NAME` and `SERVICE_DOMAIN`
+///
+/// must be called before either is first read, returns false otherwise
+pub fn set_service(name: String, domain: String) -> bool {
+    SERVICE.set((name, domain)).is_ok()
+}
+
+fn service() -> &'static (String, String) {
+    SERVICE.get().expect("shared::set_service must be called before reading the service")
+}
+
+pub static SERVICE_NAME: LazyLock<String> = LazyLock::new(|| service().0.clone());
+
+pub static SERVICE_DOMAIN: LazyLock<String> = LazyLock::new(|| service().1.clone());
@@ -54,6 +54,10 @@ pub enum ValidationError {
     #[error("Invalid Email Format")]
     InvalidEmailFormat,
 
+    // Phone
+    #[error("Invalid Phone Number: {0}")]
+    InvalidPhone(&'static str),
+
     // Image
     #[error("Image data too short: need at least {needed} bytes, got {got}")]
     ImageTooShort { needed: usize, got: usize },
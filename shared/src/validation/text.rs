@@ -138,6 +138,34 @@ pub fn is_country_valid(country: &str) -> Result<String, ValidationError> {
     Ok(c.long_name.to_string())
 }
 
+// returns the number in E.164 format (`+` followed by 8 to 15 digits)
+// spaces, dashes, dots and parentheses are ignored and a leading `00` is read as `+`
+pub fn is_phone_valid(phone: &str) -> Result<String, ValidationError> {
+    let phone = phone.trim();
+    let digits = if let Some(v) = phone.strip_prefix('+') {
+        v
+    } else if let Some(v) = phone.strip_prefix("00") {
+        v
+    } else {
+        return Err(ValidationError::InvalidPhone("Country code is required (e.g. +1)"));
+    };
+    let mut result = String::from("+");
+    for c in digits.chars() {
+        match c {
+            '0'..='9' => result.push(c),
+            ' ' | '-' | '.' | '(' | ')' => continue,
+            _ => return Err(ValidationError::InvalidPhone("Only digits are allowed")),
+        }
+    }
+    if result.starts_with("+0") {
+        return Err(ValidationError::InvalidPhone("Country code cannot start with 0"));
+    }
+    if !(9..=16).contains(&result.len()) {
+        return Err(ValidationError::InvalidPhone("Phone number must have 8 to 15 digits"));
+    }
+    Ok(result)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -169,4 +197,28 @@ mod tests {
         name_test12: (" abc-def  ", None),
         name_test13: (" abc@def  ", None),
     }
+
+    macro_rules! phone_test {
+        ($($name:ident: $exp:expr,)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    let (haystack, expected) = $exp;
+                    assert_eq!(is_phone_valid(haystack).ok().as_deref(), expected);
+                }
+            )*
+        };
+    }
+
+    phone_test! {
+        phone_test1: ("+14155552671", Some("+14155552671")),
+        phone_test2: (" +1 (415) 555-2671 ", Some("+14155552671")),
+        phone_test3: ("0091 98765.43210", Some("+919876543210")),
+        phone_test4: ("4155552671", None),
+        phone_test5: ("+0155552671", None),
+        phone_test6: ("+1234567", None),
+        phone_test7: ("+1234567890123456", None),
+        phone_test8: ("+1 415 555 267a", None),
+        phone_test9: ("+", None),
+    }
 }
@@ -78,6 +78,138 @@ fn is_domain_valid(domain: &str) -> bool {
     true
 }
 
+/// usernames nobody can claim, as they could be mistaken for the service or its staff
+///
+/// shorter ones than a valid username are kept in case the length limit changes
+pub const RESERVED_USERNAMES: [&str; 32] = [
+    "about",
+    "abuse",
+    "account",
+    "accounts",
+    "admin",
+    "administrator",
+    "anonymous",
+    "api",
+    "billing",
+    "contact",
+    "everyone",
+    "help",
+    "hostmaster",
+    "info",
+    "login",
+    "logout",
+    "moderator",
+    "no.reply",
+    "noreply",
+    "null",
+    "official",
+    "postmaster",
+    "privacy",
+    "register",
+    "root",
+    "security",
+    "settings",
+    "staff",
+    "support",
+    "system",
+    "undefined",
+    "webmaster",
+];
+
+/// whether `s` looks like one of `RESERVED_USERNAMES`
+pub fn is_username_reserved(s: &str) -> bool {
+    let skeleton = username_skeleton(s);
+    RESERVED_USERNAMES.iter().any(|v| username_skeleton(v) == skeleton)
+}
+
+// look-alike characters and the one they are compared as, `username_skeleton()` in the
+// migrations has to be kept in sync
+const CONFUSABLES: [(char, char); 14] = [
+    ('0', 'o'),
+    ('1', 'l'),
+    ('а', 'a'), // cyrillic
+    ('е', 'e'),
+    ('о', 'o'),
+    ('р', 'p'),
+    ('с', 'c'),
+    ('у', 'y'),
+    ('х', 'x'),
+    ('і', 'i'),
+    ('ο', 'o'), // greek
+    ('α', 'a'),
+    ('ρ', 'p'),
+    ('χ', 'x'),
+];
+
+/// the form usernames are unique by, two usernames with the same skeleton can't coexist
+///
+/// it ignores case and periods and replaces characters that look alike with one of them, so
+/// `john.doe`, `J0hnDoe` and `jоhndое` (with cyrillic letters) all end up as `johndoe`
+pub fn username_skeleton(s: &str) -> String {
+    s.chars()
+        .flat_map(char::to_lowercase)
+        .filter(|&c| c != '.')
+        .map(|c| CONFUSABLES.iter().find(|v| v.0 == c).map_or(c, |v| v.1))
+        .collect::<String>()
+        .replace("rn", "m")
+        .replace("vv", "w")
+}
+
+/// valid usernames made from the display name and the local part of the email, the most
+/// recognizable first, numbers still have to be appended to taken ones
+pub fn username_candidates(display_name: &str, email: &str) -> Vec<String> {
+    // words with other letters are left out, rather than losing some of their letters
+    let words = display_name
+        .split(|c: char| c.is_whitespace() || c.is_ascii_punctuation())
+        .filter(|v| !v.is_empty() && v.chars().all(|c| c.is_ascii_alphanumeric()))
+        .map(|v| v.to_ascii_lowercase())
+        .collect::<Vec<_>>();
+    // `+` starts a subaddress, which is rarely part of the name
+    let local_part = email.split('@').next().unwrap_or_default();
+    let local_part = local_part.split('+').next().unwrap_or_default().to_ascii_lowercase();
+
+    let mut candidates = vec![];
+    if words.len() > 1 {
+        let (first, last) = (&words[0], &words[words.len() - 1]);
+        candidates.push(format!("{first}.{last}"));
+        candidates.push(format!("{first}{last}"));
+        candidates.push(format!("{last}.{first}"));
+        candidates.push(words.join("."));
+    }
+    candidates.push(local_part.replace(['-', '_'], "."));
+    candidates.push(words.concat());
+
+    let mut unique = Vec::<String>::new();
+    for v in candidates.iter().filter_map(|v| to_username(v)) {
+        if !unique.contains(&v) {
+            unique.push(v);
+        }
+    }
+    unique
+}
+
+// keeps the characters allowed in a username, `None` if too little is left
+fn to_username(s: &str) -> Option<String> {
+    let mut username = String::new();
+    for c in s.chars().skip_while(|c| !c.is_ascii_lowercase()) {
+        if c.is_ascii_lowercase() || c.is_ascii_digit() || (c == '.' && !username.ends_with('.')) {
+            username.push(c);
+        }
+    }
+    username.truncate(20);
+    let username = username.trim_end_matches('.');
+    if username.len() < 3 {
+        return None;
+    }
+    // short names are padded with a number rather than dropped
+    let username = if username.len() < 6 {
+        format!("{username}{}", &"123456"[..6 - username.len()])
+    } else {
+        username.to_string()
+    };
+    is_username_valid(&username).ok().map(|_| username)
+}
+
 pub fn is_username_valid(s: &str) -> Result<(), ValidationError> {
     if s.len() < 6 || s.len() > 20 {
         return Err(ValidationError::InvalidUsername(
@@ -167,4 +299,40 @@ mod tests {
         username_test_09: ("a.7.b.xetn", Some(())),
         username_test_10: ("example.com", Some(())),
     }
+
+    #[test]
+    fn reserved_username_test() {
+        assert!(is_username_reserved("support"));
+        assert!(is_username_reserved("no.reply"));
+        assert!(!is_username_reserved("supporter"));
+        assert!(is_username_reserved("supp0rt"));
+        assert!(is_username_reserved("sys.tem"));
+        // every reserved username would be claimable otherwise
+        for name in RESERVED_USERNAMES.iter().filter(|v| v.len() >= 6) {
+            assert_eq!(is_username_valid(name).ok(), Some(()), "{name}");
+        }
+    }
+
+    #[test]
+    fn username_skeleton_test() {
+        assert_eq!(username_skeleton("john.doe"), "johndoe");
+        assert_eq!(username_skeleton("J0HN.D0E"), "johndoe");
+        assert_eq!(username_skeleton("jоhndое"), "johndoe");
+        assert_eq!(username_skeleton("wi11iam"), "william");
+        assert_eq!(username_skeleton("rnarvvin"), "marwin");
+        assert_ne!(username_skeleton("john.doe1"), username_skeleton("john.doe2"));
+    }
+
+    #[test]
+    fn username_candidates_test() {
+        assert_eq!(
+            username_candidates("John Ronald Doe", "jdoe+news@example.com"),
+            ["john.doe", "johndoe", "doe.john", "john.ronald.doe", "jdoe12", "johnronalddoe"]
+        );
+        assert_eq!(username_candidates("Émile", "1st_emile@example.com"), ["st.emile"]);
+        assert_eq!(username_candidates("李", "42@example.com"), Vec::<String>::new());
+        for v in username_candidates("Alexandria Ocasio-Cortez", "alexandria.ocasio.cortez@x.io") {
+            assert_eq!(is_username_valid(&v).ok(), Some(()), "{v}");
+        }
+    }
 }
@@ -0,0 +1,46 @@
+use dioxus::prelude::*;
+
+// CSRF token of the current session, fetched on the first request that needs it
+static CSRF_TOKEN: GlobalSignal<Option<String>> = Signal::global(|| None);
+
+/// forgets the CSRF token, called whenever the session changes
+pub fn reset_csrf_token() {
+    *CSRF_TOKEN.write() = None;
+}
+
+async fn csrf_token() -> Result<String, reqwest::Error> {
+    if let Some(token) = CSRF_TOKEN() {
+        return Ok(token);
+    }
+    let url = format!("{}/api/csrf", crate::SERVICE_DOMAIN());
+    let response = reqwest::Client::new().get(&url).send().await?.error_for_status()?;
+    let body = response.json::<serde_json::Value>().await?;
+    let token = body["csrf_token"].as_str().unwrap_or_default().to_string();
+    *CSRF_TOKEN.write() = Some(token.clone());
+    Ok(token)
+}
+
+/// whether the browser has a session, the CSRF token is only handed out to signed in users
+pub async fn is_signed_in() -> bool {
+    csrf_token().await.is_ok()
+}
+
+/// sends `body` to an endpoint that needs a session, with the CSRF token attached
+///
+/// the token is fetched again once if the server rejects it, as it changes with the session
+pub async fn post(
+    path: &str,
+    body: &serde_json::Value,
+) -> Result<reqwest::Response, reqwest::Error> {
+    let url = format!("{}{path}", crate::SERVICE_DOMAIN());
+    let send = |token: String| {
+        reqwest::Client::new().post(&url).header("X-CSRF-Token", token).json(body).send()
+    };
+
+    let response = send(csrf_token().await?).await?;
+    if response.status() != reqwest::StatusCode::FORBIDDEN {
+        return Ok(response);
+    }
+    reset_csrf_token();
+    send(csrf_token().await?).await
+}
@@ -299,6 +299,17 @@ pub(super) fn EnterUsername(
     is_loading: Signal<bool>,
     on_submit: EventHandler<()>,
 ) -> Element {
+    // available usernames made from the name and email given so far
+    let suggested = use_resource(move || async move {
+        let url = format!("{}/api/register/suggest_usernames", crate::SERVICE_DOMAIN());
+        let response =
+            reqwest::Client::new().get(&url).query(&[("email", email())]).send().await.ok()?;
+        let body = response.json::<serde_json::Value>().await.ok()?;
+        let suggestions = body["suggestions"].as_array()?.iter();
+        Some(suggestions.filter_map(|v| v.as_str().map(String::from)).collect::<Vec<_>>())
+    });
+    let suggestions = move || suggested.read().clone().flatten().unwrap_or_default();
+
     rsx! {
         div {
             // Header
@@ -345,6 +356,21 @@ pub(super) fn EnterUsername(
                         class: "text-sm text-[var(--secondary-color-5)]",
                         "Only letters, numbers, and periods. Min 6 characters."
                     }
+                    if !suggestions().is_empty() {
+                        div {
+                            class: "flex flex-wrap gap-2 pt-1",
+                            for suggestion in suggestions() {
+                                button {
+                                    key: "{suggestion}",
+                                    class: "rounded-full border px-3 py-1 text-sm transition-colors text-[var(--secondary-color-2)] bg-[var(--primary-color-3)]",
+                                    style: "border-color: var(--primary-color-6);",
+                                    r#type: "button",
+                                    onclick: move |_| username.set(suggestion.clone()),
+                                    "{suggestion}"
+                                }
+                            }
+                        }
+                    }
                 }
 
                 // Action buttons
@@ -10,6 +10,24 @@ pub fn Login() -> Element {
     let mut show_username_step = use_signal(|| false);
     let mut oauth_email = use_signal(String::new);
     let mut new_username = use_signal(String::new);
+    let mut info_message = use_signal(String::new);
+    let mut show_code_step = use_signal(|| false);
+    let mut remember_me = use_signal(|| false);
+
+    // login methods enabled on the server
+    let methods = use_resource(|| async move {
+        let url = format!("{}/api/login/methods", crate::SERVICE_DOMAIN());
+        let response = reqwest::Client::new().get(&url).send().await.ok()?;
+        response.json::<serde_json::Value>().await.ok()
+    });
+    let magic_link_enabled = move || {
+        methods
+            .read()
+            .as_ref()
+            .and_then(|v| v.as_ref())
+            .and_then(|v| v["magic_link"].as_bool())
+            .unwrap_or(false)
+    };
 
     let handle_login = move |ev: Event<FormData>| async move {
         ev.prevent_default();
@@ -47,12 +65,14 @@ pub fn Login() -> Element {
                 "email": email_opt,
                 "username": username_opt,
                 "password": pass,
+                "remember_me": remember_me(),
             }))
             .send()
             .await
         {
             Ok(response) => {
                 if response.status().is_success() {
+                    crate::api::reset_csrf_token();
                     // Successfully logged in - redirect or update state
                     // You might want to save auth token, redirect to dashboard, etc.
                 } else {
@@ -69,6 +89,82 @@ pub fn Login() -> Element {
         is_loading.set(false);
     };
 
+    let handle_send_code = move |_| async move {
+        error_message.set(String::new());
+        info_message.set(String::new());
+
+        let email = id();
+        if let Err(e) = shared::validation::is_email_valid(&email) {
+            error_message.set(e.to_string());
+            return;
+        }
+        is_loading.set(true);
+
+        let url = format!("{}/api/login/otp", crate::SERVICE_DOMAIN());
+        match reqwest::Client::new()
+            .post(&url)
+            .json(&serde_json::json!({ "email": email }))
+            .send()
+            .await
+        {
+            Ok(response) => {
+                if response.status().is_success() {
+                    show_code_step.set(true);
+                } else {
+                    let error_text = response
+                        .text()
+                        .await
+                        .unwrap_or_else(|_| "Failed to send login code".to_string());
+                    error_message.set(error_text);
+                }
+            }
+            Err(e) => {
+                error_message.set(format!("Network error: {}", e));
+            }
+        }
+
+        is_loading.set(false);
+    };
+
+    let handle_magic_link = move |_| async move {
+        error_message.set(String::new());
+        info_message.set(String::new());
+
+        let email = id();
+        if let Err(e) = shared::validation::is_email_valid(&email) {
+            error_message.set(e.to_string());
+            return;
+        }
+        is_loading.set(true);
+
+        let url = format!("{}/api/login/magic_link", crate::SERVICE_DOMAIN());
+        match reqwest::Client::new()
+            .post(&url)
+            .json(&serde_json::json!({ "email": email }))
+            .send()
+            .await
+        {
+            Ok(response) => {
+                if response.status().is_success() {
+                    info_message.set(format!(
+                        "If an account exists for {email}, a sign-in link has been sent. Open it on this browser."
+                    ));
+                } else {
+                    let error_text = response
+                        .text()
+                        .await
+                        .unwrap_or_else(|_| "Failed to send sign-in link".to_string());
+                    error_message.set(error_text);
+                }
+            }
+            Err(e) => {
+                error_message.set(format!("Network error: {}", e));
+            }
+        }
+
+        is_loading.set(false);
+    };
+
     let handle_google_login = move |_| async move {
         is_loading.set(true);
         error_message.set(String::new());
@@ -78,7 +174,7 @@ pub fn Login() -> Element {
         // Redirect to OAuth endpoint
         // In a real browser environment, you'd use window.location
         // For now, we'll simulate the flow
-        match reqwest::Client::new().get(&url).send().await {
+        match reqwest::Client::new().post(&url).send().await {
             Ok(response) => {
                 if response.status().is_success() {
                     // Check if we need to set username
@@ -124,6 +220,13 @@ pub fn Login() -> Element {
                         is_loading: is_loading,
                         on_complete: handle_username_complete,
                     }
+                } else if show_code_step() {
+                    LoginCodeStep {
+                        email: id,
+                        remember_me: remember_me,
+                        is_loading: is_loading,
+                        on_back: move |_| show_code_step.set(false),
+                    }
                 } else {
                     // Card container
                     div {
@@ -151,6 +254,15 @@ pub fn Login() -> Element {
                             }
                         }
 
+                        // Info message
+                        if !info_message().is_empty() {
+                            div {
+                                class: "mb-4 p-3 rounded-md text-sm bg-[var(--primary-color-3)] text-[var(--secondary-color-2)]",
+                                style: "border: 1px solid var(--primary-color-6);",
+                                {info_message()}
+                            }
+                        }
+
                         // Form
                         form {
                             class: "space-y-4",
@@ -204,6 +316,22 @@ pub fn Login() -> Element {
                                 }
                             }
 
+                            // Remember me
+                            div {
+                                class: "flex items-center space-x-2",
+                                input {
+                                    r#type: "checkbox",
+                                    id: "remember_me",
+                                    checked: remember_me(),
+                                    onchange: move |e| remember_me.set(e.checked()),
+                                }
+                                label {
+                                    class: "text-sm text-[var(--secondary-color-2)]",
+                                    r#for: "remember_me",
+                                    "Remember me"
+                                }
+                            }
+
                             // Submit button
                             button {
                                 class: "w-full h-10 rounded-md text-sm font-medium transition-colors disabled:opacity-50 disabled:cursor-not-allowed bg-[var(--secondary-color-1)] text-[var(--primary-color)]",
@@ -217,6 +345,26 @@ pub fn Login() -> Element {
                             }
                         }
 
+                        // Login code button
+                        button {
+                            class: "w-full h-10 mt-3 rounded-md border text-sm font-medium transition-colors disabled:opacity-50 disabled:cursor-not-allowed bg-[var(--primary-color-3)] text-[var(--secondary-color-1)]",
+                            style: "border-color: var(--primary-color-6);",
+                            onclick: handle_send_code,
+                            disabled: is_loading(),
+                            "Email me a login code"
+                        }
+
+                        // Magic link button
+                        if magic_link_enabled() {
+                            button {
+                                class: "w-full h-10 mt-3 rounded-md border text-sm font-medium transition-colors disabled:opacity-50 disabled:cursor-not-allowed bg-[var(--primary-color-3)] text-[var(--secondary-color-1)]",
+                                style: "border-color: var(--primary-color-6);",
+                                onclick: handle_magic_link,
+                                disabled: is_loading(),
+                                "Email me a sign-in link"
+                            }
+                        }
+
                         // Divider
                         div {
                             class: "relative my-6",
@@ -413,3 +561,124 @@ fn SetUsernameStep(
         }
     }
 }
+
+#[component]
+fn LoginCodeStep(
+    email: Signal<String>,
+    remember_me: Signal<bool>,
+    is_loading: Signal<bool>,
+    on_back: EventHandler<()>,
+) -> Element {
+    let mut code = use_signal(String::new);
+    let mut error_message = use_signal(String::new);
+
+    let handle_submit = move |ev: Event<FormData>| async move {
+        ev.prevent_default();
+        is_loading.set(true);
+        error_message.set(String::new());
+
+        let url = format!("{}/api/login/otp/verify", crate::SERVICE_DOMAIN());
+        match reqwest::Client::new()
+            .post(&url)
+            .json(&serde_json::json!({
+                "email": email(),
+                "otp": code(),
+                "remember_me": remember_me(),
+            }))
+            .send()
+            .await
+        {
+            Ok(response) => {
+                if response.status().is_success() {
+                    crate::api::reset_csrf_token();
+                    // Successfully logged in - redirect or update state
+                } else {
+                    let error_text =
+                        response.text().await.unwrap_or_else(|_| "Login failed".to_string());
+                    error_message.set(error_text);
+                }
+            }
+            Err(e) => {
+                error_message.set(format!("Network error: {}", e));
+            }
+        }
+
+        is_loading.set(false);
+    };
+
+    rsx! {
+        div {
+            class: "rounded-lg border p-8 shadow-sm bg-[var(--primary-color-1)] border-[var(--primary-color-6)]",
+
+            // Header
+            div {
+                class: "flex flex-col space-y-2 text-center mb-6",
+                h1 {
+                    class: "text-2xl font-semibold tracking-tight text-[var(--secondary-color-1)]",
+                    "Check your email"
+                }
+                p {
+                    class: "text-sm text-[var(--secondary-color-5)]",
+                    "Enter the 6 digit code sent to {email}"
+                }
+            }
+
+            // Error message
+            if !error_message().is_empty() {
+                div {
+                    class: "mb-4 p-3 rounded-md text-sm bg-[#fee] text-[#c33]",
+                    style: "border: 1px solid #fcc;",
+                    {error_message()}
+                }
+            }
+
+            // Form
+            form {
+                class: "space-y-4",
+                onsubmit: handle_submit,
+
+                // Code field
+                div {
+                    class: "space-y-2",
+                    label {
+                        class: "text-sm font-medium text-[var(--secondary-color-2)]",
+                        r#for: "code",
+                        "Login code"
+                    }
+                    input {
+                        class: "flex h-10 w-full rounded-md border px-3 py-2 text-sm tracking-widest transition-colors focus:outline-none focus:ring-2 focus:ring-offset-2 bg-[var(--primary-color-3)] text-[var(--secondary-color-1)]",
+                        style: "border-color: var(--primary-color-6); focus:ring-color: var(--focused-border-color);",
+                        r#type: "text",
+                        id: "code",
+                        inputmode: "numeric",
+                        autocomplete: "one-time-code",
+                        maxlength: 6,
+                        placeholder: "123456",
+                        value: "{code}",
+                        oninput: move |e| code.set(e.value()),
+                        required: true,
+                    }
+                }
+
+                // Submit button
+                button {
+                    class: "w-full h-10 rounded-md text-sm font-medium transition-colors disabled:opacity-50 disabled:cursor-not-allowed bg-[var(--secondary-color-1)] text-[var(--primary-color)]",
+                    r#type: "submit",
+                    disabled: is_loading(),
+                    if is_loading() {
+                        "Signing in..."
+                    } else {
+                        "Login"
+                    }
+                }
+            }
+
+            // Back link
+            button {
+                class: "w-full mt-4 text-sm font-medium hover:underline text-[var(--focused-border-color)]",
+                onclick: move |_| on_back.call(()),
+                "Use a different login method"
+            }
+        }
+    }
+}
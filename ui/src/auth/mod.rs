@@ -5,5 +5,5 @@ mod register;
 
 use dialogs::*;
 pub(crate) use login::Login;
-pub(crate) use recovery::{ForgotPassword, ResetPassword};
+pub(crate) use recovery::{ForgotPassword, NotMe, ResetPassword, RestoreAccount};
 pub(crate) use register::Register;
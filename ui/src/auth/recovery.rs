@@ -305,3 +305,221 @@ pub fn ResetPassword(code: String) -> Element {
         }
     }
 }
+
+/// opened from the "this wasn't me" link of a new sign-in alert, nothing happens until the user
+/// confirms, as mail scanners open links on their own
+#[component]
+pub fn NotMe(token: String) -> Element {
+    let mut is_loading = use_signal(|| false);
+    let mut error_message = use_signal(String::new);
+
+    let handle_confirm = move |_| {
+        let token = token.clone();
+        async move {
+            is_loading.set(true);
+            error_message.set(String::new());
+
+            let url = format!("{}/api/login/not_me", crate::SERVICE_DOMAIN());
+            match reqwest::Client::new()
+                .post(&url)
+                .json(&serde_json::json!({ "token": token }))
+                .send()
+                .await
+            {
+                Ok(response) => {
+                    if response.status().is_success() {
+                        crate::api::reset_csrf_token();
+                        let body = response.json::<serde_json::Value>().await.unwrap_or_default();
+                        let code = body["code"].as_str().unwrap_or_default().to_string();
+                        navigator().push(crate::Route::ResetPassword { code });
+                    } else {
+                        let error_text = response
+                            .text()
+                            .await
+                            .unwrap_or_else(|_| "Failed to secure your account".to_string());
+                        error_message.set(error_text);
+                    }
+                }
+                Err(e) => {
+                    error_message.set(format!("Network error: {}", e));
+                }
+            }
+
+            is_loading.set(false);
+        }
+    };
+
+    rsx! {
+        div {
+            class: "min-h-screen flex items-center justify-center px-4 bg-[var(--primary-color)]",
+
+            div {
+                class: "w-full max-w-md",
+
+                div {
+                    class: "rounded-lg border p-8 shadow-sm bg-[var(--primary-color)] border-[var(--primary-color-6)]",
+
+                    // Header
+                    div {
+                        class: "flex flex-col space-y-2 mb-6",
+                        h1 {
+                            class: "text-2xl font-semibold tracking-tight text-[var(--secondary-color-1)]",
+                            "Secure your account"
+                        }
+                        p {
+                            class: "text-base text-[var(--secondary-color-5)]",
+                            "This signs out every device, including this one, and asks you for a new password"
+                        }
+                    }
+
+                    // Error message
+                    if !error_message().is_empty() {
+                        div {
+                            class: "mb-4 p-3 rounded-md text-sm bg-[#fee] text-[#c33]",
+                            style: "border: 1px solid #fcc;",
+                            {error_message()}
+                        }
+                    }
+
+                    // Action buttons
+                    div {
+                        class: "flex gap-3 pt-2",
+                        button {
+                            class: "flex-1 h-11 rounded-md border text-base font-medium transition-colors text-[var(--secondary-color-1)]",
+                            style: "background-color: var(--primary-color-3); border-color: var(--primary-color-6);",
+                            r#type: "button",
+                            onclick: move |_| {
+                                router().go_back();
+                            },
+                            "Cancel"
+                        }
+                        button {
+                            class: "flex-1 h-11 rounded-md text-base font-medium transition-colors disabled:opacity-50 disabled:cursor-not-allowed text-[var(--primary-color)]",
+                            style: "background-color: #b91c1c;",
+                            r#type: "button",
+                            disabled: is_loading(),
+                            onclick: handle_confirm,
+                            if is_loading() {
+                                "Signing out..."
+                            } else {
+                                "This wasn't me"
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// opened from the link sent when the deletion of an account was scheduled, the account is only
+/// restored once the user confirms
+#[component]
+pub fn RestoreAccount(token: String) -> Element {
+    let mut is_loading = use_signal(|| false);
+    let mut error_message = use_signal(String::new);
+    let mut restored = use_signal(|| false);
+
+    let handle_restore = move |_| {
+        let token = token.clone();
+        async move {
+            is_loading.set(true);
+            error_message.set(String::new());
+
+            let url = format!("{}/api/settings/delete_account/cancel", crate::SERVICE_DOMAIN());
+            match reqwest::Client::new()
+                .post(&url)
+                .json(&serde_json::json!({ "token": token }))
+                .send()
+                .await
+            {
+                Ok(response) => {
+                    if response.status().is_success() {
+                        restored.set(true);
+                    } else {
+                        let error_text = response
+                            .text()
+                            .await
+                            .unwrap_or_else(|_| "Failed to restore your account".to_string());
+                        error_message.set(error_text);
+                    }
+                }
+                Err(e) => {
+                    error_message.set(format!("Network error: {}", e));
+                }
+            }
+
+            is_loading.set(false);
+        }
+    };
+
+    rsx! {
+        div {
+            class: "min-h-screen flex items-center justify-center px-4 bg-[var(--primary-color)]",
+
+            div {
+                class: "w-full max-w-md",
+
+                div {
+                    class: "rounded-lg border p-8 shadow-sm bg-[var(--primary-color)] border-[var(--primary-color-6)]",
+
+                    if !restored() {
+                        // Header
+                        div {
+                            class: "flex flex-col space-y-2 mb-6",
+                            h1 {
+                                class: "text-2xl font-semibold tracking-tight text-[var(--secondary-color-1)]",
+                                "Restore your account"
+                            }
+                            p {
+                                class: "text-base text-[var(--secondary-color-5)]",
+                                "Your account is scheduled for deletion, restoring it cancels the deletion"
+                            }
+                        }
+
+                        // Error message
+                        if !error_message().is_empty() {
+                            div {
+                                class: "mb-4 p-3 rounded-md text-sm bg-[#fee] text-[#c33]",
+                                style: "border: 1px solid #fcc;",
+                                {error_message()}
+                            }
+                        }
+
+                        button {
+                            class: "w-full h-11 rounded-md text-base font-medium transition-colors disabled:opacity-50 disabled:cursor-not-allowed text-[var(--primary-color)]",
+                            style: "background-color: var(--secondary-color-1);",
+                            r#type: "button",
+                            disabled: is_loading(),
+                            onclick: handle_restore,
+                            if is_loading() {
+                                "Restoring..."
+                            } else {
+                                "Restore my account"
+                            }
+                        }
+                    } else {
+                        // Restored confirmation
+                        div {
+                            class: "flex flex-col space-y-2 text-center mb-6",
+                            h2 {
+                                class: "text-2xl font-semibold tracking-tight text-[var(--secondary-color-1)]",
+                                "Your account has been restored"
+                            }
+                            p {
+                                class: "text-base text-[var(--secondary-color-5)]",
+                                "You can sign in again"
+                            }
+                        }
+
+                        Link {
+                            class: "flex items-center justify-center w-full h-11 rounded-md border text-base font-medium transition-colors bg-[var(--primary-color-3)] border-[var(--primary-color-6)] text-[var(--secondary-color-1)]",
+                            to: crate::Route::Login {},
+                            "Go to login"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,15 +1,18 @@
 mod about;
+pub mod api;
 mod auth;
 mod blog;
 mod home;
 mod navbar;
 mod not_found;
+mod profile;
 
 use crate::about::About;
-use crate::auth::{ForgotPassword, Login, Register, ResetPassword};
+use crate::auth::{ForgotPassword, Login, NotMe, Register, ResetPassword, RestoreAccount};
 use crate::blog::Blog;
 use crate::home::Home;
 use crate::not_found::NotFound;
+use crate::profile::{Handle, Profile};
 use dioxus::prelude::*;
 
 static SERVICE_NAME: GlobalSignal<String> = Signal::global(|| (*shared::SERVICE_NAME).clone());
@@ -29,10 +32,17 @@ pub enum Route {
         ForgotPassword {},
         #[route("/reset-password?:code")]
         ResetPassword { code: String },
+        #[route("/not-me?:token")]
+        NotMe { token: String },
+        #[route("/restore-account?:token")]
+        RestoreAccount { token: String },
         #[route("/blog")]
         Blog {},
         #[route("/about")]
         About {},
+        // `/@username`, other single segments fail to parse as a `Handle`
+        #[route("/:handle")]
+        Profile { handle: Handle },
 
         // wildcard route
         #[route("/:..endpoint")]
@@ -4,6 +4,18 @@ use dioxus::prelude::*;
 
 #[component]
 pub fn NavigationBar() -> Element {
+    let mut signed_in = use_resource(crate::api::is_signed_in);
+
+    let handle_logout = move |_| async move {
+        if let Ok(response) = crate::api::post("/api/logout", &serde_json::json!({})).await
+            && response.status().is_success()
+        {
+            crate::api::reset_csrf_token();
+            signed_in.restart();
+            navigator().push(Route::Login {});
+        }
+    };
+
     rsx! {
         div {
             class: "flex flex-col h-screen overflow-hidden",
@@ -73,13 +85,24 @@ pub fn NavigationBar() -> Element {
                     div {
                         class: "flex justify-end items-center gap-4",
 
-                        // Login button
-                        Link {
-                            class: "px-3 py-2 rounded-md text-sm font-medium transition-colors",
-                            style: "color: var(--secondary-color-4); hover:background-color: var(--primary-color-5);",
-                            aria_label: "Login",
-                            to: Route::Login {},
-                            "Login"
+                        if signed_in().unwrap_or(false) {
+                            // Logout button
+                            button {
+                                class: "px-3 py-2 rounded-md text-sm font-medium transition-colors",
+                                style: "color: var(--secondary-color-4); hover:background-color: var(--primary-color-5);",
+                                aria_label: "Logout",
+                                onclick: handle_logout,
+                                "Logout"
+                            }
+                        } else {
+                            // Login button
+                            Link {
+                                class: "px-3 py-2 rounded-md text-sm font-medium transition-colors",
+                                style: "color: var(--secondary-color-4); hover:background-color: var(--primary-color-5);",
+                                aria_label: "Login",
+                                to: Route::Login {},
+                                "Login"
+                            }
                         }
                     }
                 }
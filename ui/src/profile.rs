@@ -0,0 +1,136 @@
+use crate::not_found::NotFound;
+use dioxus::prelude::*;
+use std::{fmt, str::FromStr};
+
+/// the `@username` segment of a profile url
+#[derive(Clone, Debug, PartialEq)]
+pub struct Handle(pub String);
+
+impl FromStr for Handle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_prefix('@') {
+            Some(v) if !v.is_empty() => Ok(Self(v.to_string())),
+            _ => Err(format!("{s} is not a profile")),
+        }
+    }
+}
+
+impl fmt::Display for Handle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "@{}", self.0)
+    }
+}
+
+async fn fetch_profile(username: &str) -> Option<serde_json::Value> {
+    let url = format!("{}/api/user/@{username}", crate::SERVICE_DOMAIN());
+    let response = reqwest::Client::new().get(&url).send().await.ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+    response.json::<serde_json::Value>().await.ok()
+}
+
+#[component]
+pub fn Profile(handle: Handle) -> Element {
+    // fetched while rendering on the server, so that link previews get the meta tags, it's the
+    // profile as seen by signed out visitors
+    let profile =
+        use_server_future(use_reactive!(|(handle,)| async move { fetch_profile(&handle.0).await }))?;
+    let Some(profile) = profile().flatten() else {
+        return rsx! {
+            NotFound { endpoint: vec![handle.to_string()] }
+        };
+    };
+
+    let field = |name: &str| profile[name].as_str().map(String::from);
+    let username = field("username").unwrap_or_else(|| handle.0.clone());
+    let display_name = field("display_name").unwrap_or_else(|| username.clone());
+    let icon = field("icon").unwrap_or_default();
+    let bio = field("bio").filter(|v| !v.trim().is_empty());
+    let title = format!("{display_name} (@{username})");
+    let description = match &bio {
+        Some(v) if v.chars().count() > 200 => {
+            format!("{}…", v.chars().take(200).collect::<String>())
+        }
+        Some(v) => v.clone(),
+        None => format!("{display_name} on {}", crate::SERVICE_NAME()),
+    };
+    let url = format!("{}/@{username}", crate::SERVICE_DOMAIN());
+
+    rsx! {
+        document::Title { "{title}" }
+        document::Meta { name: "description", content: "{description}" }
+        document::Meta { property: "og:type", content: "profile" }
+        document::Meta { property: "og:site_name", content: "{crate::SERVICE_NAME}" }
+        document::Meta { property: "og:title", content: "{title}" }
+        document::Meta { property: "og:description", content: "{description}" }
+        document::Meta { property: "og:url", content: "{url}" }
+        document::Meta { property: "og:image", content: "{icon}" }
+        document::Meta { property: "profile:username", content: "{username}" }
+        document::Meta { name: "twitter:card", content: "summary" }
+
+        div {
+            class: "max-w-2xl mx-auto w-full",
+
+            // Banner
+            if let Some(banner) = field("banner") {
+                img {
+                    class: "w-full h-40 object-cover",
+                    src: "{banner}",
+                    alt: "",
+                }
+            } else {
+                div {
+                    class: "w-full h-40",
+                    style: "background-color: var(--primary-color-4);",
+                }
+            }
+
+            div {
+                class: "px-6 -mt-12 space-y-4",
+                img {
+                    class: "h-24 w-24 rounded-full border-4 object-cover",
+                    style: "border-color: var(--primary-color);",
+                    src: "{icon}",
+                    alt: "{display_name}",
+                }
+
+                // Names
+                div {
+                    class: "flex flex-col space-y-1",
+                    h1 {
+                        class: "text-2xl font-semibold tracking-tight text-[var(--secondary-color-1)]",
+                        "{display_name}"
+                    }
+                    p {
+                        class: "text-base text-[var(--secondary-color-5)]",
+                        "@{username}"
+                    }
+                }
+
+                if let Some(bio) = bio {
+                    p {
+                        class: "text-base whitespace-pre-wrap text-[var(--secondary-color-2)]",
+                        "{bio}"
+                    }
+                }
+
+                // Details the user made public
+                div {
+                    class: "flex flex-wrap gap-4 text-sm text-[var(--secondary-color-5)]",
+                    if let Some(country) = field("country") {
+                        span { "{country}" }
+                    }
+                    if let Some(gender) = field("gender") {
+                        span { "{gender}" }
+                    }
+                    if let Some(birth_date) = field("birth_date") {
+                        span { "Born {birth_date}" }
+                    }
+                }
+            }
+        }
+    }
+}
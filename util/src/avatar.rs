@@ -0,0 +1,125 @@
+use sha2::{Digest, Sha256};
+use std::sync::OnceLock;
+
+/// where the avatar of a user without an `icon` comes from
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AvatarSource {
+    /// initials or identicon generated from the user id and display name
+    Generated,
+    /// gravatar lookup by email hash, falls back to `Generated`
+    Gravatar,
+}
+
+// `AVATAR_SOURCE`, set once by `crate::init`
+static AVATAR_SOURCE: OnceLock<AvatarSource> = OnceLock::new();
+
+// background colors for the generated avatars
+const PALETTE: [&str; 12] = [
+    "#e57373", "#f06292", "#ba68c8", "#9575cd", "#7986cb", "#64b5f6", "#4db6ac", "#81c784",
+    "#aed581", "#ffb74d", "#ff8a65", "#a1887f",
+];
+
+pub(crate) fn init(source: &str) {
+    let _ = AVATAR_SOURCE.set(match source {
+        "gravatar" => AvatarSource::Gravatar,
+        _ => AvatarSource::Generated,
+    });
+}
+
+pub fn source() -> AvatarSource {
+    *AVATAR_SOURCE.get().expect("util::init must be called before serving avatars")
+}
+
+/// returns the url serving the default avatar of the user with `username`
+pub fn fallback_url(username: &str) -> String {
+    format!("{}/api/user/@{username}/avatar", &*shared::SERVICE_DOMAIN)
+}
+
+/// returns the gravatar url of `email`, `default` is used when gravatar has no image
+pub fn gravatar_url(email: &str, default: &str) -> String {
+    let hash = Sha256::digest(email.trim().to_lowercase().as_bytes());
+    let mut url = reqwest::Url::parse("https://gravatar.com/avatar/").unwrap();
+    url.path_segments_mut().unwrap().pop_if_empty().push(&const_hex::encode(hash));
+    url.query_pairs_mut().append_pair("s", "256").append_pair("d", default);
+    url.to_string()
+}
+
+/// generates a deterministic svg avatar
+///
+/// the initials of `display_name` are drawn over a background picked from `id`,
+/// if the name has no usable initials an identicon derived from `id` is drawn instead
+pub fn generate_svg(id: &uuid::Uuid, display_name: &str) -> String {
+    let hash = Sha256::digest(id.as_bytes());
+    let color = PALETTE[hash[0] as usize % PALETTE.len()];
+    let initials = initials(display_name);
+
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="256" height="256" viewBox="0 0 256 256"><rect width="256" height="256" fill="{color}"/>"#
+    );
+    if initials.is_empty() {
+        // 5x5 grid mirrored on the vertical axis, so only 3 columns are derived from the hash
+        for row in 0..5 {
+            for col in 0..3 {
+                let bit = row * 3 + col;
+                if (hash[1 + bit / 8] >> (bit % 8)) & 1 == 0 {
+                    continue;
+                }
+                for x in [col, 4 - col] {
+                    svg.push_str(&format!(
+                        r##"<rect x="{}" y="{}" width="40" height="40" fill="#ffffff"/>"##,
+                        28 + x * 40,
+                        28 + row * 40
+                    ));
+                }
+            }
+        }
+    } else {
+        svg.push_str(&format!(
+            r##"<text x="50%" y="50%" dy=".35em" text-anchor="middle" font-family="sans-serif" font-size="112" fill="#ffffff">{initials}</text>"##
+        ));
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+// first alphanumeric character of the first two words
+fn initials(display_name: &str) -> String {
+    display_name
+        .split_whitespace()
+        .filter_map(|word| word.chars().find(|c| c.is_ascii_alphanumeric()))
+        .take(2)
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn initials_test() {
+        assert_eq!(initials("sumit modak"), "SM");
+        assert_eq!(initials("  rust  lang  foundation "), "RL");
+        assert_eq!(initials("x"), "X");
+        assert_eq!(initials("-- ++"), "");
+    }
+
+    #[test]
+    fn generated_avatar_is_deterministic() {
+        let id = uuid::Uuid::new_v4();
+        assert_eq!(generate_svg(&id, "Sumit Modak"), generate_svg(&id, "Sumit Modak"));
+        assert_eq!(generate_svg(&id, "__"), generate_svg(&id, "__"));
+        assert!(generate_svg(&id, "Sumit Modak").contains(">SM</text>"));
+        assert!(!generate_svg(&id, "__").contains("<text"));
+    }
+
+    #[test]
+    fn gravatar_url_test() {
+        let url = gravatar_url(" Hello@Example.com ", "identicon");
+        assert!(url.starts_with(&format!(
+            "https://gravatar.com/avatar/{}",
+            const_hex::encode(Sha256::digest(b"hello@example.com"))
+        )));
+        assert!(url.ends_with("?s=256&d=identicon"));
+    }
+}
@@ -0,0 +1,532 @@
+use std::{fmt::Display, path::Path, str::FromStr};
+
+/// file read when `CONFIG_FILE` isn't set, it's optional as long as the environment is complete
+pub const DEFAULT_CONFIG_FILE: &str = "stronghold.toml";
+
+/// typed configuration of the server, read from a TOML file
+///
+/// every value can be overridden by the environment variable noted next to it, so existing
+/// `.env` files keep working without a config file
+#[derive(Clone, Debug, Default, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub service: ServiceConfig,
+    pub database: DatabaseConfig,
+    pub bucket: BucketConfig,
+    pub mail: MailConfig,
+    pub sms: SmsConfig,
+    pub google: GoogleConfig,
+    pub session: SessionConfig,
+    pub cors: CorsConfig,
+    pub username: UsernameConfig,
+}
+
+#[derive(Clone, Debug, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServiceConfig {
+    /// `SERVICE_NAME`
+    pub name: String,
+    /// `SERVICE_DOMAIN`, with the scheme
+    pub domain: String,
+    /// `SECRET_KEY`, signs the session cookies and the links sent by email
+    pub secret_key: String,
+    /// `SIGNING_KEYS_KEY`, encrypts the rotated signing keys stored in the database
+    pub signing_keys_key: String,
+    /// `MAGIC_LINK_LOGIN`
+    pub magic_link_login: bool,
+    /// `AVATAR_SOURCE`, `generated` or `gravatar`
+    pub avatar_source: String,
+    /// `GEOIP_DATABASE`, path to a MaxMind/DB-IP `City` database
+    pub geoip_database: Option<String>,
+    /// `DELETION_GRACE_DAYS`, days a deleted account can be restored for
+    pub deletion_grace_days: u64,
+}
+
+impl Default for ServiceConfig {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            domain: String::new(),
+            secret_key: String::new(),
+            signing_keys_key: String::new(),
+            magic_link_login: false,
+            avatar_source: "generated".to_string(),
+            geoip_database: None,
+            deletion_grace_days: 30,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    /// `DATABASE_URL`
+    pub url: String,
+}
+
+#[derive(Clone, Debug, Default, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BucketConfig {
+    /// `BUCKET_ACCESS_KEY`
+    pub access_key: String,
+    /// `BUCKET_SECRET_KEY`
+    pub secret_key: String,
+    /// `BUCKET_ID`
+    pub id: String,
+    /// `BUCKET_ENDPOINT`
+    pub endpoint: String,
+    /// `BUCKET_NAME`
+    pub name: String,
+    /// `BUCKET_REGION`, only used by Cloudflare R2
+    pub region: String,
+    /// `BUCKET_PUBLIC_URL`
+    pub public_url: String,
+}
+
+#[derive(Clone, Debug, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MailConfig {
+    /// `MAIL_TRANSPORT`, `smtp`, `file`, `log` or `memory`
+    pub transport: String,
+    /// `MAIL_FILE_DIR`, used by the `file` transport
+    pub file_dir: String,
+    /// `SMTP_HOST`
+    pub smtp_host: String,
+    /// `SMTP_KEY`
+    pub smtp_key: String,
+    /// `NOREPLY_EMAIL`, also the SMTP username
+    pub noreply_email: String,
+    /// `MAIL_TEMPLATES_DIR`
+    pub templates_dir: Option<String>,
+}
+
+impl Default for MailConfig {
+    fn default() -> Self {
+        Self {
+            transport: "smtp".to_string(),
+            file_dir: "mail".to_string(),
+            smtp_host: String::new(),
+            smtp_key: String::new(),
+            noreply_email: String::new(),
+            templates_dir: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SmsConfig {
+    /// `SMS_PROVIDER`, `log` or `twilio`
+    pub provider: String,
+    /// `TWILIO_ACCOUNT_SID`
+    pub twilio_account_sid: String,
+    /// `TWILIO_AUTH_TOKEN`
+    pub twilio_auth_token: String,
+    /// `TWILIO_FROM_NUMBER`
+    pub twilio_from_number: String,
+    /// `TWILIO_API_URL`
+    pub twilio_api_url: String,
+}
+
+impl Default for SmsConfig {
+    fn default() -> Self {
+        Self {
+            provider: "log".to_string(),
+            twilio_account_sid: String::new(),
+            twilio_auth_token: String::new(),
+            twilio_from_number: String::new(),
+            twilio_api_url: "https://api.twilio.com".to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GoogleConfig {
+    /// `GOOGLE_CLIENT_ID`
+    pub client_id: String,
+    /// `GOOGLE_CLIENT_SECRET`
+    pub client_secret: String,
+}
+
+#[derive(Clone, Debug, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SessionConfig {
+    /// `SESSION_{FIELD}`
+    pub user: PolicyOverrides,
+    /// `SESSION_ADMIN_{FIELD}`
+    pub admin: PolicyOverrides,
+    /// `NEW_SIGN_IN_SIGNALS`
+    pub new_sign_in_signals: String,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            user: PolicyOverrides::default(),
+            admin: PolicyOverrides::default(),
+            new_sign_in_signals: "device,country".to_string(),
+        }
+    }
+}
+
+/// fields of `SessionPolicy` replacing the defaults of a role, in seconds
+#[derive(Clone, Copy, Debug, Default, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PolicyOverrides {
+    pub ttl: Option<u64>,
+    pub remember_ttl: Option<u64>,
+    pub idle_timeout: Option<u64>,
+    pub max_lifetime: Option<u64>,
+}
+
+#[derive(Clone, Debug, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CorsConfig {
+    /// `CORS_ALLOWED_ORIGINS`, comma separated in the environment
+    pub allowed_origins: Vec<String>,
+    /// `CORS_ALLOW_CREDENTIALS`
+    pub allow_credentials: bool,
+    /// `CORS_ALLOWED_METHODS`, comma separated in the environment
+    pub allowed_methods: Vec<String>,
+    /// `CORS_MAX_AGE`, in seconds
+    pub max_age: u64,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_origins: Vec::new(),
+            allow_credentials: true,
+            allowed_methods: vec!["GET".to_string(), "POST".to_string()],
+            max_age: 600,
+        }
+    }
+}
+
+#[derive(Clone, Debug, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UsernameConfig {
+    /// `USERNAME_REUSE_COOLDOWN_DAYS`, days a released username stays with its previous owner
+    pub reuse_cooldown_days: u64,
+    /// `USERNAME_RENAME_INTERVAL_DAYS`, days a user has to wait between renames
+    pub rename_interval_days: u64,
+    /// `RESERVED_USERNAMES`, comma separated in the environment, in addition to
+    /// `shared::validation::RESERVED_USERNAMES`
+    pub reserved: Vec<String>,
+}
+
+impl Default for UsernameConfig {
+    fn default() -> Self {
+        Self { reuse_cooldown_days: 90, rename_interval_days: 30, reserved: Vec::new() }
+    }
+}
+
+impl Config {
+    /// parses a TOML document, missing values keep their defaults
+    pub fn from_toml(s: &str) -> Result<Self, String> {
+        toml::from_str(s).map_err(|e| e.message().to_string())
+    }
+
+    // reads the config file and applies the environment overrides, without validating
+    fn read() -> (Self, Vec<String>) {
+        let mut errors = Vec::new();
+        let path = std::env::var("CONFIG_FILE").ok();
+        let mut config =
+            match std::fs::read_to_string(path.as_deref().unwrap_or(DEFAULT_CONFIG_FILE)) {
+                Ok(s) => Self::from_toml(&s).unwrap_or_else(|e| {
+                    errors.push(format!("Invalid config file: {e}"));
+                    Self::default()
+                }),
+                // the default file is optional, an explicitly selected one isn't
+                Err(e) => {
+                    if let Some(path) = path {
+                        errors.push(format!("Failed to read CONFIG_FILE {path}: {e}"));
+                    }
+                    Self::default()
+                }
+            };
+        config.apply_env(&mut errors);
+        (config, errors)
+    }
+
+    fn apply_env(&mut self, errors: &mut Vec<String>) {
+        let service = &mut self.service;
+        env_string("SERVICE_NAME", &mut service.name);
+        env_string("SERVICE_DOMAIN", &mut service.domain);
+        env_string("SECRET_KEY", &mut service.secret_key);
+        env_string("SIGNING_KEYS_KEY", &mut service.signing_keys_key);
+        env_parsed("MAGIC_LINK_LOGIN", &mut service.magic_link_login, errors);
+        env_string("AVATAR_SOURCE", &mut service.avatar_source);
+        env_optional("GEOIP_DATABASE", &mut service.geoip_database);
+        env_parsed("DELETION_GRACE_DAYS", &mut service.deletion_grace_days, errors);
+
+        env_string("DATABASE_URL", &mut self.database.url);
+
+        let bucket = &mut self.bucket;
+        env_string("BUCKET_ACCESS_KEY", &mut bucket.access_key);
+        env_string("BUCKET_SECRET_KEY", &mut bucket.secret_key);
+        env_string("BUCKET_ID", &mut bucket.id);
+        env_string("BUCKET_ENDPOINT", &mut bucket.endpoint);
+        env_string("BUCKET_NAME", &mut bucket.name);
+        env_string("BUCKET_REGION", &mut bucket.region);
+        env_string("BUCKET_PUBLIC_URL", &mut bucket.public_url);
+
+        let mail = &mut self.mail;
+        env_string("MAIL_TRANSPORT", &mut mail.transport);
+        env_string("MAIL_FILE_DIR", &mut mail.file_dir);
+        env_string("SMTP_HOST", &mut mail.smtp_host);
+        env_string("SMTP_KEY", &mut mail.smtp_key);
+        env_string("NOREPLY_EMAIL", &mut mail.noreply_email);
+        env_optional("MAIL_TEMPLATES_DIR", &mut mail.templates_dir);
+
+        let sms = &mut self.sms;
+        env_string("SMS_PROVIDER", &mut sms.provider);
+        env_string("TWILIO_ACCOUNT_SID", &mut sms.twilio_account_sid);
+        env_string("TWILIO_AUTH_TOKEN", &mut sms.twilio_auth_token);
+        env_string("TWILIO_FROM_NUMBER", &mut sms.twilio_from_number);
+        env_string("TWILIO_API_URL", &mut sms.twilio_api_url);
+
+        env_string("GOOGLE_CLIENT_ID", &mut self.google.client_id);
+        env_string("GOOGLE_CLIENT_SECRET", &mut self.google.client_secret);
+
+        for (prefix, policy) in
+            [("SESSION", &mut self.session.user), ("SESSION_ADMIN", &mut self.session.admin)]
+        {
+            env_seconds(&format!("{prefix}_TTL"), &mut policy.ttl, errors);
+            env_seconds(&format!("{prefix}_REMEMBER_TTL"), &mut policy.remember_ttl, errors);
+            env_seconds(&format!("{prefix}_IDLE_TIMEOUT"), &mut policy.idle_timeout, errors);
+            env_seconds(&format!("{prefix}_MAX_LIFETIME"), &mut policy.max_lifetime, errors);
+        }
+        env_string("NEW_SIGN_IN_SIGNALS", &mut self.session.new_sign_in_signals);
+
+        let cors = &mut self.cors;
+        env_list("CORS_ALLOWED_ORIGINS", &mut cors.allowed_origins);
+        env_parsed("CORS_ALLOW_CREDENTIALS", &mut cors.allow_credentials, errors);
+        env_list("CORS_ALLOWED_METHODS", &mut cors.allowed_methods);
+        env_parsed("CORS_MAX_AGE", &mut cors.max_age, errors);
+
+        let username = &mut self.username;
+        env_parsed("USERNAME_REUSE_COOLDOWN_DAYS", &mut username.reuse_cooldown_days, errors);
+        env_parsed("USERNAME_RENAME_INTERVAL_DAYS", &mut username.rename_interval_days, errors);
+        env_list("RESERVED_USERNAMES", &mut username.reserved);
+    }
+
+    /// returns every missing or invalid value, the config is usable if it's empty
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        let mut required = |value: &str, name: &str| {
+            if value.trim().is_empty() {
+                errors.push(format!("{name} is not set"));
+            }
+        };
+
+        required(&self.service.name, "service.name (SERVICE_NAME)");
+        required(&self.service.domain, "service.domain (SERVICE_DOMAIN)");
+        required(&self.service.secret_key, "service.secret_key (SECRET_KEY)");
+        required(&self.service.signing_keys_key, "service.signing_keys_key (SIGNING_KEYS_KEY)");
+        required(&self.database.url, "database.url (DATABASE_URL)");
+        required(&self.bucket.access_key, "bucket.access_key (BUCKET_ACCESS_KEY)");
+        required(&self.bucket.secret_key, "bucket.secret_key (BUCKET_SECRET_KEY)");
+        required(&self.bucket.id, "bucket.id (BUCKET_ID)");
+        required(&self.bucket.endpoint, "bucket.endpoint (BUCKET_ENDPOINT)");
+        required(&self.bucket.name, "bucket.name (BUCKET_NAME)");
+        required(&self.bucket.public_url, "bucket.public_url (BUCKET_PUBLIC_URL)");
+        required(&self.google.client_id, "google.client_id (GOOGLE_CLIENT_ID)");
+        required(&self.google.client_secret, "google.client_secret (GOOGLE_CLIENT_SECRET)");
+        if self.mail.transport == "smtp" {
+            required(&self.mail.smtp_host, "mail.smtp_host (SMTP_HOST)");
+            required(&self.mail.smtp_key, "mail.smtp_key (SMTP_KEY)");
+            required(&self.mail.noreply_email, "mail.noreply_email (NOREPLY_EMAIL)");
+        }
+        if self.sms.provider == "twilio" {
+            required(&self.sms.twilio_account_sid, "sms.twilio_account_sid (TWILIO_ACCOUNT_SID)");
+            required(&self.sms.twilio_auth_token, "sms.twilio_auth_token (TWILIO_AUTH_TOKEN)");
+            required(&self.sms.twilio_from_number, "sms.twilio_from_number (TWILIO_FROM_NUMBER)");
+        }
+
+        let domain = &self.service.domain;
+        if !domain.is_empty() && !is_http_url(domain) {
+            errors.push(format!("service.domain (SERVICE_DOMAIN) is not an http(s) url: {domain}"));
+        }
+        let url = &self.database.url;
+        if !url.is_empty() && !url.starts_with("postgres://") && !url.starts_with("postgresql://") {
+            errors.push("database.url (DATABASE_URL) is not a postgres url".to_string());
+        }
+        if !["generated", "gravatar"].contains(&self.service.avatar_source.as_str()) {
+            errors.push(format!(
+                "Unknown service.avatar_source (AVATAR_SOURCE): {}",
+                self.service.avatar_source
+            ));
+        }
+        if let Some(path) = &self.service.geoip_database
+            && !Path::new(path).is_file()
+        {
+            errors.push(format!("service.geoip_database (GEOIP_DATABASE) not found: {path}"));
+        }
+        if self.service.deletion_grace_days == 0 {
+            errors.push(
+                "service.deletion_grace_days (DELETION_GRACE_DAYS) must be greater than 0"
+                    .to_string(),
+            );
+        }
+
+        if !["smtp", "file", "log", "memory"].contains(&self.mail.transport.as_str()) {
+            errors.push(format!("Unknown mail.transport (MAIL_TRANSPORT): {}", self.mail.transport));
+        }
+        let noreply = &self.mail.noreply_email;
+        if !noreply.is_empty() && noreply.parse::<lettre::message::Mailbox>().is_err() {
+            errors.push(format!("mail.noreply_email (NOREPLY_EMAIL) is not an email: {noreply}"));
+        }
+        if let Some(dir) = &self.mail.templates_dir
+            && !Path::new(dir).is_dir()
+        {
+            errors.push(format!("mail.templates_dir (MAIL_TEMPLATES_DIR) not found: {dir}"));
+        }
+        if !["log", "twilio"].contains(&self.sms.provider.as_str()) {
+            errors.push(format!("Unknown sms.provider (SMS_PROVIDER): {}", self.sms.provider));
+        }
+
+        for (role, policy) in [("user", &self.session.user), ("admin", &self.session.admin)] {
+            for (field, value) in [
+                ("ttl", policy.ttl),
+                ("remember_ttl", policy.remember_ttl),
+                ("max_lifetime", policy.max_lifetime),
+            ] {
+                if value == Some(0) {
+                    errors.push(format!("session.{role}.{field} must be greater than 0"));
+                }
+            }
+        }
+        for signal in self.session.new_sign_in_signals.split(',').map(str::trim) {
+            if !["device", "ip", "country", "none", ""].contains(&signal) {
+                errors.push(format!(
+                    "Unknown sign-in signal in session.new_sign_in_signals (NEW_SIGN_IN_SIGNALS): {signal}"
+                ));
+            }
+        }
+
+        for origin in &self.cors.allowed_origins {
+            if !is_http_url(origin) || axum::http::HeaderValue::from_str(origin).is_err() {
+                errors.push(format!(
+                    "Invalid origin in cors.allowed_origins (CORS_ALLOWED_ORIGINS): {origin}"
+                ));
+            }
+        }
+        for method in &self.cors.allowed_methods {
+            if method.trim().to_uppercase().parse::<axum::http::Method>().is_err() {
+                errors.push(format!(
+                    "Invalid method in cors.allowed_methods (CORS_ALLOWED_METHODS): {method}"
+                ));
+            }
+        }
+        errors
+    }
+}
+
+fn is_http_url(s: &str) -> bool {
+    reqwest::Url::parse(s).is_ok_and(|url| matches!(url.scheme(), "http" | "https"))
+}
+
+fn env_string(key: &str, field: &mut String) {
+    if let Ok(v) = std::env::var(key) {
+        *field = v;
+    }
+}
+
+fn env_optional(key: &str, field: &mut Option<String>) {
+    if let Ok(v) = std::env::var(key) {
+        *field = (!v.is_empty()).then_some(v);
+    }
+}
+
+fn env_list(key: &str, field: &mut Vec<String>) {
+    if let Ok(v) = std::env::var(key) {
+        *field = v
+            .split(',')
+            .map(|v| v.trim().trim_end_matches('/').to_string())
+            .filter(|v| !v.is_empty())
+            .collect();
+    }
+}
+
+fn env_parsed<T: FromStr>(key: &str, field: &mut T, errors: &mut Vec<String>)
+where
+    T::Err: Display,
+{
+    if let Ok(v) = std::env::var(key) {
+        match v.trim().parse() {
+            Ok(v) => *field = v,
+            Err(e) => errors.push(format!("Invalid {key}: {e}")),
+        }
+    }
+}
+
+fn env_seconds(key: &str, field: &mut Option<u64>, errors: &mut Vec<String>) {
+    let mut seconds = 0;
+    if std::env::var(key).is_ok() {
+        let count = errors.len();
+        env_parsed(key, &mut seconds, errors);
+        if errors.len() == count {
+            *field = Some(seconds);
+        }
+    }
+}
+
+/// reads the config file (`CONFIG_FILE`, defaults to `stronghold.toml`), applies the
+/// environment overrides and validates the result
+///
+/// every missing or invalid value is returned together, the binaries stop on any of them before
+/// passing the config on
+pub fn load() -> Result<Config, Vec<String>> {
+    let (config, mut errors) = Config::read();
+    errors.extend(config.validate());
+    if errors.is_empty() { Ok(config) } else { Err(errors) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_validation_test() {
+        let config = Config::from_toml(
+            r#"
+            [service]
+            name = "Stronghold"
+            domain = "localhost:3000"
+            secret_key = "secret"
+
+            [mail]
+            transport = "pigeon"
+
+            [session.admin]
+            ttl = 0
+
+            [cors]
+            allowed_origins = ["https://app.example.com"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.cors.allowed_methods, ["GET", "POST"]);
+        assert_eq!(config.session.new_sign_in_signals, "device,country");
+
+        // every problem is reported at once
+        let errors = config.validate();
+        for expected in [
+            "database.url (DATABASE_URL) is not set",
+            "service.signing_keys_key (SIGNING_KEYS_KEY) is not set",
+            "bucket.id (BUCKET_ID) is not set",
+            "google.client_id (GOOGLE_CLIENT_ID) is not set",
+            "service.domain (SERVICE_DOMAIN) is not an http(s) url: localhost:3000",
+            "Unknown mail.transport (MAIL_TRANSPORT): pigeon",
+            "session.admin.ttl must be greater than 0",
+        ] {
+            assert!(errors.iter().any(|e| e == expected), "missing {expected:?} in {errors:?}");
+        }
+        // smtp values are only required by the smtp transport
+        assert!(!errors.iter().any(|e| e.contains("SMTP_HOST")));
+
+        assert!(Config::from_toml("[service]\nport = 3000").is_err());
+    }
+}
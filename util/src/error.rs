@@ -5,6 +5,8 @@ use shared::validation::ValidationError;
 pub enum AppError {
     BadReq(&'static str),
     Unauthorized(&'static str),
+    Forbidden(&'static str),
+    TooManyRequests(&'static str),
     NotFound,
     Validation(ValidationError),
     InvalidOTP,
@@ -28,6 +30,12 @@ impl axum::response::IntoResponse for AppError {
             Self::Unauthorized(e) => {
                 (StatusCode::UNAUTHORIZED, JsonMsg::new(e)).into_response()
             }
+            Self::Forbidden(e) => {
+                (StatusCode::FORBIDDEN, JsonMsg::new(e)).into_response()
+            }
+            Self::TooManyRequests(e) => {
+                (StatusCode::TOO_MANY_REQUESTS, JsonMsg::new(e)).into_response()
+            }
             Self::NotFound => {
                 (StatusCode::NOT_FOUND).into_response()
             }
@@ -67,6 +67,23 @@ pub fn hex_64(secret: &str) -> String {
     const_hex::encode(result)
 }
 
+/// signs `value` with `SECRET_KEY`, the digest is url safe
+pub fn signature(value: &str) -> String {
+    let mut mac = Hmac::<sha2::Sha256>::new_from_slice(crate::secret_key()).unwrap();
+    mac.update(value.as_bytes());
+    base64::prelude::BASE64_URL_SAFE_NO_PAD.encode(mac.finalize().into_bytes())
+}
+
+/// checks a digest returned by `signature` in constant time
+pub fn verify_signature(value: &str, signature: &str) -> bool {
+    let Ok(digest) = base64::prelude::BASE64_URL_SAFE_NO_PAD.decode(signature) else {
+        return false;
+    };
+    let mut mac = Hmac::<sha2::Sha256>::new_from_slice(crate::secret_key()).unwrap();
+    mac.update(value.as_bytes());
+    mac.verify_slice(&digest).is_ok()
+}
+
 // Generate random string for state and nonce
 pub fn random_string(length: usize) -> String {
     const CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
@@ -120,6 +137,15 @@ mod tests {
         dbg!(&r);
     }
 
+    #[test]
+    fn signature_test() {
+        crate::init_for_tests();
+        let sig = signature("magic_link:abc:device");
+        assert!(verify_signature("magic_link:abc:device", &sig));
+        assert!(!verify_signature("magic_link:abc:other", &sig));
+        assert!(!verify_signature("magic_link:abc:device", "not base64!"));
+    }
+
     #[test]
     fn generate_rand_str_test() {
         assert_eq!(128, random_string(128).len());
@@ -0,0 +1,80 @@
+use maxminddb::{Reader, geoip2};
+use std::{collections::BTreeMap, net::IpAddr, sync::OnceLock};
+
+// opened once from `GEOIP_DATABASE` (a MaxMind/DB-IP `City` database) by `crate::init`, lookups
+// are skipped when it isn't set
+static READER: OnceLock<Reader<Vec<u8>>> = OnceLock::new();
+
+pub(crate) fn init(path: Option<&str>) -> Result<(), String> {
+    let Some(path) = path else {
+        return Ok(());
+    };
+    let reader = Reader::open_readfile(path)
+        .map_err(|e| format!("Failed to open GeoIP database {path}: {e:?}"))?;
+    let _ = READER.set(reader);
+    Ok(())
+}
+
+/// returns the approximate location of `ip` as `City, Country` (or just `Country`)
+pub fn locate(ip: IpAddr) -> Option<String> {
+    let city = lookup(ip)?;
+    let country = city.country.and_then(|c| english(c.names));
+    match (city.city.and_then(|c| english(c.names)), country) {
+        (Some(city), Some(country)) => Some(format!("{city}, {country}")),
+        (city, country) => country.or(city),
+    }
+}
+
+/// returns the ISO code of the country of `ip`
+pub fn country(ip: IpAddr) -> Option<String> {
+    lookup(ip)?.country?.iso_code.map(|v| v.to_string())
+}
+
+fn lookup(ip: IpAddr) -> Option<geoip2::City<'static>> {
+    if !is_public(ip) {
+        return None;
+    }
+    READER.get()?.lookup(ip).ok()
+}
+
+fn english(names: Option<BTreeMap<&str, &str>>) -> Option<String> {
+    names.and_then(|n| n.get("en").map(|v| v.to_string()))
+}
+
+// private, loopback and link-local addresses can't be located
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            !(v4.is_private()
+                || v4.is_loopback()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_broadcast())
+        }
+        IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+            Some(v4) => is_public(IpAddr::V4(v4)),
+            // unique local (fc00::/7) and link-local (fe80::/10)
+            None => {
+                !(v6.is_loopback()
+                    || v6.is_unspecified()
+                    || (v6.segments()[0] & 0xfe00) == 0xfc00
+                    || (v6.segments()[0] & 0xffc0) == 0xfe80)
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn private_addresses_are_skipped() {
+        for ip in ["127.0.0.1", "10.1.2.3", "192.168.0.10", "::1", "fd00::1", "::ffff:10.0.0.1"] {
+            assert!(!is_public(ip.parse().unwrap()), "{ip}");
+            assert_eq!(locate(ip.parse().unwrap()), None);
+        }
+        assert!(is_public("8.8.8.8".parse().unwrap()));
+        assert!(is_public("2001:4860:4860::8888".parse().unwrap()));
+    }
+}
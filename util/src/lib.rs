@@ -1,10 +1,49 @@
+pub mod avatar;
+pub mod config;
 mod error;
 pub mod generate;
+pub mod geoip;
 pub mod mail;
 pub mod oauth;
 pub mod session;
+pub mod sms;
+pub mod user_agent;
 
 pub use error::AppError;
 
-pub static SECRET_KEY: std::sync::LazyLock<Vec<u8>> =
-    std::sync::LazyLock::new(|| Vec::from(std::env::var("SECRET_KEY").unwrap()));
+// `service.secret_key`, set by `init`
+static SECRET_KEY: std::sync::OnceLock<Vec<u8>> = std::sync::OnceLock::new();
+
+/// sets up what this crate and `shared` keep for the whole process from the validated `config`:
+/// the secret key, the session policies, the sign-in signals, the avatars, the GeoIP database,
+/// the OAuth clients, the mail transport and the sms provider
+///
+/// must be called once at startup, before serving anything, returns the first part that can't be
+/// set up
+pub fn init(config: &config::Config) -> Result<(), String> {
+    let service = &config.service;
+    if !shared::set_service(service.name.clone(), service.domain.clone())
+        || SECRET_KEY.set(Vec::from(service.secret_key.as_str())).is_err()
+    {
+        return Err("util::init was already called".to_string());
+    }
+    session::init(&config.session);
+    avatar::init(&service.avatar_source);
+    geoip::init(service.geoip_database.as_deref())?;
+    oauth::init(&config.google);
+    mail::init(&config.mail, &service.name)?;
+    sms::init(&config.sms)?;
+    Ok(())
+}
+
+// the key signing the session cookies and the links sent by email
+pub(crate) fn secret_key() -> &'static [u8] {
+    SECRET_KEY.get().expect("util::init must be called before signing anything")
+}
+
+// sets the parts of `init` the unit tests rely on, without a complete config
+#[cfg(test)]
+pub(crate) fn init_for_tests() {
+    let _ = SECRET_KEY.set(b"test-secret".to_vec());
+    session::init(&config::SessionConfig::default());
+}
@@ -0,0 +1,232 @@
+use super::Template;
+
+/// locales that have a complete set of built-in templates
+pub(super) const LOCALES: [&str; 2] = ["en", "es"];
+
+pub(super) struct Builtin {
+    pub subject: &'static str,
+    pub text: &'static str,
+    pub html: &'static str,
+}
+
+pub(super) const LAYOUT: &str = r#"<!DOCTYPE html>
+<html lang="{{locale}}">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{{subject}}</title>
+</head>
+<body style="margin:0;padding:24px;background-color:#f4f4f5;font-family:-apple-system,'Segoe UI',Roboto,Helvetica,Arial,sans-serif;color:#18181b;">
+<div style="max-width:560px;margin:0 auto;padding:32px;background-color:#ffffff;border-radius:8px;">
+<p style="margin:0 0 24px;font-size:20px;font-weight:600;"><a href="{{service_domain}}" style="color:#18181b;text-decoration:none;">{{service_name}}</a></p>
+{{content}}
+</div>
+<p style="max-width:560px;margin:16px auto 0;font-size:12px;color:#71717a;text-align:center;"><a href="{{service_domain}}" style="color:#71717a;">{{service_domain}}</a></p>
+</body>
+</html>
+"#;
+
+pub(super) fn get(locale: &str, template: Template) -> Option<Builtin> {
+    let builtin = match (locale, template) {
+        ("en", Template::Otp) => Builtin {
+            subject: "Your {{service_name}} verification code",
+            text: "Confirm your email address\n\n{{otp}}\n\nIf you didn't request this code, you can safely ignore this email.\n\nThanks,\n{{service_name}}\n",
+            html: r#"<h1 style="font-size:22px;">Confirm your email address</h1>
+<p>Enter this code to continue:</p>
+<p style="font-size:32px;font-weight:700;letter-spacing:8px;">{{otp}}</p>
+<p>If you didn't request this code, you can safely ignore this email.</p>"#,
+        },
+        ("en", Template::Welcome) => Builtin {
+            subject: "Welcome to {{service_name}}",
+            text: "Your email {{email}} has been verified successfully.\n\nThanks,\n{{service_name}}\n",
+            html: r#"<h1 style="font-size:22px;">Welcome to {{service_name}}</h1>
+<p>Your email <strong>{{email}}</strong> has been verified successfully.</p>"#,
+        },
+        ("en", Template::PasswordReset) => Builtin {
+            subject: "{{service_name}} password reset request",
+            text: "If you requested a password reset for {{email}}, open this link:\n{{link}}\n\nIf you didn't make the request, please ignore this email.\n\nThanks,\n{{service_name}}\n",
+            html: r#"<h1 style="font-size:22px;">Reset your password?</h1>
+<p>If you requested a password reset for <strong>{{email}}</strong>, press the button below.</p>
+<p><a href="{{link}}" style="display:inline-block;padding:12px 20px;background-color:#18181b;color:#ffffff;border-radius:6px;text-decoration:none;">Reset password</a></p>
+<p>If you didn't make the request, please ignore this email.</p>"#,
+        },
+        ("en", Template::PasswordChanged) => Builtin {
+            subject: "Your {{service_name}} password has been changed",
+            text: "Your password for {{email}} has been changed.\n\nIf you didn't make this change, reset your password immediately.\n\nThanks,\n{{service_name}}\n",
+            html: r#"<h1 style="font-size:22px;">Your password has been changed</h1>
+<p>Your password for <strong>{{email}}</strong> has been changed.</p>
+<p>If you didn't make this change, reset your password immediately.</p>"#,
+        },
+        ("en", Template::EmailChanged) => Builtin {
+            subject: "Your {{service_name}} email has been changed",
+            text: "The email of your account has been changed from {{old_email}} to {{new_email}}.\n\nIf you didn't make this change, contact us immediately.\n\nThanks,\n{{service_name}}\n",
+            html: r#"<h1 style="font-size:22px;">Your email has been changed</h1>
+<p>The email of your account has been changed from <strong>{{old_email}}</strong> to <strong>{{new_email}}</strong>.</p>
+<p>If you didn't make this change, contact us immediately.</p>"#,
+        },
+        ("en", Template::NewSignIn) => Builtin {
+            subject: "New sign-in to your {{service_name}} account",
+            text: "Your account was signed in from a new device.\n\nDevice: {{device}}\nLocation: {{location}}\nTime: {{time}}\n\nIf this wasn't you, open this link to sign the device out and reset your password:\n{{link}}\n\nThanks,\n{{service_name}}\n",
+            html: r#"<h1 style="font-size:22px;">New sign-in to your account</h1>
+<p>Your account was signed in from a new device.</p>
+<p>Device: <strong>{{device}}</strong><br>Location: <strong>{{location}}</strong><br>Time: <strong>{{time}}</strong></p>
+<p><a href="{{link}}" style="display:inline-block;padding:12px 20px;background-color:#b91c1c;color:#ffffff;border-radius:6px;text-decoration:none;">This wasn't me</a></p>"#,
+        },
+
+        ("en", Template::MagicLink) => Builtin {
+            subject: "Sign in to {{service_name}}",
+            text: "Open this link to sign in as {{email}}:\n{{link}}\n\nThe link works once, expires in 15 minutes and only on the browser that requested it.\nIf you didn't request it, you can safely ignore this email.\n\nThanks,\n{{service_name}}\n",
+            html: r#"<h1 style="font-size:22px;">Sign in to {{service_name}}</h1>
+<p>Press the button below to sign in as <strong>{{email}}</strong>.</p>
+<p><a href="{{link}}" style="display:inline-block;padding:12px 20px;background-color:#18181b;color:#ffffff;border-radius:6px;text-decoration:none;">Sign in</a></p>
+<p>The link works once, expires in 15 minutes and only on the browser that requested it. If you didn't request it, you can safely ignore this email.</p>"#,
+        },
+
+        ("en", Template::LoginCode) => Builtin {
+            subject: "Your {{service_name}} login code",
+            text: "Sign in to {{service_name}}\n\n{{otp}}\n\nThe code expires in 10 minutes. If you didn't try to sign in, you can safely ignore this email.\n\nThanks,\n{{service_name}}\n",
+            html: r#"<h1 style="font-size:22px;">Sign in to {{service_name}}</h1>
+<p>Enter this code to sign in:</p>
+<p style="font-size:32px;font-weight:700;letter-spacing:8px;">{{otp}}</p>
+<p>The code expires in 10 minutes. If you didn't try to sign in, you can safely ignore this email.</p>"#,
+        },
+
+        ("en", Template::DataExport) => Builtin {
+            subject: "Your {{service_name}} data export is ready",
+            text: "The copy of your {{service_name}} data you requested is ready.\n\nDownload it while signed in:\n{{link}}\n\nThe link expires in 24 hours. If you didn't request it, change your password immediately.\n\nThanks,\n{{service_name}}\n",
+            html: r#"<h1 style="font-size:22px;">Your data export is ready</h1>
+<p>The copy of your {{service_name}} data you requested is ready. Download it while signed in.</p>
+<p><a href="{{link}}" style="display:inline-block;padding:12px 20px;background-color:#18181b;color:#ffffff;border-radius:6px;text-decoration:none;">Download my data</a></p>
+<p>The link expires in 24 hours. If you didn't request it, change your password immediately.</p>"#,
+        },
+
+        ("en", Template::DataExportFailed) => Builtin {
+            subject: "Your {{service_name}} data export couldn't be prepared",
+            text: "We couldn't prepare the copy of your {{service_name}} data you requested.\n\nPlease request it again from your settings in a while.\n\nThanks,\n{{service_name}}\n",
+            html: r#"<h1 style="font-size:22px;">Your data export couldn't be prepared</h1>
+<p>We couldn't prepare the copy of your {{service_name}} data you requested.</p>
+<p>Please request it again from your settings in a while.</p>"#,
+        },
+
+        ("en", Template::AccountDeletion) => Builtin {
+            subject: "Your {{service_name}} account will be deleted",
+            text: "Your {{service_name}} account {{email}} is scheduled for deletion and will be permanently removed on {{date}}.\n\nUntil then you can restore it by signing in again or by opening this link:\n{{link}}\n\nIf you didn't delete your account, restore it and change your password immediately.\n\nThanks,\n{{service_name}}\n",
+            html: r#"<h1 style="font-size:22px;">Your account will be deleted</h1>
+<p>Your {{service_name}} account <strong>{{email}}</strong> is scheduled for deletion and will be permanently removed on <strong>{{date}}</strong>.</p>
+<p>Until then you can restore it by signing in again or with the button below.</p>
+<p><a href="{{link}}" style="display:inline-block;padding:12px 20px;background-color:#18181b;color:#ffffff;border-radius:6px;text-decoration:none;">Restore my account</a></p>
+<p>If you didn't delete your account, restore it and change your password immediately.</p>"#,
+        },
+
+        ("en", Template::ReauthCode) => Builtin {
+            subject: "Your {{service_name}} verification code",
+            text: "Confirm it's you\n\n{{otp}}\n\nEnter this code to continue changing your account. The code expires in 10 minutes.\nIf you didn't request it, change your password immediately.\n\nThanks,\n{{service_name}}\n",
+            html: r#"<h1 style="font-size:22px;">Confirm it's you</h1>
+<p>Enter this code to continue changing your account:</p>
+<p style="font-size:32px;font-weight:700;letter-spacing:8px;">{{otp}}</p>
+<p>The code expires in 10 minutes. If you didn't request it, change your password immediately.</p>"#,
+        },
+
+        ("es", Template::Otp) => Builtin {
+            subject: "Tu código de verificación de {{service_name}}",
+            text: "Confirma tu dirección de correo\n\n{{otp}}\n\nSi no solicitaste este código, puedes ignorar este correo.\n\nGracias,\n{{service_name}}\n",
+            html: r#"<h1 style="font-size:22px;">Confirma tu dirección de correo</h1>
+<p>Introduce este código para continuar:</p>
+<p style="font-size:32px;font-weight:700;letter-spacing:8px;">{{otp}}</p>
+<p>Si no solicitaste este código, puedes ignorar este correo.</p>"#,
+        },
+        ("es", Template::Welcome) => Builtin {
+            subject: "Te damos la bienvenida a {{service_name}}",
+            text: "Tu correo {{email}} se ha verificado correctamente.\n\nGracias,\n{{service_name}}\n",
+            html: r#"<h1 style="font-size:22px;">Te damos la bienvenida a {{service_name}}</h1>
+<p>Tu correo <strong>{{email}}</strong> se ha verificado correctamente.</p>"#,
+        },
+        ("es", Template::PasswordReset) => Builtin {
+            subject: "Solicitud de restablecimiento de contraseña de {{service_name}}",
+            text: "Si solicitaste restablecer la contraseña de {{email}}, abre este enlace:\n{{link}}\n\nSi no hiciste la solicitud, ignora este correo.\n\nGracias,\n{{service_name}}\n",
+            html: r#"<h1 style="font-size:22px;">¿Restablecer tu contraseña?</h1>
+<p>Si solicitaste restablecer la contraseña de <strong>{{email}}</strong>, pulsa el botón de abajo.</p>
+<p><a href="{{link}}" style="display:inline-block;padding:12px 20px;background-color:#18181b;color:#ffffff;border-radius:6px;text-decoration:none;">Restablecer contraseña</a></p>
+<p>Si no hiciste la solicitud, ignora este correo.</p>"#,
+        },
+        ("es", Template::PasswordChanged) => Builtin {
+            subject: "Tu contraseña de {{service_name}} ha cambiado",
+            text: "La contraseña de {{email}} ha cambiado.\n\nSi no hiciste este cambio, restablece tu contraseña de inmediato.\n\nGracias,\n{{service_name}}\n",
+            html: r#"<h1 style="font-size:22px;">Tu contraseña ha cambiado</h1>
+<p>La contraseña de <strong>{{email}}</strong> ha cambiado.</p>
+<p>Si no hiciste este cambio, restablece tu contraseña de inmediato.</p>"#,
+        },
+        ("es", Template::EmailChanged) => Builtin {
+            subject: "Tu correo de {{service_name}} ha cambiado",
+            text: "El correo de tu cuenta ha cambiado de {{old_email}} a {{new_email}}.\n\nSi no hiciste este cambio, contáctanos de inmediato.\n\nGracias,\n{{service_name}}\n",
+            html: r#"<h1 style="font-size:22px;">Tu correo ha cambiado</h1>
+<p>El correo de tu cuenta ha cambiado de <strong>{{old_email}}</strong> a <strong>{{new_email}}</strong>.</p>
+<p>Si no hiciste este cambio, contáctanos de inmediato.</p>"#,
+        },
+        ("es", Template::NewSignIn) => Builtin {
+            subject: "Nuevo inicio de sesión en tu cuenta de {{service_name}}",
+            text: "Se inició sesión en tu cuenta desde un dispositivo nuevo.\n\nDispositivo: {{device}}\nUbicación: {{location}}\nHora: {{time}}\n\nSi no fuiste tú, abre este enlace para cerrar esa sesión y restablecer tu contraseña:\n{{link}}\n\nGracias,\n{{service_name}}\n",
+            html: r#"<h1 style="font-size:22px;">Nuevo inicio de sesión en tu cuenta</h1>
+<p>Se inició sesión en tu cuenta desde un dispositivo nuevo.</p>
+<p>Dispositivo: <strong>{{device}}</strong><br>Ubicación: <strong>{{location}}</strong><br>Hora: <strong>{{time}}</strong></p>
+<p><a href="{{link}}" style="display:inline-block;padding:12px 20px;background-color:#b91c1c;color:#ffffff;border-radius:6px;text-decoration:none;">No fui yo</a></p>"#,
+        },
+
+        ("es", Template::MagicLink) => Builtin {
+            subject: "Inicia sesión en {{service_name}}",
+            text: "Abre este enlace para iniciar sesión como {{email}}:\n{{link}}\n\nEl enlace funciona una sola vez, caduca en 15 minutos y solo en el navegador que lo solicitó.\nSi no lo solicitaste, puedes ignorar este correo.\n\nGracias,\n{{service_name}}\n",
+            html: r#"<h1 style="font-size:22px;">Inicia sesión en {{service_name}}</h1>
+<p>Pulsa el botón de abajo para iniciar sesión como <strong>{{email}}</strong>.</p>
+<p><a href="{{link}}" style="display:inline-block;padding:12px 20px;background-color:#18181b;color:#ffffff;border-radius:6px;text-decoration:none;">Iniciar sesión</a></p>
+<p>El enlace funciona una sola vez, caduca en 15 minutos y solo en el navegador que lo solicitó. Si no lo solicitaste, puedes ignorar este correo.</p>"#,
+        },
+
+        ("es", Template::LoginCode) => Builtin {
+            subject: "Tu código de inicio de sesión de {{service_name}}",
+            text: "Inicia sesión en {{service_name}}\n\n{{otp}}\n\nEl código caduca en 10 minutos. Si no intentaste iniciar sesión, puedes ignorar este correo.\n\nGracias,\n{{service_name}}\n",
+            html: r#"<h1 style="font-size:22px;">Inicia sesión en {{service_name}}</h1>
+<p>Introduce este código para iniciar sesión:</p>
+<p style="font-size:32px;font-weight:700;letter-spacing:8px;">{{otp}}</p>
+<p>El código caduca en 10 minutos. Si no intentaste iniciar sesión, puedes ignorar este correo.</p>"#,
+        },
+
+        ("es", Template::DataExport) => Builtin {
+            subject: "Tu exportación de datos de {{service_name}} está lista",
+            text: "La copia de tus datos de {{service_name}} que solicitaste está lista.\n\nDescárgala con tu sesión iniciada:\n{{link}}\n\nEl enlace caduca en 24 horas. Si no la solicitaste, cambia tu contraseña de inmediato.\n\nGracias,\n{{service_name}}\n",
+            html: r#"<h1 style="font-size:22px;">Tu exportación de datos está lista</h1>
+<p>La copia de tus datos de {{service_name}} que solicitaste está lista. Descárgala con tu sesión iniciada.</p>
+<p><a href="{{link}}" style="display:inline-block;padding:12px 20px;background-color:#18181b;color:#ffffff;border-radius:6px;text-decoration:none;">Descargar mis datos</a></p>
+<p>El enlace caduca en 24 horas. Si no la solicitaste, cambia tu contraseña de inmediato.</p>"#,
+        },
+
+        ("es", Template::DataExportFailed) => Builtin {
+            subject: "No se pudo preparar tu exportación de datos de {{service_name}}",
+            text: "No pudimos preparar la copia de tus datos de {{service_name}} que solicitaste.\n\nVuelve a solicitarla desde tus ajustes dentro de un rato.\n\nGracias,\n{{service_name}}\n",
+            html: r#"<h1 style="font-size:22px;">No se pudo preparar tu exportación de datos</h1>
+<p>No pudimos preparar la copia de tus datos de {{service_name}} que solicitaste.</p>
+<p>Vuelve a solicitarla desde tus ajustes dentro de un rato.</p>"#,
+        },
+
+        ("es", Template::AccountDeletion) => Builtin {
+            subject: "Tu cuenta de {{service_name}} será eliminada",
+            text: "Tu cuenta de {{service_name}} {{email}} está programada para eliminarse y se borrará definitivamente el {{date}}.\n\nHasta entonces puedes restaurarla iniciando sesión de nuevo o abriendo este enlace:\n{{link}}\n\nSi no eliminaste tu cuenta, restáurala y cambia tu contraseña de inmediato.\n\nGracias,\n{{service_name}}\n",
+            html: r#"<h1 style="font-size:22px;">Tu cuenta será eliminada</h1>
+<p>Tu cuenta de {{service_name}} <strong>{{email}}</strong> está programada para eliminarse y se borrará definitivamente el <strong>{{date}}</strong>.</p>
+<p>Hasta entonces puedes restaurarla iniciando sesión de nuevo o con el botón de abajo.</p>
+<p><a href="{{link}}" style="display:inline-block;padding:12px 20px;background-color:#18181b;color:#ffffff;border-radius:6px;text-decoration:none;">Restaurar mi cuenta</a></p>
+<p>Si no eliminaste tu cuenta, restáurala y cambia tu contraseña de inmediato.</p>"#,
+        },
+
+        ("es", Template::ReauthCode) => Builtin {
+            subject: "Tu código de verificación de {{service_name}}",
+            text: "Confirma que eres tú\n\n{{otp}}\n\nIntroduce este código para seguir modificando tu cuenta. El código caduca en 10 minutos.\nSi no lo solicitaste, cambia tu contraseña de inmediato.\n\nGracias,\n{{service_name}}\n",
+            html: r#"<h1 style="font-size:22px;">Confirma que eres tú</h1>
+<p>Introduce este código para seguir modificando tu cuenta:</p>
+<p style="font-size:32px;font-weight:700;letter-spacing:8px;">{{otp}}</p>
+<p>El código caduca en 10 minutos. Si no lo solicitaste, cambia tu contraseña de inmediato.</p>"#,
+        },
+
+        _ => return None,
+    };
+    Some(builtin)
+}
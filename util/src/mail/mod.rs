@@ -0,0 +1,96 @@
+use crate::{AppError, config::MailConfig};
+use lettre::{
+    Message,
+    message::{Mailbox, MultiPart},
+};
+use std::sync::{Arc, OnceLock};
+
+mod builtin;
+mod template;
+mod transport;
+
+pub use template::{
+    DEFAULT_LOCALE, Email, Template, is_locale_supported, locale_from_headers, render,
+};
+pub use transport::{
+    FileTransport, LogTransport, MemoryTransport, OutgoingMail, SendFuture, SmtpTransport, Transport,
+};
+
+// selected by `init` from `MAIL_TRANSPORT`, unless a transport was set before
+static TRANSPORT: OnceLock<Arc<dyn Transport>> = OnceLock::new();
+
+// the noreply email is stored in this static variable to avoid parsing on every send
+// it is shown with `SERVICE_NAME` as the sender name
+static NOREPLY_EMAIL: OnceLock<Mailbox> = OnceLock::new();
+
+/// replaces the transport selected by `MAIL_TRANSPORT`
+///
+/// must be called before `init`, returns false otherwise
+pub fn set_transport(transport: Arc<dyn Transport>) -> bool {
+    TRANSPORT.set(transport).is_ok()
+}
+
+/// loads the templates and builds the transport selected by `MAIL_TRANSPORT`, called by
+/// `crate::init` so that a transport that can't be built (e.g. an invalid `SMTP_HOST`) stops the
+/// server instead of losing every mail
+pub(crate) fn init(config: &MailConfig, service_name: &str) -> Result<(), String> {
+    if let Some(dir) = &config.templates_dir {
+        template::load_templates(dir);
+    }
+
+    let mailbox: Mailbox =
+        config.noreply_email.parse().unwrap_or_else(|_| "noreply@localhost".parse().unwrap());
+    let mailbox =
+        Mailbox::new(mailbox.name.or_else(|| Some(service_name.to_string())), mailbox.email);
+    let _ = NOREPLY_EMAIL.set(mailbox);
+
+    if TRANSPORT.get().is_none() {
+        let transport = transport::from_config(config)?;
+        let _ = TRANSPORT.set(transport);
+    }
+    Ok(())
+}
+
+fn transport() -> Result<&'static Arc<dyn Transport>, String> {
+    TRANSPORT.get().ok_or_else(|| "No mail transport, util::init wasn't called".to_string())
+}
+
+/// the transport selected with `MAIL_TRANSPORT=memory`, to read the mails sent from tests
+pub fn memory() -> Option<&'static MemoryTransport> {
+    transport::MEMORY.get().map(|v| v.as_ref())
+}
+
+/// renders `template` in `locale` and sends it to the given mail address
+pub async fn send_template(
+    to_email: String,
+    template: Template,
+    locale: Option<&str>,
+    vars: &[(&str, &str)],
+) -> Result<(), AppError> {
+    send(to_email, render(template, locale, vars)).await
+}
+
+// function to send any mail to the given mail address
+pub async fn send(to_email: String, email: Email) -> Result<(), AppError> {
+    deliver(to_email, email).await.map_err(|e| {
+        tracing::error!("Failed to send email: {e}");
+        AppError::ServerError
+    })
+}
+
+/// sends the mail through the configured transport, returns the reason if it failed
+pub async fn deliver(to_email: String, email: Email) -> Result<(), String> {
+    let msg: Message = Message::builder()
+        .from(NOREPLY_EMAIL.get().ok_or("No sender, util::init wasn't called")?.clone())
+        .to(to_email.parse().map_err(|e| format!("Invalid address {to_email}: {e}"))?)
+        .subject(email.subject.clone())
+        .multipart(MultiPart::alternative_plain_html(email.text.clone(), email.html.clone()))
+        .map_err(|e| format!("{e:?}"))?;
+
+    // sending the email and wait for completion
+    let mail = OutgoingMail { to: to_email, email, message: msg };
+    let transport = transport()?;
+    tokio::spawn(async move { transport.send(mail).await })
+        .await
+        .map_err(|e| format!("Task join error: {e:?}"))?
+}
@@ -0,0 +1,234 @@
+use super::builtin;
+use axum::http::{HeaderMap, header};
+use std::{collections::HashMap, path::Path, sync::OnceLock};
+
+/// every kind of email sent by the service
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Template {
+    Otp,
+    Welcome,
+    PasswordReset,
+    PasswordChanged,
+    EmailChanged,
+    NewSignIn,
+    MagicLink,
+    LoginCode,
+    DataExport,
+    DataExportFailed,
+    AccountDeletion,
+    ReauthCode,
+}
+
+impl Template {
+    pub const ALL: [Template; 12] = [
+        Template::Otp,
+        Template::Welcome,
+        Template::PasswordReset,
+        Template::PasswordChanged,
+        Template::EmailChanged,
+        Template::NewSignIn,
+        Template::MagicLink,
+        Template::LoginCode,
+        Template::DataExport,
+        Template::DataExportFailed,
+        Template::AccountDeletion,
+        Template::ReauthCode,
+    ];
+
+    /// name of the template files inside `MAIL_TEMPLATES_DIR/{locale}/`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Template::Otp => "otp",
+            Template::Welcome => "welcome",
+            Template::PasswordReset => "password_reset",
+            Template::PasswordChanged => "password_changed",
+            Template::EmailChanged => "email_changed",
+            Template::NewSignIn => "new_sign_in",
+            Template::MagicLink => "magic_link",
+            Template::LoginCode => "login_code",
+            Template::DataExport => "data_export",
+            Template::DataExportFailed => "data_export_failed",
+            Template::AccountDeletion => "account_deletion",
+            Template::ReauthCode => "reauth_code",
+        }
+    }
+}
+
+/// rendered multipart email
+#[derive(Clone, Debug)]
+pub struct Email {
+    pub subject: String,
+    pub text: String,
+    pub html: String,
+}
+
+pub const DEFAULT_LOCALE: &str = "en";
+
+// operator provided templates, loaded once at startup
+static OVERRIDES: OnceLock<Overrides> = OnceLock::new();
+
+#[derive(Default)]
+struct Overrides {
+    layout: Option<String>,
+    parts: HashMap<(String, Template), Parts>,
+}
+
+#[derive(Default)]
+struct Parts {
+    subject: Option<String>,
+    text: Option<String>,
+    html: Option<String>,
+}
+
+/// loads the templates found inside `MAIL_TEMPLATES_DIR`
+///
+/// the directory is laid out as `layout.html` and `{locale}/{template}.{subject,txt,html}`,
+/// every file is optional and missing parts fall back to the built-in templates
+pub(super) fn load_templates(dir: &str) {
+    let dir = Path::new(dir);
+    let mut overrides = Overrides {
+        layout: std::fs::read_to_string(dir.join("layout.html")).ok(),
+        ..Default::default()
+    };
+
+    let entries = match std::fs::read_dir(dir) {
+        Ok(v) => v,
+        Err(e) => {
+            tracing::error!("Failed to read mail templates from {}: {e:?}", dir.display());
+            return;
+        }
+    };
+    for entry in entries.flatten() {
+        if !entry.path().is_dir() {
+            continue;
+        }
+        let locale = entry.file_name().to_string_lossy().to_lowercase();
+        for template in Template::ALL {
+            let read = |ext: &str| {
+                std::fs::read_to_string(entry.path().join(format!("{}.{ext}", template.name()))).ok()
+            };
+            let parts = Parts { subject: read("subject"), text: read("txt"), html: read("html") };
+            if parts.subject.is_some() || parts.text.is_some() || parts.html.is_some() {
+                tracing::info!("[Mail Template Loaded] {locale}/{}", template.name());
+                overrides.parts.insert((locale.clone(), template), parts);
+            }
+        }
+    }
+
+    if OVERRIDES.set(overrides).is_err() {
+        tracing::warn!("Mail templates were already loaded");
+    }
+}
+
+/// returns true if emails can be rendered in the given locale
+pub fn is_locale_supported(locale: &str) -> bool {
+    builtin::LOCALES.contains(&locale)
+        || OVERRIDES.get().is_some_and(|o| o.parts.keys().any(|(l, _)| l == locale))
+}
+
+/// picks the first supported language from the `Accept-Language` header
+pub fn locale_from_headers(headers: &HeaderMap) -> Option<String> {
+    let accept_language = headers.get(header::ACCEPT_LANGUAGE)?.to_str().ok()?;
+    accept_language
+        .split(',')
+        .filter_map(|v| v.split(';').next())
+        .map(|v| v.trim().to_lowercase())
+        .find_map(|tag| resolve(&tag))
+}
+
+// tries the exact tag (`pt-br`) before its primary language (`pt`)
+fn resolve(locale: &str) -> Option<String> {
+    let locale = locale.to_lowercase();
+    if is_locale_supported(&locale) {
+        return Some(locale);
+    }
+    let primary = locale.split(['-', '_']).next()?;
+    is_locale_supported(primary).then(|| primary.to_string())
+}
+
+/// renders `template` in `locale` (or the default locale) with the given variables
+///
+/// `service_name` and `service_domain` are always available to the templates
+pub fn render(template: Template, locale: Option<&str>, vars: &[(&str, &str)]) -> Email {
+    let locale = locale.and_then(resolve).unwrap_or_else(|| DEFAULT_LOCALE.to_string());
+    let overrides = OVERRIDES.get().and_then(|o| o.parts.get(&(locale.clone(), template)));
+    let builtin = builtin::get(&locale, template)
+        .unwrap_or_else(|| builtin::get(DEFAULT_LOCALE, template).unwrap());
+
+    let mut all_vars = vec![
+        ("service_name", shared::SERVICE_NAME.as_str()),
+        ("service_domain", shared::SERVICE_DOMAIN.as_str()),
+    ];
+    all_vars.extend_from_slice(vars);
+
+    let subject = overrides.and_then(|o| o.subject.as_deref()).unwrap_or(builtin.subject);
+    let text = overrides.and_then(|o| o.text.as_deref()).unwrap_or(builtin.text);
+    let html = overrides.and_then(|o| o.html.as_deref()).unwrap_or(builtin.html);
+
+    let subject = fill(subject.trim(), &all_vars, false);
+    let content = fill(html, &all_vars, true);
+    let layout = OVERRIDES.get().and_then(|o| o.layout.as_deref()).unwrap_or(builtin::LAYOUT);
+    all_vars.extend_from_slice(&[("locale", locale.as_str()), ("subject", subject.as_str())]);
+    let html = fill(layout, &all_vars, true).replace("{{content}}", &content);
+
+    Email { text: fill(text, &all_vars, false), subject, html }
+}
+
+// replaces every `{{name}}` with its value
+fn fill(s: &str, vars: &[(&str, &str)], escape: bool) -> String {
+    let mut result = s.to_string();
+    for (name, value) in vars {
+        let value = if escape { escape_html(value) } else { value.to_string() };
+        result = result.replace(&format!("{{{{{name}}}}}"), &value);
+    }
+    result
+}
+
+fn escape_html(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_template_has_builtin_locales() {
+        for locale in builtin::LOCALES {
+            for template in Template::ALL {
+                assert!(builtin::get(locale, template).is_some(), "{locale}/{}", template.name());
+            }
+        }
+    }
+
+    #[test]
+    fn fill_escapes_only_html() {
+        let vars = [("name", "<b>&</b>")];
+        assert_eq!(fill("hi {{name}}", &vars, false), "hi <b>&</b>");
+        assert_eq!(fill("hi {{name}}", &vars, true), "hi &lt;b&gt;&amp;&lt;/b&gt;");
+        assert_eq!(fill("hi {{other}}", &vars, true), "hi {{other}}");
+    }
+
+    #[test]
+    fn resolve_locale_test() {
+        assert_eq!(resolve("es-MX").as_deref(), Some("es"));
+        assert_eq!(resolve("EN").as_deref(), Some("en"));
+        assert_eq!(resolve("xx"), None);
+        let headers = HeaderMap::from_iter([(
+            header::ACCEPT_LANGUAGE,
+            "xx-YY, es;q=0.8, en;q=0.5".parse().unwrap(),
+        )]);
+        assert_eq!(locale_from_headers(&headers).as_deref(), Some("es"));
+    }
+}
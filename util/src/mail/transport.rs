@@ -0,0 +1,189 @@
+use super::Email;
+use crate::config::MailConfig;
+use lettre::{
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+    transport::smtp::authentication::Credentials,
+};
+use std::{
+    future::Future,
+    path::PathBuf,
+    pin::Pin,
+    sync::{Arc, Mutex, OnceLock},
+};
+
+/// resolves to the reason of the failure if the mail couldn't be delivered
+pub type SendFuture<'a> = Pin<Box<dyn Future<Output = Result<(), String>> + Send + 'a>>;
+
+/// a built email together with the parts it was built from
+pub struct OutgoingMail {
+    pub to: String,
+    pub email: Email,
+    pub message: Message,
+}
+
+/// delivers outgoing mails, selected at startup with `MAIL_TRANSPORT`
+pub trait Transport: Send + Sync {
+    fn send(&self, mail: OutgoingMail) -> SendFuture<'_>;
+}
+
+/// sends mails through the `SMTP_HOST` relay (`MAIL_TRANSPORT=smtp`)
+pub struct SmtpTransport(AsyncSmtpTransport<Tokio1Executor>);
+
+impl SmtpTransport {
+    pub fn new(config: &MailConfig) -> Result<Self, String> {
+        let creds = Credentials::new(config.noreply_email.clone(), config.smtp_key.clone());
+        // the connection to the mail server is opened lazily and kept inside the pool
+        let relay = AsyncSmtpTransport::<Tokio1Executor>::relay(&config.smtp_host)
+            .map_err(|e| format!("Invalid SMTP_HOST: {e:?}"))?;
+        Ok(Self(relay.credentials(creds).build()))
+    }
+}
+
+impl Transport for SmtpTransport {
+    fn send(&self, mail: OutgoingMail) -> SendFuture<'_> {
+        Box::pin(async move {
+            self.0.send(mail.message).await.map(|_| ()).map_err(|e| format!("SMTP error: {e}"))
+        })
+    }
+}
+
+/// writes every mail as an `.eml` file inside `MAIL_FILE_DIR` (`MAIL_TRANSPORT=file`)
+pub struct FileTransport {
+    dir: PathBuf,
+}
+
+impl FileTransport {
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self, String> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create {}: {e:?}", dir.display()))?;
+        Ok(Self { dir })
+    }
+}
+
+impl Transport for FileTransport {
+    fn send(&self, mail: OutgoingMail) -> SendFuture<'_> {
+        Box::pin(async move {
+            let filename = format!(
+                "{}-{}.eml",
+                time::OffsetDateTime::now_utc().unix_timestamp(),
+                uuid::Uuid::new_v4()
+            );
+            let path = self.dir.join(filename);
+            tokio::fs::write(&path, mail.message.formatted())
+                .await
+                .map_err(|e| format!("Failed to write mail to {}: {e}", path.display()))?;
+            tracing::info!("[Mail Written] To: {}, File: {}", mail.to, path.display());
+            Ok(())
+        })
+    }
+}
+
+/// only logs the text part of every mail (`MAIL_TRANSPORT=log`)
+pub struct LogTransport;
+
+impl Transport for LogTransport {
+    fn send(&self, mail: OutgoingMail) -> SendFuture<'_> {
+        Box::pin(async move {
+            tracing::info!(
+                "[Mail Logged] To: {}, Subject: {}\n{}",
+                mail.to,
+                mail.email.subject,
+                mail.email.text
+            );
+            Ok(())
+        })
+    }
+}
+
+/// keeps every mail in memory so that tests can inspect them (`MAIL_TRANSPORT=memory`)
+#[derive(Default)]
+pub struct MemoryTransport {
+    sent: Mutex<Vec<(String, Email)>>,
+}
+
+impl MemoryTransport {
+    /// returns every mail sent to `address`, oldest first
+    pub fn sent_to(&self, address: &str) -> Vec<Email> {
+        let guard = self.sent.lock().unwrap();
+        guard.iter().filter(|(to, _)| to == address).map(|(_, email)| email.clone()).collect()
+    }
+
+    /// returns the 6 digit code of the last mail sent to `address` that contains one
+    pub fn last_otp(&self, address: &str) -> Option<String> {
+        self.sent_to(address)
+            .iter()
+            .rev()
+            .find_map(|email| find_otp(&email.subject).or_else(|| find_otp(&email.text)))
+    }
+
+    pub fn clear(&self) {
+        self.sent.lock().unwrap().clear();
+    }
+}
+
+impl Transport for MemoryTransport {
+    fn send(&self, mail: OutgoingMail) -> SendFuture<'_> {
+        self.sent.lock().unwrap().push((mail.to, mail.email));
+        Box::pin(async { Ok(()) })
+    }
+}
+
+// first standalone run of exactly 6 digits
+fn find_otp(s: &str) -> Option<String> {
+    s.split(|c: char| !c.is_ascii_digit()).find(|v| v.len() == 6).map(|v| v.to_string())
+}
+
+// the transport built for `MAIL_TRANSPORT=memory`, kept typed so that tests can read it
+pub(super) static MEMORY: OnceLock<Arc<MemoryTransport>> = OnceLock::new();
+
+/// builds the transport selected by `mail.transport` (defaults to `smtp`)
+pub(super) fn from_config(config: &MailConfig) -> Result<Arc<dyn Transport>, String> {
+    let kind = config.transport.as_str();
+    match kind {
+        "smtp" => SmtpTransport::new(config).map(|t| Arc::new(t) as _),
+        "file" => FileTransport::new(&config.file_dir).map(|t| Arc::new(t) as _),
+        "log" => Ok(Arc::new(LogTransport)),
+        "memory" => Ok(MEMORY.get_or_init(Default::default).clone()),
+        _ => Err(format!("Unknown MAIL_TRANSPORT: {kind}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outgoing(to: &str, subject: &str, text: &str) -> OutgoingMail {
+        let email =
+            Email { subject: subject.to_string(), text: text.to_string(), html: String::new() };
+        let message = Message::builder()
+            .from("noreply@example.com".parse().unwrap())
+            .to(to.parse().unwrap())
+            .subject(subject)
+            .body(text.to_string())
+            .unwrap();
+        OutgoingMail { to: to.to_string(), email, message }
+    }
+
+    #[tokio::test]
+    async fn memory_transport_captures_otp() {
+        let transport = MemoryTransport::default();
+        transport.send(outgoing("a@example.com", "123456 is your code", "")).await.unwrap();
+        transport.send(outgoing("b@example.com", "Welcome", "code: 654321")).await.unwrap();
+        transport.send(outgoing("a@example.com", "Welcome", "no code in 2025")).await.unwrap();
+
+        assert_eq!(transport.sent_to("a@example.com").len(), 2);
+        assert_eq!(transport.last_otp("a@example.com").as_deref(), Some("123456"));
+        assert_eq!(transport.last_otp("b@example.com").as_deref(), Some("654321"));
+        assert_eq!(transport.last_otp("c@example.com"), None);
+
+        transport.clear();
+        assert!(transport.sent_to("a@example.com").is_empty());
+    }
+
+    #[test]
+    fn find_otp_test() {
+        assert_eq!(find_otp("1234567 and 000123").as_deref(), Some("000123"));
+        assert_eq!(find_otp("12345"), None);
+    }
+}
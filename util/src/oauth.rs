@@ -1,7 +1,12 @@
-use std::sync::Arc;
+use crate::config::GoogleConfig;
+use std::sync::{Arc, OnceLock};
 
-static OAUTH_PROVIDERS: std::sync::LazyLock<OAuthProviders> =
-    std::sync::LazyLock::new(OAuthProviders::default);
+// set once by `crate::init`
+static OAUTH_PROVIDERS: OnceLock<OAuthProviders> = OnceLock::new();
+
+pub(crate) fn init(google: &GoogleConfig) {
+    let _ = OAUTH_PROVIDERS.set(OAuthProviders::new(google));
+}
 
 struct OAuthProviders<'a> {
     /// https://accounts.google.com/.well-known/openid-configuration
@@ -17,12 +22,12 @@ pub struct OAuthConfig<'a> {
     pub provider: OAuthProvider,
 }
 
-impl<'a> Default for OAuthProviders<'a> {
-    fn default() -> Self {
+impl<'a> OAuthProviders<'a> {
+    fn new(google: &GoogleConfig) -> Self {
         Self {
             google: Arc::new(OAuthConfig {
-                client_id: std::env::var("GOOGLE_CLIENT_ID").unwrap(),
-                client_secret: std::env::var("GOOGLE_CLIENT_SECRET").unwrap(),
+                client_id: google.client_id.clone(),
+                client_secret: google.client_secret.clone(),
                 authorization_endpoint: reqwest::Url::parse(
                     "https://accounts.google.com/o/oauth2/v2/auth",
                 )
@@ -81,7 +86,13 @@ impl OAuthProvider {
 
 pub fn get_oauth_provider(provider: OAuthProvider) -> Option<Arc<OAuthConfig<'static>>> {
     match provider {
-        OAuthProvider::Google => Some(OAUTH_PROVIDERS.google.clone()),
+        OAuthProvider::Google => Some(
+            OAUTH_PROVIDERS
+                .get()
+                .expect("util::init must be called before signing in with a provider")
+                .google
+                .clone(),
+        ),
         OAuthProvider::None => None,
     }
 }
@@ -1,31 +1,31 @@
-use base64::Engine;
-use hmac::Mac;
+use axum::http::{HeaderMap, header};
 
 pub const BASE64_DIGEST_LEN: usize = 44;
 
 /// this function is used to sign cookie value to ensure integrity and authenticity
 ///
-/// value is the `VALUE` part of the whole cookie (`KEY=VALUE`)
+/// value is the `VALUE` part of the whole cookie (`KEY=VALUE`),
+/// the digest is prefixed with the id of the current signing key (`{key_id}.{digest}`)
 pub fn sign(value: &str) -> String {
-    let mut mac = hmac::Hmac::<sha2::Sha256>::new_from_slice(&crate::SECRET_KEY).unwrap();
-    hmac::Mac::update(&mut mac, value.as_bytes());
-    base64::prelude::BASE64_STANDARD.encode(mac.finalize().into_bytes())
+    super::keyring::with(|k| k.sign(value))
 }
 
 /// this function is used to verify signed cookie value to ensure integrity and authenticity
 ///
 /// value is the `VALUE` part of the whole cookie (`KEY=VALUE`)
 pub fn verify(value: &str) -> Option<String> {
-    if !value.is_char_boundary(BASE64_DIGEST_LEN) {
-        return None;
-    }
-
-    // Split [MAC | original-value] into its two parts.
-    let (digest_str, uid) = value.split_at(BASE64_DIGEST_LEN);
-    let digest = base64::prelude::BASE64_STANDARD.decode(digest_str).ok()?;
+    super::keyring::with(|k| k.verify(value))
+}
 
-    // Perform the verification
-    let mut mac = hmac::Hmac::<sha2::Sha256>::new_from_slice(&crate::SECRET_KEY).unwrap();
-    hmac::Mac::update(&mut mac, uid.as_bytes());
-    mac.verify_slice(&digest).map(|_| uid.to_string()).ok()
+/// returns the value of the cookie named `name` sent by the client
+pub fn get_cookie(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|h| h.to_str().ok())
+        .flat_map(|h| h.split(';'))
+        .find_map(|cookie| {
+            let (key, value) = cookie.trim().split_once('=')?;
+            (key == name).then(|| value.to_string())
+        })
 }
@@ -0,0 +1,74 @@
+use crate::user_agent;
+use std::{net::IpAddr, sync::OnceLock};
+
+/// parts of a sign-in compared against the earlier sessions of the user
+///
+/// selected with `NEW_SIGN_IN_SIGNALS`, a comma separated list of `device`, `ip` and `country`
+/// (defaults to `device,country`), `none` turns the alerts off
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SignInSignals {
+    pub device: bool,
+    pub ip: bool,
+    pub country: bool,
+}
+
+impl SignInSignals {
+    pub fn parse(list: &str) -> Self {
+        let mut signals = Self::default();
+        for signal in list.split(',').map(str::trim) {
+            match signal {
+                "device" => signals.device = true,
+                "ip" => signals.ip = true,
+                "country" => signals.country = true,
+                "none" | "" => {}
+                _ => tracing::error!("Unknown sign-in signal: {signal}"),
+            }
+        }
+        signals
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.device || self.ip || self.country
+    }
+
+    /// returns true if any enabled signal of the sign-in matches none of the `known` origins
+    ///
+    /// without any known origin (e.g. once they were cleared after a takeover) every sign-in is
+    /// unfamiliar
+    pub fn is_unfamiliar(
+        &self,
+        known: &[(Option<String>, IpAddr)],
+        user_agent: Option<&str>,
+        ip: IpAddr,
+    ) -> bool {
+        if known.is_empty() {
+            return true;
+        }
+        let agent = user_agent::parse(user_agent.unwrap_or_default());
+        let new_device = || {
+            !known.iter().any(|(ua, _)| {
+                user_agent::parse(ua.as_deref().unwrap_or_default()).same_device(&agent)
+            })
+        };
+        let new_ip = || !known.iter().any(|(_, known_ip)| *known_ip == ip);
+        // unknown countries (no GeoIP database, private addresses) are never reported
+        let new_country = || {
+            crate::geoip::country(ip).is_some_and(|c| {
+                !known.iter().any(|(_, k)| crate::geoip::country(*k) == Some(c.clone()))
+            })
+        };
+        (self.device && new_device()) || (self.ip && new_ip()) || (self.country && new_country())
+    }
+}
+
+// set once by `crate::init`
+static SIGNALS: OnceLock<SignInSignals> = OnceLock::new();
+
+pub(super) fn init(list: &str) {
+    let _ = SIGNALS.set(SignInSignals::parse(list));
+}
+
+/// returns the signals selected by `NEW_SIGN_IN_SIGNALS`
+pub fn sign_in_signals() -> &'static SignInSignals {
+    SIGNALS.get().expect("util::init must be called before checking sign-ins")
+}
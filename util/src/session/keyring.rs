@@ -0,0 +1,139 @@
+use base64::Engine;
+use hmac::Mac;
+use std::sync::{LazyLock, RwLock};
+
+/// id of the key derived from `SECRET_KEY`, also used by cookies signed before key ids existed
+pub const LEGACY_KEY_ID: &str = "0";
+
+/// a key used to sign session cookies
+#[derive(Clone)]
+pub struct SigningKey {
+    pub id: String,
+    pub secret: Vec<u8>,
+}
+
+impl SigningKey {
+    fn legacy() -> Self {
+        Self { id: LEGACY_KEY_ID.to_string(), secret: crate::secret_key().to_vec() }
+    }
+}
+
+/// keys accepted for verification, the newest one is used for signing
+pub struct Keyring {
+    keys: Vec<SigningKey>,
+}
+
+impl Keyring {
+    /// `keys` must be sorted from oldest to newest and can't be empty
+    pub fn new(keys: Vec<SigningKey>) -> Option<Self> {
+        (!keys.is_empty()).then_some(Self { keys })
+    }
+
+    pub fn current(&self) -> &SigningKey {
+        self.keys.last().unwrap()
+    }
+
+    /// returns `{key_id}.{digest}` of `value` signed with the newest key
+    pub fn sign(&self, value: &str) -> String {
+        let key = self.current();
+        let mut mac = hmac::Hmac::<sha2::Sha256>::new_from_slice(&key.secret).unwrap();
+        mac.update(value.as_bytes());
+        format!(
+            "{}.{}",
+            key.id,
+            base64::prelude::BASE64_STANDARD.encode(mac.finalize().into_bytes())
+        )
+    }
+
+    /// verifies `{key_id}.{digest}{value}` with any active key, returns `value`
+    pub fn verify(&self, signed: &str) -> Option<String> {
+        let (key_id, signed) = split_key_id(signed);
+        let key = self.keys.iter().find(|k| k.id == key_id)?;
+        if !signed.is_char_boundary(super::BASE64_DIGEST_LEN) {
+            return None;
+        }
+
+        // Split [MAC | original-value] into its two parts.
+        let (digest_str, value) = signed.split_at(super::BASE64_DIGEST_LEN);
+        let digest = base64::prelude::BASE64_STANDARD.decode(digest_str).ok()?;
+
+        let mut mac = hmac::Hmac::<sha2::Sha256>::new_from_slice(&key.secret).unwrap();
+        mac.update(value.as_bytes());
+        mac.verify_slice(&digest).map(|_| value.to_string()).ok()
+    }
+}
+
+// the keyring used by the session cookies, starts with the `SECRET_KEY` derived key only
+static KEYRING: LazyLock<RwLock<Keyring>> =
+    LazyLock::new(|| RwLock::new(Keyring { keys: vec![SigningKey::legacy()] }));
+
+/// replaces the active keys, `keys` must be sorted from oldest to newest
+///
+/// the `SECRET_KEY` derived key stays active unless `retire_legacy` is set
+pub fn set_keys(mut keys: Vec<SigningKey>, retire_legacy: bool) {
+    if !retire_legacy {
+        keys.insert(0, SigningKey::legacy());
+    }
+    match Keyring::new(keys) {
+        Some(keyring) => *KEYRING.write().unwrap() = keyring,
+        None => tracing::error!("Refusing to retire every signing key"),
+    }
+}
+
+/// runs `f` with the active keyring
+pub fn with<T>(f: impl FnOnce(&Keyring) -> T) -> T {
+    f(&KEYRING.read().unwrap())
+}
+
+/// returns the id of the key used for signing new cookies
+pub fn current_key_id() -> String {
+    with(|k| k.current().id.clone())
+}
+
+/// returns the ids of the active keys, oldest first
+pub fn key_ids() -> Vec<String> {
+    with(|k| k.keys.iter().map(|k| k.id.clone()).collect())
+}
+
+/// returns the id of the key that signed the cookie value
+pub fn key_id(signed: &str) -> &str {
+    split_key_id(signed).0
+}
+
+// cookies signed before key ids existed have no prefix and belong to the legacy key
+fn split_key_id(signed: &str) -> (&str, &str) {
+    signed.split_once('.').unwrap_or((LEGACY_KEY_ID, signed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(id: &str) -> SigningKey {
+        SigningKey { id: id.to_string(), secret: format!("secret-{id}").into_bytes() }
+    }
+
+    #[test]
+    fn rotated_keys_verify_until_retired() {
+        let uid = uuid::Uuid::new_v4().to_string();
+        let old = Keyring::new(vec![key(LEGACY_KEY_ID)]).unwrap();
+        let legacy = old.sign(&uid);
+        assert!(legacy.starts_with("0."));
+
+        // cookies signed before key ids existed
+        let unprefixed = legacy.split_once('.').unwrap().1;
+        assert_eq!(old.verify(&format!("{unprefixed}{uid}")).as_deref(), Some(uid.as_str()));
+
+        let rotated = Keyring::new(vec![key(LEGACY_KEY_ID), key("k1")]).unwrap();
+        let signed = rotated.sign(&uid);
+        assert_eq!(key_id(&format!("{signed}{uid}")), "k1");
+        assert_eq!(rotated.verify(&format!("{legacy}{uid}")).as_deref(), Some(uid.as_str()));
+        assert_eq!(rotated.verify(&format!("{signed}{uid}")).as_deref(), Some(uid.as_str()));
+
+        let retired = Keyring::new(vec![key("k1")]).unwrap();
+        assert_eq!(retired.verify(&format!("{legacy}{uid}")), None);
+        assert_eq!(retired.verify(&format!("{unprefixed}{uid}")), None);
+        assert_eq!(retired.verify(&format!("{signed}{uid}")).as_deref(), Some(uid.as_str()));
+        assert!(Keyring::new(vec![]).is_none());
+    }
+}
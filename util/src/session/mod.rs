@@ -1,14 +1,28 @@
 mod cookie;
+mod familiar;
+pub mod keyring;
 mod parsed_session;
+mod policy;
 mod session_fns;
 mod session_struct;
 
-pub use cookie::BASE64_DIGEST_LEN;
+pub use cookie::{BASE64_DIGEST_LEN, get_cookie};
 use cookie::{sign, verify};
+pub use familiar::{SignInSignals, sign_in_signals};
+pub use keyring::key_id;
 pub use parsed_session::{ParsedSession, ParsedSessionError};
-pub use session_fns::{create_session, expire_session};
+pub use policy::{SessionPolicy, policy};
+pub use session_fns::{
+    create_session, csrf_cookie, csrf_token, expire_session, refresh_session, resign_session,
+};
 pub use session_struct::{Session, SessionStatus};
 
+// selects the policies and the sign-in signals, called by `crate::init`
+pub(crate) fn init(config: &crate::config::SessionConfig) {
+    policy::init(config);
+    familiar::init(&config.new_sign_in_signals);
+}
+
 #[cfg(test)]
 mod tests {
     #![allow(clippy::needless_range_loop)]
@@ -20,7 +34,7 @@ mod tests {
 
     #[test]
     fn db_session_test() {
-        dotenv::dotenv().ok();
+        crate::init_for_tests();
         let headers = HeaderMap::from_iter([(
             header::USER_AGENT,
             HeaderValue::from_str("Mozilla Firefox").unwrap(),
@@ -28,7 +42,7 @@ mod tests {
         let uid = uuid::Uuid::new_v4();
         let sock_addr = SocketAddr::new(std::net::IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 39849);
         let (new_session, parsed_session, set_cookie_headermap) =
-            create_session(uid, &headers, sock_addr);
+            create_session(uid, &headers, sock_addr, policy("user"), true);
         dbg!(&new_session);
         dbg!(&parsed_session);
         dbg!(&set_cookie_headermap);
@@ -40,7 +54,7 @@ mod tests {
 
     #[test]
     fn sign_then_verify() {
-        dotenv::dotenv().ok();
+        crate::init_for_tests();
         let uid = uuid::Uuid::new_v4().to_string();
         let signed_uid = sign(&uid);
         let decrypted_uid = verify(&format!("{signed_uid}{uid}")).unwrap();
@@ -50,6 +64,81 @@ mod tests {
         assert_eq!(uid, decrypted_uid);
     }
 
+    #[test]
+    fn session_policy_test() {
+        crate::init_for_tests();
+        let headers = HeaderMap::new();
+        let sock_addr = SocketAddr::new(std::net::IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 39849);
+        let policy = SessionPolicy::DEFAULT;
+        let hours = |h: i64| time::Duration::hours(h);
+
+        let (short, _, _) =
+            create_session(uuid::Uuid::new_v4(), &headers, sock_addr, &policy, false);
+        let (long, _, _) = create_session(uuid::Uuid::new_v4(), &headers, sock_addr, &policy, true);
+        assert!(short.expires_at < long.expires_at);
+        assert!(matches!(short.session_status(&policy), SessionStatus::Valid(_)));
+
+        // only "remember me" sessions are refreshed after expiring
+        let mut expired = short.clone();
+        expired.expires_at -= hours(25);
+        assert!(matches!(expired.session_status(&policy), SessionStatus::Invalid));
+        expired.persistent = true;
+        assert!(matches!(expired.session_status(&policy), SessionStatus::Refreshable(_)));
+
+        // idle sessions can't be refreshed
+        let mut idle = long.clone();
+        idle.last_used -= hours(15 * 24);
+        assert!(matches!(idle.session_status(&policy), SessionStatus::Invalid));
+
+        // refreshing keeps the sign-in time, so the maximum lifetime still applies
+        let mut old = long.clone();
+        old.login_at -= hours(90 * 24 - 1);
+        let (refreshed, _, _) =
+            refresh_session(uuid::Uuid::new_v4(), &old, &headers, sock_addr, &policy);
+        assert_eq!(refreshed.login_at, old.login_at);
+        assert!(refreshed.expires_at <= old.login_at + hours(90 * 24));
+        old.login_at -= hours(1);
+        assert!(matches!(old.session_status(&policy), SessionStatus::Invalid));
+    }
+
+    #[test]
+    fn recent_authentication_test() {
+        crate::init_for_tests();
+        let headers = HeaderMap::new();
+        let sock_addr = SocketAddr::new(std::net::IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 39849);
+        let policy = SessionPolicy::DEFAULT;
+
+        let (mut session, _, _) =
+            create_session(uuid::Uuid::new_v4(), &headers, sock_addr, &policy, true);
+        assert!(session.is_recently_authenticated());
+
+        // refreshing a session doesn't re-authenticate it
+        session.authenticated_at -= time::Duration::minutes(11);
+        assert!(!session.is_recently_authenticated());
+        let (refreshed, _, _) =
+            refresh_session(uuid::Uuid::new_v4(), &session, &headers, sock_addr, &policy);
+        assert_eq!(refreshed.authenticated_at, session.authenticated_at);
+        assert!(!refreshed.is_recently_authenticated());
+    }
+
+    #[test]
+    fn unfamiliar_sign_in_test() {
+        let chrome = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) Chrome/124.0.0.0 Safari/537.36";
+        let firefox = "Mozilla/5.0 (X11; Linux x86_64; rv:125.0) Gecko/20100101 Firefox/125.0";
+        let home: std::net::IpAddr = "203.0.113.7".parse().unwrap();
+        let known = vec![(Some(chrome.to_string()), home)];
+
+        let signals = SignInSignals::parse("device,ip");
+        assert!(!signals.is_unfamiliar(&known, Some(chrome), home));
+        assert!(signals.is_unfamiliar(&known, Some(firefox), home));
+        assert!(signals.is_unfamiliar(&known, Some(chrome), "198.51.100.1".parse().unwrap()));
+        assert!(signals.is_unfamiliar(&[], Some(firefox), home));
+
+        let device_only = SignInSignals::parse("device");
+        assert!(!device_only.is_unfamiliar(&known, Some(chrome), "198.51.100.1".parse().unwrap()));
+        assert!(!SignInSignals::parse("none").is_enabled());
+    }
+
     // #[test]
     // fn syncing_session_test() {
     //     dotenv::dotenv().ok();
@@ -0,0 +1,69 @@
+use crate::config::{PolicyOverrides, SessionConfig};
+use std::{collections::HashMap, sync::OnceLock};
+
+/// lifetime rules applied to the sessions of a role
+///
+/// durations are in seconds, an `idle_timeout` of 0 disables the idle check
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SessionPolicy {
+    /// lifetime of sessions created without "remember me"
+    pub ttl: u64,
+    /// lifetime of sessions created with "remember me"
+    pub remember_ttl: u64,
+    /// sessions unused for this long are ended
+    pub idle_timeout: u64,
+    /// sessions are ended this long after the sign-in, even if they were auto-refreshed
+    pub max_lifetime: u64,
+}
+
+impl SessionPolicy {
+    pub const DEFAULT: Self = Self {
+        ttl: 86400,                    // 1 day
+        remember_ttl: 37 * 86400 + 60, // 37 days
+        idle_timeout: 14 * 86400,      // 14 days
+        max_lifetime: 90 * 86400,      // 90 days
+    };
+
+    pub const ADMIN: Self = Self {
+        ttl: 43200,          // 12 hours
+        remember_ttl: 43200, // 12 hours
+        idle_timeout: 3600,  // 1 hour
+        max_lifetime: 43200, // 12 hours
+    };
+
+    /// returns the lifetime of a new session
+    pub fn lifetime(&self, persistent: bool) -> u64 {
+        if persistent { self.remember_ttl } else { self.ttl }
+    }
+
+    /// sessions with less than this many seconds left are refreshed
+    pub fn refresh_within(&self, persistent: bool) -> u64 {
+        (self.lifetime(persistent) / 4).min(super::Session::MEM_CACHE_DURATION)
+    }
+
+    /// replaces the fields set in `overrides` (`[session.{role}]` in the config)
+    pub fn with_overrides(self, overrides: &PolicyOverrides) -> Self {
+        Self {
+            ttl: overrides.ttl.unwrap_or(self.ttl),
+            remember_ttl: overrides.remember_ttl.unwrap_or(self.remember_ttl),
+            idle_timeout: overrides.idle_timeout.unwrap_or(self.idle_timeout),
+            max_lifetime: overrides.max_lifetime.unwrap_or(self.max_lifetime),
+        }
+    }
+}
+
+// the defaults with the overrides of `[session]`, set once by `crate::init`
+static POLICIES: OnceLock<HashMap<&'static str, SessionPolicy>> = OnceLock::new();
+
+pub(super) fn init(config: &SessionConfig) {
+    let _ = POLICIES.set(HashMap::from([
+        ("user", SessionPolicy::DEFAULT.with_overrides(&config.user)),
+        ("admin", SessionPolicy::ADMIN.with_overrides(&config.admin)),
+    ]));
+}
+
+/// returns the policy of `role`, unknown roles get the `user` policy
+pub fn policy(role: &str) -> &'static SessionPolicy {
+    let policies = POLICIES.get().expect("util::init must be called before using sessions");
+    policies.get(role).unwrap_or(&policies["user"])
+}
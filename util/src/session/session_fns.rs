@@ -1,50 +1,117 @@
-use super::{ParsedSession, Session};
+use super::{ParsedSession, Session, SessionPolicy};
 use axum::http::{HeaderMap, HeaderValue, header};
 use std::time::Duration;
 use time::OffsetDateTime;
 
 /// this function creates a session that is passed to the user
 /// and stored in both in-memory and primary database
+///
+/// sessions created without `persistent` ("remember me") use the shorter lifetime of `policy`
+/// and a cookie that is dropped when the browser closes
 pub fn create_session(
     user_id: uuid::Uuid,
     headers: &HeaderMap,
     socket_addr: std::net::SocketAddr,
+    policy: &SessionPolicy,
+    persistent: bool,
+) -> (Session, ParsedSession, HeaderMap) {
+    let now = OffsetDateTime::now_utc();
+    build_session(user_id, headers, socket_addr, policy, persistent, now, now)
+}
+
+/// creates the session replacing `old`, keeping its sign-in and authentication times and
+/// "remember me" choice
+pub fn refresh_session(
+    user_id: uuid::Uuid,
+    old: &Session,
+    headers: &HeaderMap,
+    socket_addr: std::net::SocketAddr,
+    policy: &SessionPolicy,
+) -> (Session, ParsedSession, HeaderMap) {
+    let (login_at, authenticated_at) = (old.login_at, old.authenticated_at);
+    build_session(user_id, headers, socket_addr, policy, old.persistent, login_at, authenticated_at)
+}
+
+fn build_session(
+    user_id: uuid::Uuid,
+    headers: &HeaderMap,
+    socket_addr: std::net::SocketAddr,
+    policy: &SessionPolicy,
+    persistent: bool,
+    login_at: OffsetDateTime,
+    authenticated_at: OffsetDateTime,
 ) -> (Session, ParsedSession, HeaderMap) {
     let user_agent =
         headers.get(header::USER_AGENT).map(|v| v.to_str().unwrap_or_default().to_owned());
 
     let now = OffsetDateTime::now_utc();
-    let expires_at = now + Duration::from_secs(37 * 86400 + 60); // days * 86400 + secs
+    // a refreshed session never outlives the maximum lifetime of the sign-in
+    let expires_at = (now + Duration::from_secs(policy.lifetime(persistent)))
+        .min(login_at + Duration::from_secs(policy.max_lifetime));
     let uid = uuid::Uuid::new_v4();
-    let signed_uid = super::sign(&uid.to_string());
-
-    (
-        Session {
-            unsigned_ssid: uid,
-            user_agent,
-            ip_address: socket_addr.ip(),
-            created_at: now,
-            last_used: now,
-            expires_at,
-        },
-        ParsedSession { ssid: format!("{signed_uid}{uid}"), unsigned_ssid: uid, user_id },
-        HeaderMap::from_iter([
-            (
-                header::SET_COOKIE,
-                HeaderValue::from_str(&format!(
-                    "SSID={signed_uid}{uid}; HttpOnly; SameSite=Strict; Secure; Path=/; Expires={expires_at}",
-                ))
-                .unwrap(),
-            ),
-            (
-                header::SET_COOKIE,
-                HeaderValue::from_str(&format!(
-                    "UUID={user_id}; HttpOnly; SameSite=Strict; Secure; Path=/; Expires={expires_at}",
-                ))
-                .unwrap(),
-            ),
-        ]),
-    )
+    let ssid = format!("{}{uid}", super::sign(&uid.to_string()));
+    let session = Session {
+        unsigned_ssid: uid,
+        user_agent,
+        ip_address: socket_addr.ip(),
+        created_at: now,
+        last_used: now,
+        expires_at,
+        login_at,
+        authenticated_at,
+        persistent,
+    };
+
+    let set_cookie_headermap = HeaderMap::from_iter([
+        (header::SET_COOKIE, ssid_cookie(&ssid, &session)),
+        (header::SET_COOKIE, csrf_cookie(&session)),
+        (
+            header::SET_COOKIE,
+            HeaderValue::from_str(&format!(
+                "UUID={user_id}; HttpOnly; SameSite=Strict; Secure; Path=/{}",
+                cookie_expiry(&session)
+            ))
+            .unwrap(),
+        ),
+    ]);
+    (session, ParsedSession { ssid, unsigned_ssid: uid, user_id }, set_cookie_headermap)
+}
+
+// returns the `SSID` cookie holding the signed session id `ssid`
+fn ssid_cookie(ssid: &str, session: &Session) -> HeaderValue {
+    HeaderValue::from_str(&format!(
+        "SSID={ssid}; HttpOnly; SameSite=Strict; Secure; Path=/{}",
+        cookie_expiry(session)
+    ))
+    .unwrap()
+}
+
+// only "remember me" sessions survive closing the browser
+fn cookie_expiry(session: &Session) -> String {
+    if session.persistent { format!("; Expires={}", session.expires_at) } else { String::new() }
+}
+
+/// returns the CSRF token expected from the session `unsigned_ssid`
+///
+/// the token is derived from the session, so it can be checked without being stored
+pub fn csrf_token(unsigned_ssid: &uuid::Uuid) -> String {
+    crate::generate::signature(&format!("csrf:{unsigned_ssid}"))
+}
+
+/// returns the `CSRF` cookie, readable by scripts so that they can echo it in `X-CSRF-Token`
+pub fn csrf_cookie(session: &Session) -> HeaderValue {
+    HeaderValue::from_str(&format!(
+        "CSRF={}; SameSite=Strict; Secure; Path=/{}",
+        csrf_token(&session.unsigned_ssid),
+        cookie_expiry(session)
+    ))
+    .unwrap()
+}
+
+/// re-signs the id of `session` with the current signing key
+pub fn resign_session(session: &Session) -> HeaderValue {
+    let uid = session.unsigned_ssid.to_string();
+    ssid_cookie(&format!("{}{uid}", super::sign(&uid)), session)
 }
 
 pub fn expire_session() -> HeaderMap {
@@ -59,5 +126,9 @@ pub fn expire_session() -> HeaderMap {
             HeaderValue::from_str("UUID={}; HttpOnly; SameSite=Strict; Secure; Path=/; Max-Age=0")
                 .unwrap(),
         ),
+        (
+            header::SET_COOKIE,
+            HeaderValue::from_str("CSRF={}; SameSite=Strict; Secure; Path=/; Max-Age=0").unwrap(),
+        ),
     ])
 }
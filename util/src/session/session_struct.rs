@@ -6,6 +6,12 @@ pub struct Session {
     pub created_at: time::OffsetDateTime,
     pub last_used: time::OffsetDateTime,
     pub expires_at: time::OffsetDateTime,
+    /// when the user signed in, kept across auto-refreshes
+    pub login_at: time::OffsetDateTime,
+    /// when the user last proved who they are, by signing in or re-authenticating
+    pub authenticated_at: time::OffsetDateTime,
+    /// whether "remember me" was chosen while signing in
+    pub persistent: bool,
 }
 
 pub enum SessionStatus {
@@ -19,20 +25,49 @@ impl Session {
     // timestamp in seconds
     pub const MEM_CACHE_DURATION: u64 = 28800; // 8 hours
     pub const MAX_REFRESH_DURATION: u64 = 604800; // 7 days
+    pub const LAST_USED_INTERVAL: u64 = 300; // 5 minutes
+    pub const REAUTH_WINDOW: u64 = 600; // 10 minutes
+
+    /// moves `last_used` to now, returns false if it was updated within `LAST_USED_INTERVAL`
+    pub fn touch(&mut self) -> bool {
+        let now = time::OffsetDateTime::now_utc();
+        if (now - self.last_used).whole_seconds() < Self::LAST_USED_INTERVAL as i64 {
+            return false;
+        }
+        self.last_used = now;
+        true
+    }
+
+    /// whether the session was authenticated within `REAUTH_WINDOW`, as sensitive operations need
+    pub fn is_recently_authenticated(&self) -> bool {
+        let elapsed = time::OffsetDateTime::now_utc() - self.authenticated_at;
+        elapsed.whole_seconds() < Self::REAUTH_WINDOW as i64
+    }
 
     /// returns the timestamp difference of the session with current time
-    pub fn session_status(&self) -> SessionStatus {
-        let diff = (self.expires_at - time::OffsetDateTime::now_utc()).whole_seconds();
+    ///
+    /// idle and too old sessions are `Invalid` and can't be refreshed, only "remember me"
+    /// sessions are `Refreshable`
+    pub fn session_status(&self, policy: &super::SessionPolicy) -> SessionStatus {
+        let now = time::OffsetDateTime::now_utc();
+        let idle = (now - self.last_used).whole_seconds();
+        if (now - self.login_at).whole_seconds() >= policy.max_lifetime as i64
+            || (policy.idle_timeout != 0 && idle >= policy.idle_timeout as i64)
+        {
+            return SessionStatus::Invalid;
+        }
+
+        let diff = (self.expires_at - now).whole_seconds();
 
         if diff > 0 {
-            if diff > Self::MEM_CACHE_DURATION as i64 {
+            if diff > policy.refresh_within(self.persistent) as i64 {
                 SessionStatus::Valid(diff as u64)
             } else {
                 SessionStatus::Expiring(diff as u64)
             }
         } else {
             #[allow(clippy::collapsible_else_if)]
-            if -diff < Self::MAX_REFRESH_DURATION as i64 {
+            if self.persistent && -diff < Self::MAX_REFRESH_DURATION as i64 {
                 SessionStatus::Refreshable(diff as u64)
             } else {
                 SessionStatus::Invalid
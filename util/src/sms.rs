@@ -0,0 +1,109 @@
+use crate::{AppError, config::SmsConfig};
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{Arc, OnceLock},
+};
+
+/// resolves to the reason of the failure if the sms couldn't be delivered
+pub type SendFuture<'a> = Pin<Box<dyn Future<Output = Result<(), String>> + Send + 'a>>;
+
+/// delivers text messages, selected at startup with `SMS_PROVIDER`
+pub trait SmsProvider: Send + Sync {
+    fn send<'a>(&'a self, to: &'a str, body: &'a str) -> SendFuture<'a>;
+}
+
+/// sends messages through the Twilio messages api (`SMS_PROVIDER=twilio`)
+///
+/// any provider exposing the same api can be used by changing `TWILIO_API_URL`
+pub struct TwilioProvider {
+    client: reqwest::Client,
+    url: String,
+    account_sid: String,
+    auth_token: String,
+    from: String,
+}
+
+impl TwilioProvider {
+    pub fn new(config: &SmsConfig) -> Self {
+        let (api_url, account_sid) = (&config.twilio_api_url, &config.twilio_account_sid);
+        Self {
+            client: reqwest::Client::new(),
+            url: format!("{api_url}/2010-04-01/Accounts/{account_sid}/Messages.json"),
+            account_sid: account_sid.clone(),
+            auth_token: config.twilio_auth_token.clone(),
+            from: config.twilio_from_number.clone(),
+        }
+    }
+}
+
+impl SmsProvider for TwilioProvider {
+    fn send<'a>(&'a self, to: &'a str, body: &'a str) -> SendFuture<'a> {
+        Box::pin(async move {
+            let res = self
+                .client
+                .post(&self.url)
+                .basic_auth(&self.account_sid, Some(&self.auth_token))
+                .form(&[("To", to), ("From", self.from.as_str()), ("Body", body)])
+                .send()
+                .await
+                .map_err(|e| format!("Request failed: {e}"))?;
+            if !res.status().is_success() {
+                let status = res.status();
+                let text = res.text().await.unwrap_or_default();
+                return Err(format!("Provider responded with {status}: {text}"));
+            }
+            Ok(())
+        })
+    }
+}
+
+/// only logs the messages (`SMS_PROVIDER=log`)
+pub struct LogProvider;
+
+impl SmsProvider for LogProvider {
+    fn send<'a>(&'a self, to: &'a str, body: &'a str) -> SendFuture<'a> {
+        Box::pin(async move {
+            tracing::info!("[SMS Logged] To: {to}\n{body}");
+            Ok(())
+        })
+    }
+}
+
+// selected by `init` from `SMS_PROVIDER`, unless a provider was set before
+static PROVIDER: OnceLock<Arc<dyn SmsProvider>> = OnceLock::new();
+
+/// replaces the provider selected by `SMS_PROVIDER`
+///
+/// must be called before the first message is sent, returns false otherwise
+pub fn set_provider(provider: Arc<dyn SmsProvider>) -> bool {
+    PROVIDER.set(provider).is_ok()
+}
+
+/// builds the provider selected by `SMS_PROVIDER` (defaults to `log`), called by `crate::init` so
+/// that a provider that can't be built stops the server instead of losing every message
+pub(crate) fn init(config: &SmsConfig) -> Result<(), String> {
+    if PROVIDER.get().is_some() {
+        return Ok(());
+    }
+    let kind = config.provider.as_str();
+    let provider: Arc<dyn SmsProvider> = match kind {
+        "twilio" => Arc::new(TwilioProvider::new(config)),
+        "log" => Arc::new(LogProvider),
+        _ => return Err(format!("Unknown SMS_PROVIDER: {kind}")),
+    };
+    let _ = PROVIDER.set(provider);
+    Ok(())
+}
+
+/// sends `body` to the E.164 phone number `to`
+pub async fn send(to: &str, body: &str) -> Result<(), AppError> {
+    let provider = PROVIDER.get().ok_or_else(|| {
+        tracing::error!("No sms provider, util::init wasn't called");
+        AppError::ServerError
+    })?;
+    provider.send(to, body).await.map_err(|e| {
+        tracing::error!("Failed to send sms to {to}: {e}");
+        AppError::ServerError
+    })
+}
@@ -0,0 +1,196 @@
+/// kind of device a session was created from
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DeviceType {
+    Desktop,
+    Mobile,
+    Tablet,
+    Bot,
+    Unknown,
+}
+
+impl DeviceType {
+    pub fn get_str(&self) -> &'static str {
+        match self {
+            DeviceType::Desktop => "desktop",
+            DeviceType::Mobile => "mobile",
+            DeviceType::Tablet => "tablet",
+            DeviceType::Bot => "bot",
+            DeviceType::Unknown => "unknown",
+        }
+    }
+}
+
+/// browser, operating system and device type read from a `User-Agent` header
+#[derive(Clone, Debug, PartialEq)]
+pub struct UserAgent {
+    pub browser: Option<String>,
+    pub os: Option<String>,
+    pub device: DeviceType,
+}
+
+impl UserAgent {
+    /// compares the browser, operating system and device type, ignoring versions
+    pub fn same_device(&self, other: &UserAgent) -> bool {
+        without_version(&self.browser) == without_version(&other.browser)
+            && without_version(&self.os) == without_version(&other.os)
+            && self.device == other.device
+    }
+}
+
+// `Chrome 124` -> `Chrome`
+fn without_version(name: &Option<String>) -> Option<&str> {
+    name.as_deref().map(|v| v.trim_end_matches(|c: char| c.is_ascii_digit()).trim_end())
+}
+
+// checked in order, so browsers built on top of others come first
+const BROWSERS: [(&str, &str); 12] = [
+    ("Edg/", "Edge"),
+    ("EdgA/", "Edge"),
+    ("OPR/", "Opera"),
+    ("SamsungBrowser/", "Samsung Internet"),
+    ("YaBrowser/", "Yandex Browser"),
+    ("Vivaldi/", "Vivaldi"),
+    ("Firefox/", "Firefox"),
+    ("FxiOS/", "Firefox"),
+    ("CriOS/", "Chrome"),
+    ("Chromium/", "Chromium"),
+    ("Chrome/", "Chrome"),
+    ("Version/", "Safari"),
+];
+
+const BOTS: [&str; 6] = ["bot", "crawler", "spider", "curl/", "wget/", "python-requests"];
+
+/// parses a `User-Agent` header, parts that can't be recognised are left as `None`
+pub fn parse(user_agent: &str) -> UserAgent {
+    let lower = user_agent.to_lowercase();
+    if BOTS.iter().any(|b| lower.contains(b)) {
+        return UserAgent { browser: None, os: None, device: DeviceType::Bot };
+    }
+
+    let browser = BROWSERS.iter().find_map(|(token, name)| {
+        let version = major_version(user_agent, token)?;
+        // `Version/` is only meaningful with safari
+        if *token == "Version/" && !user_agent.contains("Safari/") {
+            return None;
+        }
+        Some(match version {
+            Some(v) => format!("{name} {v}"),
+            None => name.to_string(),
+        })
+    });
+
+    let os = if user_agent.contains("Windows NT") {
+        Some("Windows".to_string())
+    } else if user_agent.contains("iPhone") || user_agent.contains("iPad") {
+        Some(os_version(user_agent, "OS ", "iOS"))
+    } else if user_agent.contains("Android") {
+        Some(os_version(user_agent, "Android ", "Android"))
+    } else if user_agent.contains("CrOS") {
+        Some("ChromeOS".to_string())
+    } else if user_agent.contains("Mac OS X") || user_agent.contains("Macintosh") {
+        Some("macOS".to_string())
+    } else if user_agent.contains("Linux") {
+        Some("Linux".to_string())
+    } else {
+        None
+    };
+
+    let device = if user_agent.contains("iPad") || user_agent.contains("Tablet") {
+        DeviceType::Tablet
+    } else if user_agent.contains("Android") {
+        // android tablets don't send the `Mobile` token
+        if user_agent.contains("Mobile") { DeviceType::Mobile } else { DeviceType::Tablet }
+    } else if user_agent.contains("Mobile") || user_agent.contains("iPhone") {
+        DeviceType::Mobile
+    } else if os.is_some() {
+        DeviceType::Desktop
+    } else {
+        DeviceType::Unknown
+    };
+
+    UserAgent { browser, os, device }
+}
+
+// returns Some(None) if `token` is present without a readable version
+fn major_version(user_agent: &str, token: &str) -> Option<Option<String>> {
+    let start = user_agent.find(token)? + token.len();
+    let major: String = user_agent[start..].chars().take_while(|c| c.is_ascii_digit()).collect();
+    Some((!major.is_empty()).then_some(major))
+}
+
+// `iPhone OS 17_4` -> `iOS 17`, `Android 14;` -> `Android 14`
+fn os_version(user_agent: &str, token: &str, name: &str) -> String {
+    match major_version(user_agent, token).flatten() {
+        Some(v) => format!("{name} {v}"),
+        None => name.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    macro_rules! user_agent_test {
+        ($($name:ident: $exp:expr,)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    let (haystack, browser, os, device) = $exp;
+                    let parsed = parse(haystack);
+                    assert_eq!(parsed.browser.as_deref(), browser);
+                    assert_eq!(parsed.os.as_deref(), os);
+                    assert_eq!(parsed.device, device);
+                }
+            )*
+        };
+    }
+
+    #[test]
+    fn same_device_ignores_versions() {
+        let old = parse("Mozilla/5.0 (Windows NT 10.0; Win64; x64) Chrome/123.0.0.0 Safari/537.36");
+        let new = parse("Mozilla/5.0 (Windows NT 10.0; Win64; x64) Chrome/124.0.0.0 Safari/537.36");
+        let other = parse("Mozilla/5.0 (X11; Linux x86_64; rv:125.0) Gecko/20100101 Firefox/125.0");
+        assert!(old.same_device(&new));
+        assert!(!old.same_device(&other));
+    }
+
+    user_agent_test! {
+        chrome_windows: (
+            "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36",
+            Some("Chrome 124"), Some("Windows"), DeviceType::Desktop
+        ),
+        edge_windows: (
+            "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36 Edg/124.0.2478.51",
+            Some("Edge 124"), Some("Windows"), DeviceType::Desktop
+        ),
+        firefox_linux: (
+            "Mozilla/5.0 (X11; Linux x86_64; rv:125.0) Gecko/20100101 Firefox/125.0",
+            Some("Firefox 125"), Some("Linux"), DeviceType::Desktop
+        ),
+        safari_iphone: (
+            "Mozilla/5.0 (iPhone; CPU iPhone OS 17_4 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.4 Mobile/15E148 Safari/604.1",
+            Some("Safari 17"), Some("iOS 17"), DeviceType::Mobile
+        ),
+        safari_ipad: (
+            "Mozilla/5.0 (iPad; CPU OS 16_6 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/16.6 Mobile/15E148 Safari/604.1",
+            Some("Safari 16"), Some("iOS 16"), DeviceType::Tablet
+        ),
+        chrome_android: (
+            "Mozilla/5.0 (Linux; Android 14; Pixel 8) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Mobile Safari/537.36",
+            Some("Chrome 124"), Some("Android 14"), DeviceType::Mobile
+        ),
+        android_tablet: (
+            "Mozilla/5.0 (Linux; Android 13; SM-X700) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/123.0.0.0 Safari/537.36",
+            Some("Chrome 123"), Some("Android 13"), DeviceType::Tablet
+        ),
+        safari_mac: (
+            "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.4 Safari/605.1.15",
+            Some("Safari 17"), Some("macOS"), DeviceType::Desktop
+        ),
+        googlebot: (
+            "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)",
+            None, None, DeviceType::Bot
+        ),
+        unknown: ("Mozilla Firefox", None, None, DeviceType::Unknown),
+    }
+}
@@ -0,0 +1,23 @@
+use util::{config::Config, mail::Template};
+
+#[tokio::test]
+async fn memory_transport_test() {
+    let config = Config::from_toml(
+        r#"
+        [mail]
+        transport = "memory"
+        noreply_email = "noreply@example.com"
+        "#,
+    )
+    .unwrap();
+    util::init(&config).unwrap();
+
+    let to = "someone@example.com";
+    util::mail::send_template(to.to_string(), Template::Otp, None, &[("otp", "482910")])
+        .await
+        .unwrap();
+
+    let memory = util::mail::memory().expect("the memory transport is selected");
+    assert_eq!(memory.sent_to(to).len(), 1);
+    assert_eq!(memory.last_otp(to).as_deref(), Some("482910"));
+}